@@ -0,0 +1,237 @@
+use datex_core::{
+    global::{
+        dxb_block::DXBBlock,
+        protocol_structures::{
+            block_header::{BlockHeader, FlagsAndTimestamp},
+            routing_header::SignatureType,
+        },
+    },
+    values::core_values::endpoint::Endpoint,
+};
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    block::{
+        parse_block_type, parse_encryption_type, parse_signature_type,
+        signing::SIGNATURE_LENGTH,
+    },
+    js_utils::parse_endpoint,
+    utils::errors::{DatexJsError, JsErrorKind},
+};
+
+// largest integer that is exactly representable as a JS number
+const MAX_SAFE_INTEGER: f64 = 9007199254740991.0;
+
+/// Builds a DXB block with arbitrary routing and block header fields.
+/// By default, the block is an unsigned request that ends its section
+/// and context, all other fields are zero.
+#[wasm_bindgen]
+pub struct BlockBuilder {
+    block: DXBBlock,
+    receivers: Vec<Endpoint>,
+}
+
+impl Default for BlockBuilder {
+    fn default() -> Self {
+        BlockBuilder {
+            block: DXBBlock {
+                block_header: BlockHeader {
+                    flags_and_timestamp: FlagsAndTimestamp::default()
+                        .with_is_end_of_context(true)
+                        .with_is_end_of_section(true),
+                    ..BlockHeader::default()
+                },
+                ..Default::default()
+            },
+            receivers: vec![],
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl BlockBuilder {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> BlockBuilder {
+        BlockBuilder::default()
+    }
+
+    /// Sets the DXB body of the block
+    pub fn set_body(&mut self, body: Vec<u8>) {
+        self.block.body = body;
+    }
+
+    /// Sets the sender endpoint, e.g. "@example"
+    pub fn set_sender(&mut self, sender: &str) -> Result<(), DatexJsError> {
+        self.block.routing_header.sender = parse_endpoint(sender)?;
+        Ok(())
+    }
+
+    /// Sets the receiver endpoints of the block
+    pub fn set_receivers(
+        &mut self,
+        receivers: Vec<String>,
+    ) -> Result<(), DatexJsError> {
+        self.receivers = receivers
+            .iter()
+            .map(|receiver| parse_endpoint(receiver))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(())
+    }
+
+    /// Sets the time to live (maximum number of hops) of the block
+    pub fn set_ttl(&mut self, ttl: u8) {
+        self.block.routing_header.ttl = ttl;
+    }
+
+    pub fn set_context_id(&mut self, context_id: u32) {
+        self.block.block_header.context_id = context_id;
+    }
+
+    pub fn set_scope_id(&mut self, scope_id: u32) {
+        self.block.block_header.scope_id = scope_id;
+    }
+
+    pub fn set_section_index(&mut self, section_index: u16) {
+        self.block.block_header.section_index = section_index;
+    }
+
+    pub fn set_block_number(&mut self, block_number: u16) {
+        self.block.block_header.block_number = block_number;
+    }
+
+    /// Sets the block type, one of "request", "response", "hello",
+    /// "trace" or "trace_back"
+    pub fn set_block_type(
+        &mut self,
+        block_type: &str,
+    ) -> Result<(), DatexJsError> {
+        let block_type = parse_block_type(block_type)?;
+        self.update_flags(|flags| flags.with_block_type(block_type));
+        Ok(())
+    }
+
+    pub fn set_allow_execution(&mut self, allow_execution: bool) {
+        self.update_flags(|flags| flags.with_allow_execution(allow_execution));
+    }
+
+    pub fn set_end_of_section(&mut self, end_of_section: bool) {
+        self.update_flags(|flags| flags.with_is_end_of_section(end_of_section));
+    }
+
+    pub fn set_end_of_context(&mut self, end_of_context: bool) {
+        self.update_flags(|flags| flags.with_is_end_of_context(end_of_context));
+    }
+
+    /// Sets the creation timestamp in milliseconds since the unix epoch.
+    /// Fails if the timestamp is not a non-negative integer that fits into
+    /// the timestamp field of the block header
+    pub fn set_timestamp(
+        &mut self,
+        timestamp: f64,
+    ) -> Result<(), DatexJsError> {
+        let invalid_timestamp = || {
+            DatexJsError::new(
+                JsErrorKind::Conversion,
+                "InvalidTimestamp",
+                format!("Invalid block timestamp {timestamp}"),
+            )
+        };
+        if !timestamp.is_finite()
+            || timestamp < 0.0
+            || timestamp.fract() != 0.0
+            || timestamp > MAX_SAFE_INTEGER
+        {
+            return Err(invalid_timestamp());
+        }
+        let header = &mut self.block.block_header;
+        header.flags_and_timestamp = header
+            .flags_and_timestamp
+            .with_creation_timestamp_checked(timestamp as u64)
+            .map_err(|_| invalid_timestamp())?;
+        Ok(())
+    }
+
+    /// Sets the signature type flag of the routing header, one of "none",
+    /// "unencrypted" or "encrypted". The flag is overwritten by
+    /// `set_signature`, this allows constructing blocks with a
+    /// mismatching signature flag
+    pub fn set_signature_type(
+        &mut self,
+        signature_type: &str,
+    ) -> Result<(), DatexJsError> {
+        let signature_type = parse_signature_type(signature_type)?;
+        self.block
+            .routing_header
+            .flags
+            .set_signature_type(signature_type);
+        Ok(())
+    }
+
+    /// Sets the encryption type flag of the routing header, one of "none"
+    /// or "encrypted". The body is not encrypted by the builder
+    pub fn set_encryption_type(
+        &mut self,
+        encryption_type: &str,
+    ) -> Result<(), DatexJsError> {
+        let encryption_type = parse_encryption_type(encryption_type)?;
+        self.block
+            .routing_header
+            .flags
+            .set_encryption_type(encryption_type);
+        Ok(())
+    }
+
+    pub fn set_bounce_back(&mut self, bounce_back: bool) {
+        self.block
+            .routing_header
+            .flags
+            .set_is_bounce_back(bounce_back);
+    }
+
+    /// Attaches an unencrypted Ed25519 signature to the block,
+    /// or removes the signature if null is given
+    pub fn set_signature(
+        &mut self,
+        signature: Option<Vec<u8>>,
+    ) -> Result<(), DatexJsError> {
+        let signature_type = match &signature {
+            Some(signature) if signature.len() != SIGNATURE_LENGTH => {
+                return Err(DatexJsError::new(
+                    JsErrorKind::Conversion,
+                    "InvalidSignature",
+                    format!(
+                        "Expected a signature of {SIGNATURE_LENGTH} bytes, got {} bytes",
+                        signature.len()
+                    ),
+                ));
+            }
+            Some(_) => SignatureType::Unencrypted,
+            None => SignatureType::None,
+        };
+        self.block
+            .routing_header
+            .flags
+            .set_signature_type(signature_type);
+        self.block.signature = signature;
+        Ok(())
+    }
+
+    /// Serializes the block, the block size and receiver
+    /// fields of the routing header are calculated automatically
+    pub fn build(&self) -> Vec<u8> {
+        let mut block = self.block.clone();
+        block.recalculate_struct();
+        block.set_receivers(self.receivers.clone());
+        block.to_bytes()
+    }
+}
+
+impl BlockBuilder {
+    fn update_flags(
+        &mut self,
+        update: impl FnOnce(FlagsAndTimestamp) -> FlagsAndTimestamp,
+    ) {
+        let header = &mut self.block.block_header;
+        header.flags_and_timestamp = update(header.flags_and_timestamp);
+    }
+}
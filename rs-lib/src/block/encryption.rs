@@ -0,0 +1,396 @@
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
+
+use datex_core::{
+    crypto::CryptoImpl,
+    global::{
+        dxb_block::DXBBlock,
+        protocol_structures::{
+            block_header::{BlockHeader, BlockType, FlagsAndTimestamp},
+            routing_header::{EncryptionType, SignatureType},
+        },
+    },
+    values::core_values::endpoint::Endpoint,
+};
+use datex_crypto_facade::crypto::Crypto;
+use futures::{FutureExt, channel::oneshot, select};
+use gloo_timers::future::TimeoutFuture;
+use log::{error, warn};
+use wasm_bindgen::JsValue;
+use web_sys::js_sys::{Date, Function};
+
+use crate::{
+    block::{parse_block_bytes, signing::signed_payload},
+    crypto::{MAC_LENGTH, crypto_error, hmac_sha256, mac_eq, random_array},
+    js_utils::js_object,
+    keystore::KeyPair,
+    utils::errors::{DatexJsError, JsErrorKind},
+};
+
+const IV_LENGTH: usize = 16;
+/// HKDF salt for deriving the MAC key of a peer from its AES key
+const MAC_KEY_SALT: &[u8] = b"DXBE-MAC";
+/// Magic bytes at the start of the body of a key announcement block
+const KEY_ANNOUNCEMENT_MAGIC: &[u8; 4] = b"DXKX";
+/// Time to wait for the public key of an endpoint that requires encryption
+/// before an outgoing block is dropped
+pub const KEY_EXCHANGE_TIMEOUT_MS: u32 = 10_000;
+
+/// Peer keys and encryption requirements of a runtime
+#[derive(Default)]
+pub struct BlockEncryption {
+    // X25519 public keys of known endpoints, by endpoint name
+    peer_keys: HashMap<String, Vec<u8>>,
+    // endpoints that must only exchange encrypted blocks with this runtime
+    pub required: HashSet<String>,
+    // encrypt the blocks for all endpoints whose key is known,
+    // not only for the endpoints that require encryption
+    pub enabled: bool,
+    // endpoints the own public key was announced to
+    pub announced: HashSet<String>,
+    // senders that are notified when the key of an endpoint is set
+    key_waiters: HashMap<String, Vec<oneshot::Sender<()>>>,
+    // called with { endpoint, direction, socket } for unencrypted blocks
+    // that are exchanged with an endpoint that requires encryption
+    pub on_unencrypted_block: Option<Function>,
+}
+
+impl BlockEncryption {
+    pub fn peer_key(&self, endpoint: &str) -> Option<&Vec<u8>> {
+        self.peer_keys.get(endpoint)
+    }
+
+    pub fn peer_keys(&self) -> impl Iterator<Item = (&String, &Vec<u8>)> {
+        self.peer_keys.iter()
+    }
+
+    /// Sets the public key of an endpoint and wakes up all outgoing
+    /// blocks that are waiting for the key
+    pub fn set_peer_key(&mut self, endpoint: String, public_key: Vec<u8>) {
+        for waiter in self.key_waiters.remove(&endpoint).unwrap_or_default() {
+            let _ = waiter.send(());
+        }
+        self.peer_keys.insert(endpoint, public_key);
+    }
+
+    pub fn remove_peer_key(&mut self, endpoint: &str) {
+        self.peer_keys.remove(endpoint);
+        // the key must be announced again to get the new key of the peer
+        self.announced.remove(endpoint);
+    }
+
+    /// Returns true if blocks exchanged with the endpoint are encrypted
+    pub fn encrypts_for(&self, endpoint: &str) -> bool {
+        self.enabled || self.required.contains(endpoint)
+    }
+}
+
+pub type BlockEncryptionSlot = Rc<RefCell<BlockEncryption>>;
+
+/// Derives the AES and MAC keys for the communication with a peer.
+/// Both endpoints derive the same keys from the X25519 shared secret,
+/// the salt is built from the endpoint names in lexical order.
+async fn derive_peer_keys(
+    own_endpoint: &str,
+    own_key: &KeyPair,
+    peer_endpoint: &str,
+    peer_public_key: &[u8],
+) -> Result<([u8; 32], [u8; 32]), DatexJsError> {
+    let secret =
+        CryptoImpl::derive_x25519(&own_key.private_key, peer_public_key)
+            .await
+            .map_err(crypto_error)?;
+    let mut endpoints = [own_endpoint, peer_endpoint];
+    endpoints.sort();
+    let salt = endpoints.join("|");
+    let key = CryptoImpl::hkdf_sha256(&secret, salt.as_bytes())
+        .await
+        .map_err(crypto_error)?;
+    let mac_key = CryptoImpl::hkdf_sha256(&key, MAC_KEY_SALT)
+        .await
+        .map_err(crypto_error)?;
+    Ok((key, mac_key))
+}
+
+pub fn is_encrypted(block: &DXBBlock) -> bool {
+    block.routing_header.flags.encryption_type() != EncryptionType::None
+}
+
+/// Returns true for blocks that carry data (requests and responses),
+/// only these blocks are encrypted
+pub fn is_data_block(block: &DXBBlock) -> bool {
+    matches!(
+        block.block_header.flags_and_timestamp.block_type(),
+        BlockType::Request | BlockType::Response
+    )
+}
+
+/// Bytes authenticated by the MAC of an encrypted block: the signed payload
+/// with the encrypted body. The block is signed after it was encrypted, so
+/// the signature type is not authenticated.
+async fn block_mac(
+    block: &DXBBlock,
+    mac_key: &[u8; 32],
+) -> Result<[u8; 32], DatexJsError> {
+    let mut block = block.clone();
+    block
+        .routing_header
+        .flags
+        .set_signature_type(SignatureType::None);
+    hmac_sha256(mac_key, &signed_payload(&block)).await
+}
+
+/// Encrypts the body of a block for its single receiver.
+/// The encrypted body is prefixed with the random IV and followed by an
+/// HMAC over the header, IV and ciphertext. The block is marked as
+/// encrypted in the routing header.
+pub async fn encrypt(
+    block: &mut DXBBlock,
+    own_endpoint: &str,
+    own_key: &KeyPair,
+    peer_key: &[u8],
+) -> Result<(), DatexJsError> {
+    if is_encrypted(block) {
+        return Err(DatexJsError::new(
+            JsErrorKind::Crypto,
+            "AlreadyEncrypted",
+            "The block is already encrypted",
+        ));
+    }
+    let receiver = single_receiver(block)?;
+    let (key, mac_key) =
+        derive_peer_keys(own_endpoint, own_key, &receiver, peer_key).await?;
+
+    let iv = random_array::<IV_LENGTH>()?;
+    let encrypted = CryptoImpl::aes_ctr_encrypt(&key, &iv, &block.body)
+        .await
+        .map_err(crypto_error)?;
+    block.body = [iv.as_slice(), &encrypted].concat();
+    block
+        .routing_header
+        .flags
+        .set_encryption_type(EncryptionType::Encrypted);
+    let mac = block_mac(block, &mac_key).await?;
+    block.body.extend_from_slice(&mac);
+    block.recalculate_struct();
+    Ok(())
+}
+
+/// Verifies the MAC and decrypts the body of a block that was encrypted
+/// with `encrypt` by its sender
+pub async fn decrypt(
+    block: &mut DXBBlock,
+    own_endpoint: &str,
+    own_key: &KeyPair,
+    peer_key: &[u8],
+) -> Result<(), DatexJsError> {
+    if !is_encrypted(block) {
+        return Err(DatexJsError::new(
+            JsErrorKind::Crypto,
+            "NotEncrypted",
+            "The block is not encrypted",
+        ));
+    }
+    if block.body.len() < IV_LENGTH + MAC_LENGTH {
+        return Err(DatexJsError::new(
+            JsErrorKind::Crypto,
+            "InvalidEncryptedBody",
+            "The encrypted body is shorter than the IV and MAC",
+        ));
+    }
+    let sender = block.routing_header.sender.to_string();
+    let (key, mac_key) =
+        derive_peer_keys(own_endpoint, own_key, &sender, peer_key).await?;
+
+    let mac = block.body.split_off(block.body.len() - MAC_LENGTH);
+    if !mac_eq(&mac, &block_mac(block, &mac_key).await?) {
+        return Err(DatexJsError::new(
+            JsErrorKind::Crypto,
+            "InvalidMac",
+            format!("The encrypted block from {sender} was modified"),
+        ));
+    }
+    let (iv, encrypted) = block.body.split_at(IV_LENGTH);
+    let iv: [u8; IV_LENGTH] = iv.try_into().unwrap();
+    block.body = CryptoImpl::aes_ctr_decrypt(&key, &iv, encrypted)
+        .await
+        .map_err(crypto_error)?;
+    block
+        .routing_header
+        .flags
+        .set_encryption_type(EncryptionType::None);
+    block.recalculate_struct();
+    Ok(())
+}
+
+/// Encrypts the body of a serialized block for its single receiver
+pub async fn encrypt_block(
+    bytes: &[u8],
+    own_endpoint: &str,
+    own_key: &KeyPair,
+    encryption: &BlockEncryptionSlot,
+) -> Result<Vec<u8>, DatexJsError> {
+    let mut block = parse_block_bytes(bytes)?;
+    let receiver = single_receiver(&block)?;
+    let peer_key = known_peer_key(encryption, &receiver)?;
+    encrypt(&mut block, own_endpoint, own_key, &peer_key).await?;
+    Ok(block.to_bytes())
+}
+
+/// Decrypts the body of a serialized block that was encrypted
+/// with `encrypt_block` by its sender
+pub async fn decrypt_block(
+    bytes: &[u8],
+    own_endpoint: &str,
+    own_key: &KeyPair,
+    encryption: &BlockEncryptionSlot,
+) -> Result<Vec<u8>, DatexJsError> {
+    let mut block = parse_block_bytes(bytes)?;
+    let sender = block.routing_header.sender.to_string();
+    let peer_key = known_peer_key(encryption, &sender)?;
+    decrypt(&mut block, own_endpoint, own_key, &peer_key).await?;
+    Ok(block.to_bytes())
+}
+
+/// Returns the receiver of a block that has exactly one receiver
+pub fn single_receiver(block: &DXBBlock) -> Result<String, DatexJsError> {
+    let receivers = block.receiver_endpoints();
+    let [receiver] = receivers.as_slice() else {
+        return Err(DatexJsError::new(
+            JsErrorKind::Crypto,
+            "InvalidReceivers",
+            format!(
+                "End-to-end encrypted blocks must have exactly one receiver, found {}",
+                receivers.len()
+            ),
+        ));
+    };
+    Ok(receiver.to_string())
+}
+
+pub fn known_peer_key(
+    encryption: &BlockEncryptionSlot,
+    endpoint: &str,
+) -> Result<Vec<u8>, DatexJsError> {
+    encryption
+        .borrow()
+        .peer_key(endpoint)
+        .cloned()
+        .ok_or_else(|| {
+            DatexJsError::new(
+                JsErrorKind::Crypto,
+                "MissingPeerKey",
+                format!("No encryption key is known for {endpoint}"),
+            )
+        })
+}
+
+/// Waits until the public key of an endpoint is set or the key exchange
+/// timed out, returns the key if it is known afterwards
+pub async fn wait_for_peer_key(
+    encryption: &BlockEncryptionSlot,
+    endpoint: &str,
+) -> Option<Vec<u8>> {
+    let (sender, receiver) = oneshot::channel();
+    encryption
+        .borrow_mut()
+        .key_waiters
+        .entry(endpoint.to_string())
+        .or_default()
+        .push(sender);
+    let receiver = receiver.fuse();
+    let timeout = TimeoutFuture::new(KEY_EXCHANGE_TIMEOUT_MS).fuse();
+    futures::pin_mut!(receiver, timeout);
+    select! {
+        _ = receiver => {},
+        _ = timeout => {},
+    }
+    let mut encryption = encryption.borrow_mut();
+    let key = encryption.peer_key(endpoint).cloned();
+    if key.is_none() {
+        // the announcement is repeated for the next block
+        encryption.announced.remove(endpoint);
+    }
+    key
+}
+
+/// Builds a block that announces the X25519 public key of the sender to the
+/// receiver. If `reply` is set, the receiver answers with its own key.
+/// Format of the body: magic, reply flag, public key
+pub fn key_announcement(
+    sender: Endpoint,
+    receiver: Endpoint,
+    public_key: &[u8],
+    reply: bool,
+) -> Result<DXBBlock, DatexJsError> {
+    let flags_and_timestamp = FlagsAndTimestamp::default()
+        .with_block_type(BlockType::Hello)
+        .with_is_end_of_context(true)
+        .with_is_end_of_section(true)
+        .with_creation_timestamp_checked(Date::now() as u64)
+        .map_err(|_| {
+            DatexJsError::new(
+                JsErrorKind::Crypto,
+                "InvalidTimestamp",
+                "The current time does not fit into a block timestamp",
+            )
+        })?;
+    let mut block = DXBBlock {
+        block_header: BlockHeader {
+            flags_and_timestamp,
+            // every announcement has its own context, so that it is not
+            // rejected as a replay of a previous announcement
+            context_id: u32::from_le_bytes(random_array()?),
+            ..BlockHeader::default()
+        },
+        body: [
+            KEY_ANNOUNCEMENT_MAGIC.as_slice(),
+            &[reply as u8],
+            public_key,
+        ]
+        .concat(),
+        ..Default::default()
+    };
+    block.routing_header.sender = sender;
+    block.recalculate_struct();
+    block.set_receivers(vec![receiver]);
+    Ok(block)
+}
+
+/// Returns the announced public key and the reply flag if the block
+/// is a key announcement
+pub fn parse_key_announcement(block: &DXBBlock) -> Option<(Vec<u8>, bool)> {
+    if block.block_header.flags_and_timestamp.block_type() != BlockType::Hello {
+        return None;
+    }
+    let payload = block.body.strip_prefix(KEY_ANNOUNCEMENT_MAGIC.as_slice())?;
+    let (reply, public_key) = payload.split_first()?;
+    Some((public_key.to_vec(), *reply != 0))
+}
+
+/// Logs an unencrypted block that is exchanged with an endpoint that
+/// requires encryption and passes it to the `on_unencrypted_block` callback
+pub fn report_unencrypted_block(
+    encryption: &BlockEncryptionSlot,
+    endpoint: &str,
+    direction: &str,
+    socket: Option<&str>,
+) {
+    let callback = encryption.borrow().on_unencrypted_block.clone();
+    warn!(
+        "Dropping unencrypted {direction} block for {endpoint}, which requires encryption"
+    );
+    if let Some(callback) = callback {
+        let block = js_object(vec![
+            ("endpoint", JsValue::from(endpoint)),
+            ("direction", JsValue::from(direction)),
+            ("socket", socket.map(JsValue::from).unwrap_or(JsValue::NULL)),
+        ]);
+        if let Err(e) = callback.call1(&JsValue::NULL, &block) {
+            error!("Error in unencrypted block callback: {e:?}");
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use datex_core::global::{
+    dxb_block::DXBBlock,
+    protocol_structures::{
+        block_header::BlockType,
+        routing_header::{EncryptionType, SignatureType},
+    },
+};
+
+use crate::utils::errors::{DatexJsError, JsErrorKind};
+
+pub mod builder;
+pub mod encryption;
+pub mod parser;
+pub mod processor;
+pub mod signing;
+
+/// Parses a block type name, e.g. "request" or "trace_back"
+pub fn parse_block_type(block_type: &str) -> Result<BlockType, DatexJsError> {
+    match block_type.to_lowercase().as_str() {
+        "request" => Ok(BlockType::Request),
+        "response" => Ok(BlockType::Response),
+        "hello" => Ok(BlockType::Hello),
+        "trace" => Ok(BlockType::Trace),
+        "trace_back" => Ok(BlockType::TraceBack),
+        _ => Err(DatexJsError::new(
+            JsErrorKind::Conversion,
+            "InvalidBlockType",
+            format!(
+                "Invalid block type '{block_type}', expected one of: request, response, hello, trace, trace_back"
+            ),
+        )),
+    }
+}
+
+/// Name of a block type as accepted by `parse_block_type`
+pub fn block_type_name(block_type: BlockType) -> &'static str {
+    match block_type {
+        BlockType::Request => "request",
+        BlockType::Response => "response",
+        BlockType::Hello => "hello",
+        BlockType::Trace => "trace",
+        BlockType::TraceBack => "trace_back",
+    }
+}
+
+/// Parses a signature type name, one of "none", "unencrypted" or "encrypted"
+pub fn parse_signature_type(
+    signature_type: &str,
+) -> Result<SignatureType, DatexJsError> {
+    match signature_type.to_lowercase().as_str() {
+        "none" => Ok(SignatureType::None),
+        "unencrypted" => Ok(SignatureType::Unencrypted),
+        "encrypted" => Ok(SignatureType::Encrypted),
+        _ => Err(DatexJsError::new(
+            JsErrorKind::Conversion,
+            "InvalidSignatureType",
+            format!(
+                "Invalid signature type '{signature_type}', expected one of: none, unencrypted, encrypted"
+            ),
+        )),
+    }
+}
+
+/// Name of a signature type as accepted by `parse_signature_type`
+pub fn signature_type_name(signature_type: SignatureType) -> &'static str {
+    match signature_type {
+        SignatureType::None => "none",
+        SignatureType::Unencrypted => "unencrypted",
+        SignatureType::Encrypted => "encrypted",
+    }
+}
+
+/// Parses an encryption type name, one of "none" or "encrypted"
+pub fn parse_encryption_type(
+    encryption_type: &str,
+) -> Result<EncryptionType, DatexJsError> {
+    match encryption_type.to_lowercase().as_str() {
+        "none" => Ok(EncryptionType::None),
+        "encrypted" => Ok(EncryptionType::Encrypted),
+        _ => Err(DatexJsError::new(
+            JsErrorKind::Conversion,
+            "InvalidEncryptionType",
+            format!(
+                "Invalid encryption type '{encryption_type}', expected one of: none, encrypted"
+            ),
+        )),
+    }
+}
+
+/// Name of an encryption type as accepted by `parse_encryption_type`
+pub fn encryption_type_name(encryption_type: EncryptionType) -> &'static str {
+    match encryption_type {
+        EncryptionType::None => "none",
+        EncryptionType::Encrypted => "encrypted",
+    }
+}
+
+/// Deserializes raw block bytes
+pub fn parse_block_bytes(bytes: &[u8]) -> Result<DXBBlock, DatexJsError> {
+    DXBBlock::from_bytes(bytes).map_err(|e| {
+        DatexJsError::new(
+            JsErrorKind::Conversion,
+            "InvalidBlock",
+            format!("Failed to parse block: {e:?}"),
+        )
+    })
+}
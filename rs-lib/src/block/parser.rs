@@ -0,0 +1,141 @@
+use datex_core::{
+    decompiler::{DecompileOptions, decompile_body},
+    global::protocol_structures::routing_header::{
+        EncryptionType, SignatureType,
+    },
+};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsValue;
+use web_sys::js_sys::Uint8Array;
+
+use crate::{
+    block::{
+        block_type_name, encryption_type_name, parse_block_bytes,
+        signature_type_name,
+    },
+    utils::errors::{DatexJsError, decompiler_error},
+};
+
+#[derive(Default, Deserialize)]
+pub struct ParseBlockOptions {
+    // if true, the body is decompiled (only for unencrypted blocks)
+    #[serde(default)]
+    pub decompile: bool,
+    #[serde(default)]
+    pub decompile_options: Option<DecompileOptions>,
+}
+
+#[derive(Serialize)]
+pub struct RoutingHeaderDescription {
+    pub version: u8,
+    pub block_size: u32,
+    pub ttl: u8,
+    pub sender: String,
+    pub receivers: Vec<String>,
+    // "none", "unencrypted" or "encrypted"
+    pub signature_type: &'static str,
+    // "none" or "encrypted"
+    pub encryption_type: &'static str,
+    pub is_bounce_back: bool,
+}
+
+#[derive(Serialize)]
+pub struct BlockHeaderDescription {
+    pub context_id: u32,
+    pub scope_id: u32,
+    pub section_index: u16,
+    pub block_number: u16,
+    // same names as accepted by BlockBuilder.set_block_type
+    pub block_type: &'static str,
+    pub allow_execution: bool,
+    pub is_end_of_section: bool,
+    pub is_end_of_context: bool,
+    pub creation_timestamp: u64,
+}
+
+#[derive(Serialize)]
+pub struct BlockDescription {
+    pub routing_header: RoutingHeaderDescription,
+    pub block_header: BlockHeaderDescription,
+    pub is_signed: bool,
+    #[serde(with = "serde_wasm_bindgen::preserve")]
+    pub signature: JsValue,
+    pub is_encrypted: bool,
+    pub body_size: usize,
+    #[serde(with = "serde_wasm_bindgen::preserve")]
+    pub body: JsValue,
+    // decompiled body, only set if requested and the body is not encrypted
+    pub decompiled_body: Option<String>,
+    // DatexError if the body could not be decompiled, otherwise null
+    #[serde(with = "serde_wasm_bindgen::preserve")]
+    pub decompiler_error: JsValue,
+}
+
+/// Parses raw block bytes (e.g. from a block interceptor) into a description
+/// of the routing header, block header, signature and body
+pub fn parse_block(
+    bytes: &[u8],
+    options: ParseBlockOptions,
+) -> Result<BlockDescription, DatexJsError> {
+    let block = parse_block_bytes(bytes)?;
+
+    let routing_header = &block.routing_header;
+    let flags = &routing_header.flags;
+    let is_signed = flags.signature_type() != SignatureType::None;
+    let is_encrypted = flags.encryption_type() != EncryptionType::None;
+
+    let header = &block.block_header;
+    let header_flags = &header.flags_and_timestamp;
+
+    let (decompiled_body, decompiler_error) =
+        if options.decompile && !is_encrypted {
+            match decompile_body(
+                &block.body,
+                options.decompile_options.unwrap_or_default(),
+            ) {
+                Ok(source) => (Some(source), JsValue::NULL),
+                Err(e) => (None, decompiler_error(e).to_js_value()),
+            }
+        } else {
+            (None, JsValue::NULL)
+        };
+
+    Ok(BlockDescription {
+        routing_header: RoutingHeaderDescription {
+            version: routing_header.version,
+            block_size: routing_header.block_size as u32,
+            ttl: routing_header.ttl,
+            sender: routing_header.sender.to_string(),
+            receivers: block
+                .receiver_endpoints()
+                .iter()
+                .map(|endpoint| endpoint.to_string())
+                .collect(),
+            signature_type: signature_type_name(flags.signature_type()),
+            encryption_type: encryption_type_name(flags.encryption_type()),
+            is_bounce_back: flags.is_bounce_back(),
+        },
+        block_header: BlockHeaderDescription {
+            context_id: header.context_id,
+            scope_id: header.scope_id,
+            section_index: header.section_index,
+            block_number: header.block_number,
+            block_type: block_type_name(header_flags.block_type()),
+            allow_execution: header_flags.allow_execution(),
+            is_end_of_section: header_flags.is_end_of_section(),
+            is_end_of_context: header_flags.is_end_of_context(),
+            creation_timestamp: header_flags.creation_timestamp(),
+        },
+        is_signed,
+        signature: block
+            .signature
+            .as_deref()
+            .map(|signature| Uint8Array::from(signature).into())
+            .unwrap_or(JsValue::NULL),
+        is_encrypted,
+        body_size: block.body.len(),
+        body: Uint8Array::from(block.body.as_slice()).into(),
+        decompiled_body,
+        decompiler_error,
+    })
+}
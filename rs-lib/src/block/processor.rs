@@ -0,0 +1,398 @@
+use std::{cell::RefCell, future::poll_fn, pin::Pin, rc::Rc};
+
+use datex_core::{
+    global::dxb_block::DXBBlock,
+    network::com_interfaces::com_interface::factory::SendFailure,
+};
+use log::{error, warn};
+
+use crate::{
+    block::{
+        encryption::{
+            BlockEncryptionSlot, decrypt, encrypt, is_data_block, is_encrypted,
+            key_announcement, known_peer_key, parse_key_announcement,
+            report_unencrypted_block, wait_for_peer_key,
+        },
+        parse_block_bytes,
+        signing::{
+            BlockSignaturesSlot, SignatureStatus, VerificationPolicy, sign,
+            verify_block, verify_incoming_block,
+        },
+    },
+    js_utils::parse_endpoint,
+    keystore::KeystoreSlot,
+    utils::errors::{DatexJsError, JsErrorKind},
+};
+
+thread_local! {
+    // processor of the runtime whose interfaces are currently created
+    static CURRENT_PROCESSOR: RefCell<Option<BlockProcessor>> = const { RefCell::new(None) };
+}
+
+/// Writes a serialized block to the socket of an interface
+pub type BlockWriter =
+    Rc<dyn Fn(Vec<u8>) -> Pin<Box<dyn Future<Output = Result<(), ()>>>>>;
+
+/// Processes the blocks that are sent and received by the interfaces of
+/// a runtime, before they are passed to the socket or to the com hub.
+/// Outgoing requests and responses of the runtime are encrypted for their
+/// receiver if encryption is enabled or required for the receiver, and
+/// signed if a signing key is set. The public encryption keys are
+/// exchanged on first contact with key announcement blocks, which are
+/// signed with the identity key of the sender and only accepted from
+/// endpoints with a trusted key.
+/// Incoming blocks are verified according to the verification policy,
+/// encrypted blocks for this runtime are decrypted. Blocks that violate
+/// the "require" policy or an encryption requirement are dropped.
+/// Only the interfaces implemented by this crate (JS interfaces, WebSocket
+/// and serial clients) pass their blocks through the processor.
+/// The default processor passes all blocks through unchanged.
+#[derive(Clone, Default)]
+pub struct BlockProcessor {
+    // blocks are only signed and encrypted if they were sent by this
+    // endpoint, forwarded blocks are passed through unchanged
+    endpoint: Option<String>,
+    keystore: KeystoreSlot,
+    signatures: BlockSignaturesSlot,
+    encryption: BlockEncryptionSlot,
+}
+
+impl BlockProcessor {
+    pub fn new(
+        endpoint: String,
+        keystore: KeystoreSlot,
+        signatures: BlockSignaturesSlot,
+        encryption: BlockEncryptionSlot,
+    ) -> Self {
+        BlockProcessor {
+            endpoint: Some(endpoint),
+            keystore,
+            signatures,
+            encryption,
+        }
+    }
+
+    /// Wraps a future so that this processor is returned by `current`
+    /// whenever the future is polled. Interfaces that are created by
+    /// datex-core (e.g. the WebSocket client) get the processor of their
+    /// runtime this way
+    pub fn scope<F: Future>(
+        &self,
+        inner: F,
+    ) -> impl Future<Output = F::Output> + use<F> {
+        let processor = self.clone();
+        let mut inner = Box::pin(inner);
+        poll_fn(move |cx| {
+            let previous = CURRENT_PROCESSOR.replace(Some(processor.clone()));
+            let result = inner.as_mut().poll(cx);
+            CURRENT_PROCESSOR.set(previous);
+            result
+        })
+    }
+
+    /// Returns the processor of the runtime whose interface is currently
+    /// created, or a processor that passes all blocks through unchanged
+    pub fn current() -> BlockProcessor {
+        CURRENT_PROCESSOR
+            .with_borrow(|processor| processor.clone())
+            .unwrap_or_default()
+    }
+
+    /// Processes a block that is sent by an interface of the runtime and
+    /// writes it to the socket. If the receiver requires encryption and its
+    /// key is not known yet, the own key is announced first and the block
+    /// is sent once the key of the receiver was received.
+    /// If the block can not be processed or must be dropped, the block is
+    /// returned as send failure
+    pub async fn send(
+        &self,
+        block: DXBBlock,
+        writer: &BlockWriter,
+    ) -> Result<(), SendFailure> {
+        let bytes = match self.process_outgoing(block.clone(), writer).await {
+            Ok(Some(bytes)) => bytes,
+            Ok(None) => return Err(SendFailure(Box::new(block))),
+            Err(e) => {
+                error!("Failed to process outgoing block: {e}");
+                return Err(SendFailure(Box::new(block)));
+            }
+        };
+        writer(bytes)
+            .await
+            .map_err(|_| SendFailure(Box::new(block)))
+    }
+
+    /// Processes a block that was received by an interface of the runtime,
+    /// returns None if the block must be dropped. Key announcements for
+    /// this runtime are consumed and answered through the writer.
+    /// Data that is not a complete block (e.g. a chunk of a stream based
+    /// interface) is passed through to the com hub, unless the "require"
+    /// verification policy is set or an endpoint requires encryption,
+    /// since it can not be checked
+    pub async fn incoming(
+        &self,
+        bytes: Vec<u8>,
+        socket: Option<&str>,
+        writer: &BlockWriter,
+    ) -> Option<Vec<u8>> {
+        let block = match parse_block_bytes(&bytes) {
+            Ok(block) => block,
+            Err(_) if self.requires_complete_blocks() => {
+                warn!(
+                    "Dropping received data that is not a complete block, \
+                     signatures or encryption are required"
+                );
+                return None;
+            }
+            Err(_) => return Some(bytes),
+        };
+        if !verify_incoming_block(&self.signatures, &block, socket).await {
+            return None;
+        }
+        if !self.is_addressed_to_self(&block) {
+            return Some(bytes);
+        }
+        if let Some((public_key, reply)) = parse_key_announcement(&block) {
+            self.receive_key_announcement(&block, public_key, reply, writer)
+                .await;
+            return None;
+        }
+        if is_encrypted(&block) {
+            return self.decrypt_incoming(block).await;
+        }
+        let sender = block.routing_header.sender.to_string();
+        if is_data_block(&block)
+            && self.encryption.borrow().required.contains(&sender)
+        {
+            report_unencrypted_block(
+                &self.encryption,
+                &sender,
+                "incoming",
+                socket,
+            );
+            return None;
+        }
+        Some(bytes)
+    }
+
+    /// Returns the serialized block, or None if it must be dropped
+    async fn process_outgoing(
+        &self,
+        mut block: DXBBlock,
+        writer: &BlockWriter,
+    ) -> Result<Option<Vec<u8>>, DatexJsError> {
+        if !self.is_own_block(&block) {
+            return Ok(Some(block.to_bytes()));
+        }
+        if is_data_block(&block)
+            && !is_encrypted(&block)
+            && !self.encrypt_outgoing(&mut block, writer).await?
+        {
+            return Ok(None);
+        }
+        if block.signature.is_none() {
+            let signing_key = self.keystore.borrow().signing_key.clone();
+            if let Some(key) = signing_key {
+                sign(&mut block, &key).await?;
+            }
+        }
+        Ok(Some(block.to_bytes()))
+    }
+
+    /// Encrypts an own block if encryption is enabled or required for its
+    /// receiver. Returns false if the block must be dropped because a
+    /// receiver requires encryption, but the block can not be encrypted
+    /// (no key was exchanged or the block has multiple receivers)
+    async fn encrypt_outgoing(
+        &self,
+        block: &mut DXBBlock,
+        writer: &BlockWriter,
+    ) -> Result<bool, DatexJsError> {
+        let receivers = block
+            .receiver_endpoints()
+            .iter()
+            .map(|endpoint| endpoint.to_string())
+            .collect::<Vec<_>>();
+        let (required, encrypts) = {
+            let encryption = self.encryption.borrow();
+            (
+                receivers
+                    .iter()
+                    .find(|receiver| encryption.required.contains(*receiver))
+                    .cloned(),
+                receivers
+                    .iter()
+                    .any(|receiver| encryption.encrypts_for(receiver)),
+            )
+        };
+        if !encrypts {
+            return Ok(true);
+        }
+        let own_key = self.keystore.borrow().encryption_key.clone();
+        let peer_key = match (&own_key, receivers.as_slice()) {
+            (Some(_), [receiver]) => {
+                self.peer_key(receiver, required.is_some(), writer).await
+            }
+            _ => None,
+        };
+        match (own_key, peer_key) {
+            (Some(own_key), Some(peer_key)) => {
+                encrypt(block, self.own_endpoint(), &own_key, &peer_key)
+                    .await?;
+                Ok(true)
+            }
+            _ => match required {
+                Some(endpoint) => {
+                    report_unencrypted_block(
+                        &self.encryption,
+                        &endpoint,
+                        "outgoing",
+                        None,
+                    );
+                    Ok(false)
+                }
+                // encryption is only enabled, the block is sent unencrypted
+                // until the key of the receiver is known
+                None => Ok(true),
+            },
+        }
+    }
+
+    /// Returns the public key of the receiver. If the key is not known,
+    /// the own key is announced to the receiver (once) and, if `wait` is
+    /// set, the announced key of the receiver is awaited
+    async fn peer_key(
+        &self,
+        receiver: &str,
+        wait: bool,
+        writer: &BlockWriter,
+    ) -> Option<Vec<u8>> {
+        let announced = {
+            let encryption = self.encryption.borrow();
+            if let Some(key) = encryption.peer_key(receiver) {
+                return Some(key.clone());
+            }
+            encryption.announced.contains(receiver)
+        };
+        if !announced
+            && let Err(e) = self.announce_key(receiver, true, writer).await
+        {
+            error!("Failed to announce the encryption key to {receiver}: {e}");
+            return None;
+        }
+        match wait {
+            true => wait_for_peer_key(&self.encryption, receiver).await,
+            false => None,
+        }
+    }
+
+    /// Writes a key announcement with the own public encryption key to the
+    /// socket, signed with the own signing key
+    async fn announce_key(
+        &self,
+        receiver: &str,
+        reply: bool,
+        writer: &BlockWriter,
+    ) -> Result<(), DatexJsError> {
+        let (encryption_key, signing_key) = {
+            let keystore = self.keystore.borrow();
+            (
+                keystore.require_encryption_key()?,
+                keystore.require_signing_key()?,
+            )
+        };
+        let mut block = key_announcement(
+            parse_endpoint(self.own_endpoint())?,
+            parse_endpoint(receiver)?,
+            &encryption_key.public_key,
+            reply,
+        )?;
+        sign(&mut block, &signing_key).await?;
+        self.encryption
+            .borrow_mut()
+            .announced
+            .insert(receiver.to_string());
+        writer(block.to_bytes()).await.map_err(|_| {
+            DatexJsError::new(
+                JsErrorKind::Network,
+                "SendFailed",
+                format!("Failed to send the key announcement to {receiver}"),
+            )
+        })
+    }
+
+    /// Stores the announced key of the sender and answers with the own key
+    /// if requested. The announcement is only accepted if it has a valid
+    /// signature of the trusted key of the sender, keys of endpoints without
+    /// a trusted key must be added with `add_peer_encryption_key`
+    async fn receive_key_announcement(
+        &self,
+        block: &DXBBlock,
+        public_key: Vec<u8>,
+        reply: bool,
+        writer: &BlockWriter,
+    ) {
+        let sender = block.routing_header.sender.to_string();
+        let trusted_key =
+            self.signatures.borrow().trusted_keys.get(&sender).cloned();
+        let accepted = match trusted_key {
+            Some(trusted_key) => matches!(
+                verify_block(block, Some(&trusted_key)).await,
+                Ok(SignatureStatus::Valid)
+            ),
+            None => false,
+        };
+        if !accepted {
+            warn!("Ignoring untrusted encryption key announced by {sender}");
+            return;
+        }
+        self.encryption
+            .borrow_mut()
+            .set_peer_key(sender.clone(), public_key);
+        if reply && let Err(e) = self.announce_key(&sender, false, writer).await
+        {
+            error!("Failed to announce the encryption key to {sender}: {e}");
+        }
+    }
+
+    /// Returns the decrypted block, or None if it can not be decrypted
+    async fn decrypt_incoming(&self, mut block: DXBBlock) -> Option<Vec<u8>> {
+        let sender = block.routing_header.sender.to_string();
+        let result = async {
+            let own_key = self.keystore.borrow().require_encryption_key()?;
+            let peer_key = known_peer_key(&self.encryption, &sender)?;
+            decrypt(&mut block, self.own_endpoint(), &own_key, &peer_key).await
+        }
+        .await;
+        match result {
+            Ok(()) => Some(block.to_bytes()),
+            Err(e) => {
+                warn!("Dropping encrypted block from {sender}: {e}");
+                None
+            }
+        }
+    }
+
+    fn requires_complete_blocks(&self) -> bool {
+        self.signatures.borrow().policy == VerificationPolicy::Require
+            || !self.encryption.borrow().required.is_empty()
+    }
+
+    fn own_endpoint(&self) -> &str {
+        self.endpoint.as_deref().unwrap_or_default()
+    }
+
+    fn is_own_block(&self, block: &DXBBlock) -> bool {
+        self.endpoint.as_deref()
+            == Some(block.routing_header.sender.to_string().as_str())
+    }
+
+    fn is_addressed_to_self(&self, block: &DXBBlock) -> bool {
+        self.endpoint.as_deref().is_some_and(|endpoint| {
+            block
+                .receiver_endpoints()
+                .iter()
+                .any(|receiver| receiver.to_string() == endpoint)
+        })
+    }
+}
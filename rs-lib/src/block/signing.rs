@@ -0,0 +1,339 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use datex_core::{
+    crypto::CryptoImpl,
+    global::{
+        dxb_block::DXBBlock,
+        protocol_structures::routing_header::{RoutingHeader, SignatureType},
+    },
+};
+use datex_crypto_facade::crypto::Crypto;
+use log::{error, warn};
+use wasm_bindgen::JsValue;
+use web_sys::js_sys::{Date, Function};
+
+use crate::{
+    block::parse_block_bytes,
+    crypto::crypto_error,
+    js_utils::js_object,
+    keystore::KeyPair,
+    utils::errors::{DatexJsError, JsErrorKind},
+};
+
+/// Length of an Ed25519 signature in bytes
+pub const SIGNATURE_LENGTH: usize = 64;
+/// Length of an SPKI encoded Ed25519 public key in bytes
+pub const PUBLIC_KEY_LENGTH: usize = 44;
+/// Length of a PKCS#8 encoded Ed25519 private key in bytes
+pub const PRIVATE_KEY_LENGTH: usize = 48;
+/// Maximum difference between the creation timestamp of a received signed
+/// block and the local time in milliseconds, older and newer blocks are
+/// rejected as expired
+pub const BLOCK_TIMESTAMP_WINDOW_MS: u64 = 5 * 60 * 1000;
+
+/// Defines how signatures of incoming blocks are checked
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VerificationPolicy {
+    // incoming blocks are not verified
+    #[default]
+    Off,
+    // signed blocks are verified, invalid signatures are reported
+    Verify,
+    // only blocks with a valid signature of a trusted key are accepted,
+    // all other blocks are reported and dropped
+    Require,
+}
+
+impl VerificationPolicy {
+    pub fn parse(policy: &str) -> Result<VerificationPolicy, DatexJsError> {
+        match policy {
+            "off" => Ok(VerificationPolicy::Off),
+            "verify" => Ok(VerificationPolicy::Verify),
+            "require" => Ok(VerificationPolicy::Require),
+            _ => Err(DatexJsError::new(
+                JsErrorKind::Conversion,
+                "InvalidVerificationPolicy",
+                format!(
+                    "Invalid verification policy '{policy}', expected one of: off, verify, require"
+                ),
+            )),
+        }
+    }
+}
+
+/// Result of the signature verification of a block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureStatus {
+    Unsigned,
+    Valid,
+    Invalid,
+    // the block is signed, but no public key of the sender is known
+    UnknownKey,
+    // the signature is valid, but the creation timestamp of the block is
+    // outside of the accepted window
+    Expired,
+    // the signature is valid, but a block with the same sender, context
+    // and block number was already received
+    Replayed,
+}
+
+impl SignatureStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SignatureStatus::Unsigned => "unsigned",
+            SignatureStatus::Valid => "valid",
+            SignatureStatus::Invalid => "invalid",
+            SignatureStatus::UnknownKey => "unknown_key",
+            SignatureStatus::Expired => "expired",
+            SignatureStatus::Replayed => "replayed",
+        }
+    }
+
+    /// Returns true if the status must be reported with the given policy
+    pub fn violates(&self, policy: VerificationPolicy) -> bool {
+        match policy {
+            VerificationPolicy::Off => false,
+            VerificationPolicy::Verify => matches!(
+                self,
+                SignatureStatus::Invalid
+                    | SignatureStatus::Expired
+                    | SignatureStatus::Replayed
+            ),
+            VerificationPolicy::Require => *self != SignatureStatus::Valid,
+        }
+    }
+
+    /// Returns true if a block with this status must be dropped with the
+    /// given policy. Expired and replayed blocks are always dropped
+    pub fn drops(&self, policy: VerificationPolicy) -> bool {
+        self.violates(policy)
+            && (policy == VerificationPolicy::Require
+                || matches!(
+                    self,
+                    SignatureStatus::Expired | SignatureStatus::Replayed
+                ))
+    }
+}
+
+/// Identifies a received block for the replay check:
+/// sender, context id and block number
+type ReceivedBlockId = (String, u32, u16);
+
+/// Trusted public keys and verification policy of a runtime
+#[derive(Default)]
+pub struct BlockSignatures {
+    // public keys of known endpoints, by endpoint name
+    pub trusted_keys: HashMap<String, Vec<u8>>,
+    pub policy: VerificationPolicy,
+    // called with { sender, status, socket, dropped } for incoming blocks
+    // that violate the policy
+    pub on_verification_failure: Option<Function>,
+    // verified blocks received within the timestamp window, with their
+    // creation timestamps
+    received_blocks: HashMap<ReceivedBlockId, u64>,
+}
+
+impl BlockSignatures {
+    /// Checks the creation timestamp of a block with a valid signature and
+    /// records the block, returns `Expired` if the timestamp is outside of
+    /// the window and `Replayed` if the block was already received.
+    /// Records older than the window are removed, since blocks with their
+    /// timestamp are rejected as expired
+    fn check_freshness(&mut self, block: &DXBBlock) -> SignatureStatus {
+        let now = Date::now() as u64;
+        let timestamp =
+            block.block_header.flags_and_timestamp.creation_timestamp();
+        if timestamp.abs_diff(now) > BLOCK_TIMESTAMP_WINDOW_MS {
+            return SignatureStatus::Expired;
+        }
+        self.received_blocks.retain(|_, received| {
+            received.abs_diff(now) <= BLOCK_TIMESTAMP_WINDOW_MS
+        });
+        let id = (
+            block.routing_header.sender.to_string(),
+            block.block_header.context_id,
+            block.block_header.block_number,
+        );
+        match self.received_blocks.insert(id, timestamp) {
+            Some(_) => SignatureStatus::Replayed,
+            None => SignatureStatus::Valid,
+        }
+    }
+}
+
+pub type BlockSignaturesSlot = Rc<RefCell<BlockSignatures>>;
+
+/// Returns the bytes that are covered by the signature of a block:
+/// the version, sender, signature and encryption type of the routing header,
+/// followed by the block header, the body and the receivers. The TTL,
+/// block size and bounce back flag are changed by every hop and are not
+/// signed, so signed blocks must not be split for different receivers
+/// on their way.
+/// The same bytes are authenticated by the MAC of encrypted blocks.
+pub fn signed_payload(block: &DXBBlock) -> Vec<u8> {
+    let flags = &block.routing_header.flags;
+    let mut payload = vec![
+        block.routing_header.version,
+        flags.signature_type() as u8,
+        flags.encryption_type() as u8,
+    ];
+    // the sender, block header and body, serialized without
+    // the hop dependent routing header fields and the signature
+    let mut unsigned = block.clone();
+    unsigned.routing_header = RoutingHeader {
+        sender: block.routing_header.sender.clone(),
+        ..RoutingHeader::default()
+    };
+    unsigned.signature = None;
+    unsigned.recalculate_struct();
+    payload.extend(unsigned.to_bytes());
+    // the receivers, each prefixed with the length of its name
+    for receiver in block.receiver_endpoints() {
+        let receiver = receiver.to_string();
+        payload.push(receiver.len() as u8);
+        payload.extend_from_slice(receiver.as_bytes());
+    }
+    payload
+}
+
+/// Fails if the key does not have the length of an SPKI (public)
+/// or PKCS#8 (private) encoded Ed25519 key
+pub fn check_key_length(key: &[u8], private: bool) -> Result<(), DatexJsError> {
+    let (expected, name) = match private {
+        true => (PRIVATE_KEY_LENGTH, "Ed25519 private key"),
+        false => (PUBLIC_KEY_LENGTH, "Ed25519 public key"),
+    };
+    if key.len() != expected {
+        return Err(DatexJsError::new(
+            JsErrorKind::Crypto,
+            "InvalidKeyLength",
+            format!(
+                "Expected a {name} of {expected} bytes, got {} bytes",
+                key.len()
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Signs a block with the given Ed25519 key
+pub async fn sign(
+    block: &mut DXBBlock,
+    key: &KeyPair,
+) -> Result<(), DatexJsError> {
+    check_key_length(&key.private_key, true)?;
+    // the signature type is part of the signed payload
+    block
+        .routing_header
+        .flags
+        .set_signature_type(SignatureType::Unencrypted);
+    let signature =
+        CryptoImpl::sig_ed25519(&key.private_key, &signed_payload(block))
+            .await
+            .map_err(crypto_error)?;
+    block.signature = Some(signature.to_vec());
+    block.recalculate_struct();
+    Ok(())
+}
+
+/// Signs a serialized block with the given Ed25519 key and returns the signed block
+pub async fn sign_block(
+    bytes: &[u8],
+    key: &KeyPair,
+) -> Result<Vec<u8>, DatexJsError> {
+    let mut block = parse_block_bytes(bytes)?;
+    sign(&mut block, key).await?;
+    Ok(block.to_bytes())
+}
+
+/// Verifies the signature of a block against the public key of its sender
+pub async fn verify_block(
+    block: &DXBBlock,
+    public_key: Option<&[u8]>,
+) -> Result<SignatureStatus, DatexJsError> {
+    let Some(signature) = &block.signature else {
+        return Ok(SignatureStatus::Unsigned);
+    };
+    let Some(public_key) = public_key else {
+        return Ok(SignatureStatus::UnknownKey);
+    };
+    if signature.len() != SIGNATURE_LENGTH {
+        return Ok(SignatureStatus::Invalid);
+    }
+    let valid =
+        CryptoImpl::ver_ed25519(public_key, signature, &signed_payload(block))
+            .await
+            .map_err(crypto_error)?;
+    Ok(if valid {
+        SignatureStatus::Valid
+    } else {
+        SignatureStatus::Invalid
+    })
+}
+
+/// Verifies the signature of a received block according to the verification
+/// policy. Blocks with a valid signature are rejected as expired if their
+/// creation timestamp differs from the local time by more than
+/// `BLOCK_TIMESTAMP_WINDOW_MS` and as replayed if a block with the same
+/// sender, context id and block number was already received.
+/// Blocks that violate the policy are logged and passed to the
+/// `on_verification_failure` callback.
+/// Returns false if the block must be dropped, which is the case for
+/// all violations of the "require" policy and for expired and replayed
+/// blocks
+pub async fn verify_incoming_block(
+    signatures: &BlockSignaturesSlot,
+    block: &DXBBlock,
+    socket: Option<&str>,
+) -> bool {
+    let (policy, public_key, callback) = {
+        let signatures = signatures.borrow();
+        (
+            signatures.policy,
+            signatures
+                .trusted_keys
+                .get(&block.routing_header.sender.to_string())
+                .cloned(),
+            signatures.on_verification_failure.clone(),
+        )
+    };
+    if policy == VerificationPolicy::Off {
+        return true;
+    }
+    let sender = block.routing_header.sender.to_string();
+    let status = match verify_block(block, public_key.as_deref()).await {
+        Ok(SignatureStatus::Valid) => {
+            signatures.borrow_mut().check_freshness(block)
+        }
+        Ok(status) => status,
+        Err(e) => {
+            error!("Failed to verify block from {sender}: {e}");
+            SignatureStatus::Invalid
+        }
+    };
+    if !status.violates(policy) {
+        return true;
+    }
+    let dropped = status.drops(policy);
+    warn!(
+        "Signature verification failed for block from {sender}: {}{}",
+        status.as_str(),
+        if dropped {
+            ", the block is dropped"
+        } else {
+            ""
+        }
+    );
+    if let Some(callback) = callback {
+        let failure = js_object(vec![
+            ("sender", JsValue::from(sender)),
+            ("status", JsValue::from(status.as_str())),
+            ("socket", socket.map(JsValue::from).unwrap_or(JsValue::NULL)),
+            ("dropped", JsValue::from(dropped)),
+        ]);
+        if let Err(e) = callback.call1(&JsValue::NULL, &failure) {
+            error!("Error in verification failure callback: {e:?}");
+        }
+    }
+    !dropped
+}
@@ -0,0 +1,179 @@
+use datex_core::{
+    compiler::{
+        CompileOptions, compile_script, compile_template,
+        error::{CompilerError, SpannedCompilerError},
+    },
+    dif::value::DIFValueContainer,
+    runtime::execution::ScriptExecutionError,
+    values::value_container::ValueContainer,
+};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsValue;
+use web_sys::js_sys::Uint8Array;
+
+use crate::{
+    js_utils::{js_object, to_js_value},
+    utils::errors::{DatexJsError, JsErrorKind, conversion_error},
+};
+
+/// Options for `compile`, used by the free function and the runtime method
+#[derive(Debug, Default, Deserialize)]
+pub struct JSCompileOptions {
+    // optional DIF values that are inserted for the `?` placeholders in the script
+    // if set, the script is compiled as a template
+    #[serde(default)]
+    pub inserted_values: Option<Vec<DIFValueContainer>>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CompileDiagnosticSeverity {
+    Error,
+    Warning,
+}
+
+/// A single compiler diagnostic that is returned to JavaScript
+#[derive(Debug, Serialize)]
+pub struct CompileDiagnostic {
+    pub severity: CompileDiagnosticSeverity,
+    /// stable identifier of the diagnostic, e.g. "EmptyBody"
+    pub code: String,
+    pub message: String,
+    /// byte range (start, end) in the script source, if known
+    pub span: Option<(usize, usize)>,
+}
+
+/// Returns the error code of a compiler error,
+/// "CompileFailed" for errors without a specific code
+pub fn compiler_error_code(err: &CompilerError) -> &'static str {
+    match err {
+        CompilerError::UndeclaredVariable { .. } => "UndeclaredVariable",
+        CompilerError::AssignmentToConst { .. } => "AssignmentToConst",
+        CompilerError::AssignmentToImmutableReference { .. } => {
+            "AssignmentToImmutableReference"
+        }
+        CompilerError::AssignmentToImmutableValue { .. } => {
+            "AssignmentToImmutableValue"
+        }
+        CompilerError::InvalidPlaceholderCount { .. } => {
+            "InvalidPlaceholderCount"
+        }
+        CompilerError::InvalidSlotName { .. } => "InvalidSlotName",
+        CompilerError::IntegerOutOfBoundsError { .. } => "IntegerOutOfBounds",
+        CompilerError::BigDecimalOutOfBoundsError { .. } => {
+            "DecimalOutOfBounds"
+        }
+        CompilerError::NonStaticValue { .. } => "NonStaticValue",
+        CompilerError::OnceScopeUsedMultipleTimes { .. } => {
+            "OnceScopeUsedMultipleTimes"
+        }
+        CompilerError::TypeError { .. } => "TypeError",
+        _ => "CompileFailed",
+    }
+}
+
+impl From<&SpannedCompilerError> for CompileDiagnostic {
+    fn from(err: &SpannedCompilerError) -> Self {
+        CompileDiagnostic {
+            severity: CompileDiagnosticSeverity::Error,
+            code: compiler_error_code(&err.error).to_string(),
+            message: err.error.to_string(),
+            span: err.span.as_ref().map(|span| (span.start, span.end)),
+        }
+    }
+}
+
+impl From<&SpannedCompilerError> for DatexJsError {
+    fn from(err: &SpannedCompilerError) -> Self {
+        let error = DatexJsError::from_error(
+            JsErrorKind::Compiler,
+            compiler_error_code(&err.error),
+            &err.error,
+        );
+        match &err.span {
+            Some(span) => error.with_span(span.clone()),
+            None => error,
+        }
+    }
+}
+
+/// Errors of script executions are split into compiler errors (with the
+/// span in the script source) and errors during the execution
+impl From<&ScriptExecutionError> for DatexJsError {
+    fn from(err: &ScriptExecutionError) -> Self {
+        match err {
+            ScriptExecutionError::CompilerError(err) => DatexJsError::from(err),
+            ScriptExecutionError::ExecutionError(err) => {
+                DatexJsError::from_error(
+                    JsErrorKind::Execution,
+                    "ExecutionFailed",
+                    err,
+                )
+            }
+        }
+    }
+}
+
+/// Compiles a script (or a template if inserted values are given) to a DXB body.
+/// Returns the compiled body and all collected diagnostics
+pub fn compile_with_diagnostics(
+    script: &str,
+    inserted_values: Option<&[ValueContainer]>,
+) -> (Option<Vec<u8>>, Vec<CompileDiagnostic>) {
+    let result = match inserted_values {
+        Some(inserted_values) => {
+            compile_template(script, inserted_values, CompileOptions::default())
+        }
+        None => compile_script(script, CompileOptions::default()),
+    };
+    match result {
+        Ok((body, _)) => {
+            let warnings = collect_warnings(script, &body);
+            (Some(body), warnings)
+        }
+        Err(err) => (None, vec![CompileDiagnostic::from(&err)]),
+    }
+}
+
+/// Collects warnings for a successfully compiled script
+fn collect_warnings(script: &str, body: &[u8]) -> Vec<CompileDiagnostic> {
+    let mut warnings = vec![];
+    // a script without any instructions (e.g. only whitespace or comments)
+    // results in an empty body, which is most likely not intended
+    if body.is_empty() {
+        warnings.push(CompileDiagnostic {
+            severity: CompileDiagnosticSeverity::Warning,
+            code: "EmptyBody".to_string(),
+            message: "The script does not contain any instructions".to_string(),
+            span: (!script.is_empty()).then_some((0, script.len())),
+        });
+    }
+    warnings
+}
+
+/// Compiles a script and converts the result to a JS object of the form
+/// `{ body: Uint8Array | null, warnings: CompileDiagnostic[], errors: CompileDiagnostic[] }`
+pub fn compile_to_js_result(
+    script: &str,
+    inserted_values: Option<&[ValueContainer]>,
+) -> Result<JsValue, DatexJsError> {
+    let (body, diagnostics) = compile_with_diagnostics(script, inserted_values);
+    let (errors, warnings): (Vec<_>, Vec<_>) =
+        diagnostics.into_iter().partition(|diagnostic| {
+            matches!(diagnostic.severity, CompileDiagnosticSeverity::Error)
+        });
+
+    Ok(js_object(vec![
+        (
+            "body",
+            body.map(|body| Uint8Array::from(body.as_slice()).into())
+                .unwrap_or(JsValue::NULL),
+        ),
+        (
+            "warnings",
+            to_js_value(&warnings).map_err(conversion_error)?,
+        ),
+        ("errors", to_js_value(&errors).map_err(conversion_error)?),
+    ])
+    .into())
+}
@@ -0,0 +1,300 @@
+use std::fmt::Debug;
+
+use datex_core::crypto::CryptoImpl;
+use datex_crypto_facade::crypto::Crypto;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    CryptoKey,
+    js_sys::{self, Array, ArrayBuffer, Reflect, Uint8Array},
+};
+
+use crate::{
+    js_utils::js_object,
+    keystore::KeyPair,
+    utils::errors::{DatexJsError, JsErrorKind},
+};
+
+/// The crypto primitives of the DATEX runtime.
+/// Keys are passed as raw bytes, public keys are SPKI
+/// and private keys PKCS#8 encoded.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Default)]
+pub struct JSCrypto;
+
+#[wasm_bindgen]
+impl JSCrypto {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> JSCrypto {
+        JSCrypto
+    }
+
+    pub async fn hash_sha256(
+        &self,
+        data: Vec<u8>,
+    ) -> Result<Vec<u8>, DatexJsError> {
+        CryptoImpl::hash_sha256(&data)
+            .await
+            .map(|hash| hash.to_vec())
+            .map_err(crypto_error)
+    }
+
+    /// Derive a 32 byte key from the input key material and salt
+    pub async fn hkdf_sha256(
+        &self,
+        ikm: Vec<u8>,
+        salt: Vec<u8>,
+    ) -> Result<Vec<u8>, DatexJsError> {
+        CryptoImpl::hkdf_sha256(&ikm, &salt)
+            .await
+            .map(|key| key.to_vec())
+            .map_err(crypto_error)
+    }
+
+    /// Generate an Ed25519 keypair `{ public_key, private_key }`
+    pub async fn generate_ed25519(&self) -> Result<JsValue, DatexJsError> {
+        Ok(key_pair_to_js(&KeyPair::generate_signing().await?))
+    }
+
+    pub async fn sign_ed25519(
+        &self,
+        private_key: Vec<u8>,
+        data: Vec<u8>,
+    ) -> Result<Vec<u8>, DatexJsError> {
+        CryptoImpl::sig_ed25519(&private_key, &data)
+            .await
+            .map(|signature| signature.to_vec())
+            .map_err(crypto_error)
+    }
+
+    pub async fn verify_ed25519(
+        &self,
+        public_key: Vec<u8>,
+        signature: Vec<u8>,
+        data: Vec<u8>,
+    ) -> Result<bool, DatexJsError> {
+        CryptoImpl::ver_ed25519(&public_key, &signature, &data)
+            .await
+            .map_err(crypto_error)
+    }
+
+    /// Generate an X25519 keypair `{ public_key, private_key }`
+    pub async fn generate_x25519(&self) -> Result<JsValue, DatexJsError> {
+        Ok(key_pair_to_js(&KeyPair::generate_encryption().await?))
+    }
+
+    /// Derive a 32 byte shared secret from an own private key
+    /// and the public key of the peer
+    pub async fn derive_x25519(
+        &self,
+        private_key: Vec<u8>,
+        peer_public_key: Vec<u8>,
+    ) -> Result<Vec<u8>, DatexJsError> {
+        CryptoImpl::derive_x25519(&private_key, &peer_public_key)
+            .await
+            .map(|secret| secret.to_vec())
+            .map_err(crypto_error)
+    }
+
+    /// Encrypt data with AES-CTR using a 32 byte key and a 16 byte IV
+    pub async fn aes_ctr_encrypt(
+        &self,
+        key: Vec<u8>,
+        iv: Vec<u8>,
+        data: Vec<u8>,
+    ) -> Result<Vec<u8>, DatexJsError> {
+        let key = to_array::<32>(&key, "key")?;
+        let iv = to_array::<16>(&iv, "IV")?;
+        CryptoImpl::aes_ctr_encrypt(&key, &iv, &data)
+            .await
+            .map_err(crypto_error)
+    }
+
+    pub async fn aes_ctr_decrypt(
+        &self,
+        key: Vec<u8>,
+        iv: Vec<u8>,
+        data: Vec<u8>,
+    ) -> Result<Vec<u8>, DatexJsError> {
+        let key = to_array::<32>(&key, "key")?;
+        let iv = to_array::<16>(&iv, "IV")?;
+        CryptoImpl::aes_ctr_decrypt(&key, &iv, &data)
+            .await
+            .map_err(crypto_error)
+    }
+
+    /// Wrap a 32 byte key with a 32 byte key encryption key (RFC 3394)
+    pub async fn key_wrap_rfc3394(
+        &self,
+        kek: Vec<u8>,
+        key: Vec<u8>,
+    ) -> Result<Vec<u8>, DatexJsError> {
+        let kek = to_array::<32>(&kek, "key encryption key")?;
+        let key = to_array::<32>(&key, "key")?;
+        CryptoImpl::key_wrap_rfc3394(&kek, &key)
+            .await
+            .map(|wrapped| wrapped.to_vec())
+            .map_err(crypto_error)
+    }
+
+    pub async fn key_unwrap_rfc3394(
+        &self,
+        kek: Vec<u8>,
+        wrapped_key: Vec<u8>,
+    ) -> Result<Vec<u8>, DatexJsError> {
+        let kek = to_array::<32>(&kek, "key encryption key")?;
+        CryptoImpl::key_unwrap_rfc3394(&kek, &wrapped_key)
+            .await
+            .map(|key| key.to_vec())
+            .map_err(crypto_error)
+    }
+
+    pub fn encode_base58(&self, data: Vec<u8>) -> String {
+        CryptoImpl::enc_b58(&data)
+    }
+
+    /// Decode a base58 string that encodes exactly 32 bytes (e.g. a key)
+    pub fn decode_base58_32(
+        &self,
+        encoded: &str,
+    ) -> Result<Vec<u8>, DatexJsError> {
+        CryptoImpl::dec_b58_32(encoded)
+            .map(|decoded| decoded.to_vec())
+            .map_err(crypto_error)
+    }
+
+    /// Generate cryptographically secure random bytes
+    pub fn random_bytes(&self, length: usize) -> Vec<u8> {
+        CryptoImpl::random_bytes(length)
+    }
+}
+
+fn key_pair_to_js(key_pair: &KeyPair) -> JsValue {
+    js_object(vec![
+        (
+            "public_key",
+            Uint8Array::from(key_pair.public_key.as_slice()),
+        ),
+        (
+            "private_key",
+            Uint8Array::from(key_pair.private_key.as_slice()),
+        ),
+    ])
+    .into()
+}
+
+fn to_array<const N: usize>(
+    bytes: &[u8],
+    name: &str,
+) -> Result<[u8; N], DatexJsError> {
+    bytes.try_into().map_err(|_| {
+        DatexJsError::new(
+            JsErrorKind::Crypto,
+            "InvalidKeyLength",
+            format!(
+                "Expected a {name} of {N} bytes, got {} bytes",
+                bytes.len()
+            ),
+        )
+    })
+}
+
+/// Generates an array of random bytes
+pub fn random_array<const N: usize>() -> Result<[u8; N], DatexJsError> {
+    CryptoImpl::random_bytes(N).try_into().map_err(|_| {
+        DatexJsError::new(
+            JsErrorKind::Crypto,
+            "RandomBytesFailed",
+            "Failed to generate random bytes",
+        )
+    })
+}
+
+/// Length of an HMAC-SHA256 tag in bytes
+pub const MAC_LENGTH: usize = 32;
+
+/// Computes the HMAC-SHA256 (RFC 2104) of the data with a 32 byte key,
+/// built from SHA-256 since the crypto facade does not provide HMAC
+pub async fn hmac_sha256(
+    key: &[u8; 32],
+    data: &[u8],
+) -> Result<[u8; MAC_LENGTH], DatexJsError> {
+    const BLOCK_SIZE: usize = 64;
+    let mut inner_key = [0x36; BLOCK_SIZE];
+    let mut outer_key = [0x5c; BLOCK_SIZE];
+    for (i, byte) in key.iter().enumerate() {
+        inner_key[i] ^= byte;
+        outer_key[i] ^= byte;
+    }
+    let inner = CryptoImpl::hash_sha256(&[inner_key.as_slice(), data].concat())
+        .await
+        .map_err(crypto_error)?;
+    let outer = CryptoImpl::hash_sha256(
+        &[outer_key.as_slice(), inner.as_ref()].concat(),
+    )
+    .await
+    .map_err(crypto_error)?;
+    let mut tag = [0; MAC_LENGTH];
+    tag.copy_from_slice(outer.as_ref());
+    Ok(tag)
+}
+
+/// Derives a 32 byte key from a password with PBKDF2-HMAC-SHA256, using
+/// the SubtleCrypto API directly since the crypto facade does not provide
+/// a password based key derivation
+pub async fn pbkdf2_sha256(
+    password: &[u8],
+    salt: &[u8],
+    iterations: u32,
+) -> Result<[u8; 32], DatexJsError> {
+    let subtle = Reflect::get(&js_sys::global(), &"crypto".into())
+        .ok()
+        .filter(|crypto| crypto.is_object())
+        .map(|crypto| crypto.unchecked_into::<web_sys::Crypto>().subtle())
+        .ok_or_else(|| {
+            DatexJsError::new(
+                JsErrorKind::Crypto,
+                "CryptoUnavailable",
+                "The SubtleCrypto API is not available",
+            )
+        })?;
+    let password_key: CryptoKey = JsFuture::from(
+        subtle
+            .import_key_with_str(
+                "raw",
+                &Uint8Array::from(password),
+                "PBKDF2",
+                false,
+                &Array::of1(&"deriveBits".into()),
+            )
+            .map_err(crypto_error)?,
+    )
+    .await
+    .map_err(crypto_error)?
+    .unchecked_into();
+    let params = js_object(vec![
+        ("name", JsValue::from("PBKDF2")),
+        ("hash", JsValue::from("SHA-256")),
+        ("salt", Uint8Array::from(salt).into()),
+        ("iterations", JsValue::from(iterations)),
+    ]);
+    let bits: ArrayBuffer = JsFuture::from(
+        subtle
+            .derive_bits_with_object(&params, &password_key, 256)
+            .map_err(crypto_error)?,
+    )
+    .await
+    .map_err(crypto_error)?
+    .unchecked_into();
+    to_array::<32>(&Uint8Array::new(&bits).to_vec(), "derived key")
+}
+
+/// Compares two MACs in constant time
+pub fn mac_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len()
+        && a.iter().zip(b).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+pub fn crypto_error<E: Debug>(err: E) -> DatexJsError {
+    DatexJsError::from_debug(JsErrorKind::Crypto, "CryptoOperationFailed", &err)
+}
@@ -0,0 +1,273 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    ops::{Deref, DerefMut},
+    rc::Rc,
+};
+
+use datex_core::runtime::{
+    Runtime,
+    execution::context::{ExecutionContext, ExecutionMode},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::errors::{DatexJsError, JsErrorKind};
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct JSExecutionContextOptions {
+    // optional unique name of the context, a name is generated if not set
+    pub name: Option<String>,
+    // if true, the context is created in debug mode and logs all executed instructions
+    #[serde(default)]
+    pub verbose: bool,
+}
+
+/// Information about a named execution context that is returned to JavaScript
+#[derive(Debug, Serialize)]
+pub struct ExecutionContextInfo {
+    pub name: String,
+    pub verbose: bool,
+    /// number of successfully completed executions in this context
+    /// since creation or last reset
+    pub execution_count: u32,
+    /// number of failed executions in this context since creation or last reset
+    pub failure_count: u32,
+    /// names of the variables declared in this context, as of the
+    /// end of the last execution
+    pub declared_variables: Vec<String>,
+    /// true if an execution is currently running in this context
+    pub is_busy: bool,
+}
+
+struct ExecutionContextEntry {
+    verbose: bool,
+    execution_count: u32,
+    failure_count: u32,
+    declared_variables: Vec<String>,
+    // None while an execution is running in this context
+    context: Option<ExecutionContext>,
+}
+
+impl ExecutionContextEntry {
+    fn new(verbose: bool, runtime: &Runtime) -> Self {
+        ExecutionContextEntry {
+            verbose,
+            execution_count: 0,
+            failure_count: 0,
+            declared_variables: vec![],
+            context: Some(Self::create_context(verbose, runtime)),
+        }
+    }
+
+    fn create_context(verbose: bool, runtime: &Runtime) -> ExecutionContext {
+        if verbose {
+            ExecutionContext::local_debug(
+                ExecutionMode::unbounded(),
+                runtime.internal(),
+            )
+        } else {
+            ExecutionContext::local(
+                ExecutionMode::unbounded(),
+                runtime.internal(),
+            )
+        }
+    }
+}
+
+/// Registry of named execution contexts that keep their state
+/// (e.g. declared variables) between multiple executions
+#[derive(Clone, Default)]
+pub struct ExecutionContextRegistry {
+    contexts: Rc<RefCell<HashMap<String, ExecutionContextEntry>>>,
+    next_id: Rc<Cell<u32>>,
+}
+
+impl ExecutionContextRegistry {
+    pub fn create(
+        &self,
+        options: JSExecutionContextOptions,
+        runtime: &Runtime,
+    ) -> Result<String, DatexJsError> {
+        let name = match options.name {
+            Some(name) => name,
+            None => self.generate_name(),
+        };
+        let mut contexts = self.contexts.borrow_mut();
+        if contexts.contains_key(&name) {
+            return Err(DatexJsError::new(
+                JsErrorKind::Runtime,
+                "ContextAlreadyExists",
+                format!("Execution context '{name}' already exists"),
+            ));
+        }
+        contexts.insert(
+            name.clone(),
+            ExecutionContextEntry::new(options.verbose, runtime),
+        );
+        Ok(name)
+    }
+
+    fn generate_name(&self) -> String {
+        let contexts = self.contexts.borrow();
+        loop {
+            let id = self.next_id.get();
+            self.next_id.set(id + 1);
+            let name = format!("context_{id}");
+            if !contexts.contains_key(&name) {
+                return name;
+            }
+        }
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> =
+            self.contexts.borrow().keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    pub fn info(
+        &self,
+        name: &str,
+    ) -> Result<ExecutionContextInfo, DatexJsError> {
+        let contexts = self.contexts.borrow();
+        let entry = contexts.get(name).ok_or_else(|| Self::not_found(name))?;
+        Ok(ExecutionContextInfo {
+            name: name.to_string(),
+            verbose: entry.verbose,
+            execution_count: entry.execution_count,
+            failure_count: entry.failure_count,
+            declared_variables: entry.declared_variables.clone(),
+            is_busy: entry.context.is_none(),
+        })
+    }
+
+    /// Replaces the context with a fresh one, dropping all state
+    pub fn reset(
+        &self,
+        name: &str,
+        runtime: &Runtime,
+    ) -> Result<(), DatexJsError> {
+        let mut contexts = self.contexts.borrow_mut();
+        let entry = contexts
+            .get_mut(name)
+            .ok_or_else(|| Self::not_found(name))?;
+        if entry.context.is_none() {
+            return Err(Self::busy(name));
+        }
+        *entry = ExecutionContextEntry::new(entry.verbose, runtime);
+        Ok(())
+    }
+
+    /// Removes the context. A currently running execution in this context
+    /// is completed, but the context is dropped afterwards
+    pub fn dispose(&self, name: &str) -> Result<(), DatexJsError> {
+        self.contexts
+            .borrow_mut()
+            .remove(name)
+            .map(|_| ())
+            .ok_or_else(|| Self::not_found(name))
+    }
+
+    pub fn clear(&self) {
+        self.contexts.borrow_mut().clear();
+    }
+
+    /// Takes the context out of the registry for the duration of an execution.
+    /// The context is put back when the returned guard is dropped.
+    pub fn acquire(
+        &self,
+        name: &str,
+    ) -> Result<ExecutionContextGuard, DatexJsError> {
+        let mut contexts = self.contexts.borrow_mut();
+        let entry = contexts
+            .get_mut(name)
+            .ok_or_else(|| Self::not_found(name))?;
+        let context = entry.context.take().ok_or_else(|| Self::busy(name))?;
+        Ok(ExecutionContextGuard {
+            registry: self.clone(),
+            name: name.to_string(),
+            context: Some(context),
+            succeeded: false,
+        })
+    }
+
+    fn not_found(name: &str) -> DatexJsError {
+        DatexJsError::new(
+            JsErrorKind::Runtime,
+            "ContextNotFound",
+            format!("Execution context '{name}' does not exist"),
+        )
+    }
+
+    fn busy(name: &str) -> DatexJsError {
+        DatexJsError::new(
+            JsErrorKind::Runtime,
+            "ContextBusy",
+            format!("Execution context '{name}' is already in use"),
+        )
+    }
+}
+
+/// Exclusive access to a named execution context during an execution
+pub struct ExecutionContextGuard {
+    registry: ExecutionContextRegistry,
+    name: String,
+    context: Option<ExecutionContext>,
+    // set by `record_result`, an execution that is dropped
+    // without a result (e.g. aborted) counts as failed
+    succeeded: bool,
+}
+
+impl ExecutionContextGuard {
+    /// Records the result of the execution in this context,
+    /// only successful executions are counted as completed
+    pub fn record_result<T, E>(&mut self, result: &Result<T, E>) {
+        self.succeeded = result.is_ok();
+    }
+}
+
+impl Deref for ExecutionContextGuard {
+    type Target = ExecutionContext;
+
+    fn deref(&self) -> &Self::Target {
+        self.context.as_ref().unwrap()
+    }
+}
+
+impl DerefMut for ExecutionContextGuard {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.context.as_mut().unwrap()
+    }
+}
+
+impl Drop for ExecutionContextGuard {
+    fn drop(&mut self) {
+        // if the context was disposed (or replaced) in the meantime, it is just dropped
+        if let Some(entry) =
+            self.registry.contexts.borrow_mut().get_mut(&self.name)
+            && entry.context.is_none()
+        {
+            let context = self.context.take().unwrap();
+            entry.declared_variables = declared_variables(&context);
+            entry.context = Some(context);
+            if self.succeeded {
+                entry.execution_count += 1;
+            } else {
+                entry.failure_count += 1;
+            }
+        }
+    }
+}
+
+/// Returns the sorted names of all variables that are declared
+/// in the compile scope of the context
+fn declared_variables(context: &ExecutionContext) -> Vec<String> {
+    let mut names: Vec<String> = context
+        .compile_scope()
+        .variable_names()
+        .map(|name| name.to_string())
+        .collect();
+    names.sort();
+    names
+}
@@ -1,17 +1,21 @@
-use std::cell::RefCell;
+use std::{cell::RefCell, str::FromStr};
 
 use datex_core::{
     dif::value::{DIFReferenceNotFoundError, DIFValueContainer},
     runtime::memory::Memory,
     serde::deserializer::from_value_container,
-    values::value_container::ValueContainer,
+    values::{
+        core_values::endpoint::Endpoint, value_container::ValueContainer,
+    },
 };
-use log::info;
 use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use serde_wasm_bindgen::{Error, from_value};
-use wasm_bindgen::{JsError, JsValue};
+use wasm_bindgen::{JsCast, JsError, JsValue};
+use wasm_bindgen_futures::JsFuture;
 use web_sys::js_sys::{self, Array, ArrayBuffer, Object, Reflect};
 
+use crate::utils::errors::{DatexJsError, JsErrorKind, conversion_error};
+
 pub trait TryAsByteSlice {
     fn try_as_u8_slice(&self) -> Result<Vec<u8>, JsError>;
 }
@@ -61,38 +65,67 @@ where
     JsValue::from(js_array)
 }
 
-pub fn js_error<T: std::fmt::Display>(err: T) -> JsError {
-    JsError::new(&err.to_string())
+/// Deserializes an optional options object passed from JavaScript.
+/// Returns the default options for null or undefined and a conversion
+/// error if the value is malformed
+pub fn options_from_js_value<T: DeserializeOwned + Default>(
+    value: JsValue,
+) -> Result<T, DatexJsError> {
+    if value.is_null() || value.is_undefined() {
+        Ok(T::default())
+    } else {
+        from_value(value).map_err(conversion_error)
+    }
 }
 
-trait ToJsError<T> {
-    fn js(self) -> Result<T, JsError>;
+/// Parses an endpoint string (e.g. "@example")
+pub fn parse_endpoint(endpoint: &str) -> Result<Endpoint, DatexJsError> {
+    Endpoint::from_str(endpoint).map_err(|e| {
+        DatexJsError::new(
+            JsErrorKind::Conversion,
+            "InvalidEndpoint",
+            format!("Invalid endpoint format: {:?}", e),
+        )
+    })
 }
 
-impl<T, E: std::error::Error + 'static> ToJsError<T> for Result<T, E> {
-    fn js(self) -> Result<T, JsError> {
-        self.map_err(js_error)
-    }
+/// Deserialize a JsValue into a DIFValueContainer
+pub fn js_value_to_dif_value_container(
+    value: JsValue,
+) -> Result<DIFValueContainer, DatexJsError> {
+    from_value(value).map_err(|e| {
+        DatexJsError::new(
+            JsErrorKind::Conversion,
+            "InvalidDIFValue",
+            format!("Failed to deserialize JsValue to DIFValueContainer: {e}"),
+        )
+    })
 }
 
 /// Deserialize a JsValue into a Rust type T using DIFValueContainer as an intermediary,
 pub fn cast_from_dif_js_value<T>(
     value: JsValue,
     memory: &RefCell<Memory>,
-) -> Result<T, ()>
+) -> Result<T, DatexJsError>
 where
     T: DeserializeOwned,
 {
-    let unresolved_value_container: DIFValueContainer = from_value(value)
-        .expect("Failed to deserialize JsValue to DIFValueContainer");
-
-    let value_container = unresolved_value_container
+    let value_container = js_value_to_dif_value_container(value)?
         .to_value_container(memory)
-        .map_err(|_| ())?;
+        .map_err(|e| {
+            DatexJsError::new(
+                JsErrorKind::Conversion,
+                "ReferenceNotFound",
+                format!("{e:?}"),
+            )
+        })?;
 
     from_value_container::<T>(&value_container).map_err(|e| {
-        info!("Deserialization error: {}", e);
-        ()
+        DatexJsError::new(
+            JsErrorKind::Conversion,
+            "DeserializationFailed",
+            e.to_string(),
+        )
     })
 }
 
@@ -115,6 +148,26 @@ pub fn value_container_to_dif_js_value(
         .expect("Failed to serialize DIFValueContainer to JsValue")
 }
 
+/// Returns true if the object has a method with the given name
+pub fn has_method(object: &JsValue, method: &str) -> bool {
+    Reflect::get(object, &method.into())
+        .map(|function| function.is_function())
+        .unwrap_or(false)
+}
+
+/// Calls a method of a JS object and awaits the result if it is a promise
+pub async fn call_method(
+    object: &JsValue,
+    method: &str,
+    args: &[JsValue],
+) -> Result<JsValue, JsValue> {
+    let function: js_sys::Function =
+        Reflect::get(object, &method.into())?.dyn_into()?;
+    let args = args.iter().collect::<Array>();
+    let result = function.apply(object, &args)?;
+    JsFuture::from(js_sys::Promise::resolve(&result)).await
+}
+
 /// Convert a serializable value to a JsValue (JSON compatible)
 pub fn to_js_value<T: Serialize>(value: &T) -> Result<JsValue, Error> {
     value.serialize(&serde_wasm_bindgen::Serializer::json_compatible())
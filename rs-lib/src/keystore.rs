@@ -0,0 +1,381 @@
+use std::{cell::RefCell, rc::Rc};
+
+use datex_core::crypto::CryptoImpl;
+use datex_crypto_facade::crypto::Crypto;
+use serde::{Deserialize, Serialize};
+use serde_wasm_bindgen::from_value;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::js_sys::{JSON, Uint8Array};
+
+use crate::{
+    crypto::{
+        MAC_LENGTH, crypto_error, hmac_sha256, mac_eq, pbkdf2_sha256,
+        random_array,
+    },
+    js_utils::{call_method, has_method, to_js_value},
+    utils::errors::{DatexJsError, JsErrorKind, conversion_error},
+};
+
+/// Magic bytes at the start of every exported identity
+const IDENTITY_MAGIC: &[u8; 4] = b"DXID";
+/// Version of the identity export format
+const IDENTITY_VERSION: u8 = 3;
+/// Identifier of PBKDF2-HMAC-SHA256 as key derivation function
+const KDF_PBKDF2_SHA256: u8 = 1;
+/// PBKDF2 iterations for newly derived wrapping keys
+const PBKDF2_ITERATIONS: u32 = 600_000;
+/// Upper bound for the iterations of an imported identity, so that a
+/// crafted identity can not block the runtime
+const MAX_PBKDF2_ITERATIONS: u32 = 10_000_000;
+const SALT_LENGTH: usize = 16;
+const IV_LENGTH: usize = 16;
+/// Length of a 32 byte key wrapped with RFC 3394
+const WRAPPED_KEY_LENGTH: usize = 40;
+/// HKDF salt for deriving the MAC key of an identity from its data key
+const MAC_KEY_SALT: &[u8] = b"DXID-MAC";
+
+/// A keypair, the public key is SPKI and the private key PKCS#8 encoded
+#[derive(Clone, Serialize, Deserialize)]
+pub struct KeyPair {
+    pub public_key: Vec<u8>,
+    pub private_key: Vec<u8>,
+}
+
+impl KeyPair {
+    /// Generates an Ed25519 keypair for signing blocks
+    pub async fn generate_signing() -> Result<KeyPair, DatexJsError> {
+        let (public_key, private_key) =
+            CryptoImpl::gen_ed25519().await.map_err(crypto_error)?;
+        Ok(KeyPair {
+            public_key: public_key.to_vec(),
+            private_key: private_key.to_vec(),
+        })
+    }
+
+    /// Generates an X25519 keypair for deriving encryption keys
+    pub async fn generate_encryption() -> Result<KeyPair, DatexJsError> {
+        let (public_key, private_key) =
+            CryptoImpl::gen_x25519().await.map_err(crypto_error)?;
+        Ok(KeyPair {
+            public_key: public_key.to_vec(),
+            private_key: private_key.to_vec(),
+        })
+    }
+}
+
+/// The signing and encryption keys of an endpoint
+#[derive(Clone, Serialize, Deserialize)]
+pub struct EndpointIdentity {
+    pub endpoint: String,
+    pub signing_key: KeyPair,
+    pub encryption_key: KeyPair,
+}
+
+impl EndpointIdentity {
+    pub async fn generate(
+        endpoint: String,
+    ) -> Result<EndpointIdentity, DatexJsError> {
+        Ok(EndpointIdentity {
+            endpoint,
+            signing_key: KeyPair::generate_signing().await?,
+            encryption_key: KeyPair::generate_encryption().await?,
+        })
+    }
+}
+
+/// Key encryption key derived from a password with PBKDF2-HMAC-SHA256,
+/// the salt and the iteration count are stored with every wrapped identity.
+#[derive(Clone)]
+pub struct WrappingKey {
+    salt: [u8; SALT_LENGTH],
+    iterations: u32,
+    key: [u8; 32],
+}
+
+impl WrappingKey {
+    /// Derives a wrapping key from a password with a new random salt.
+    /// Fails with `EmptyPassword` if the password is empty.
+    pub async fn derive(password: &str) -> Result<WrappingKey, DatexJsError> {
+        let salt = random_array::<SALT_LENGTH>()?;
+        WrappingKey::derive_with_params(password, salt, PBKDF2_ITERATIONS).await
+    }
+
+    async fn derive_with_params(
+        password: &str,
+        salt: [u8; SALT_LENGTH],
+        iterations: u32,
+    ) -> Result<WrappingKey, DatexJsError> {
+        if password.is_empty() {
+            return Err(DatexJsError::new(
+                JsErrorKind::Crypto,
+                "EmptyPassword",
+                "The password must not be empty",
+            ));
+        }
+        let key = pbkdf2_sha256(password.as_bytes(), &salt, iterations).await?;
+        Ok(WrappingKey {
+            salt,
+            iterations,
+            key,
+        })
+    }
+}
+
+/// The keys of a runtime and the optional persistence of the identity
+#[derive(Default)]
+pub struct Keystore {
+    pub signing_key: Option<KeyPair>,
+    pub encryption_key: Option<KeyPair>,
+    pub persistence: Option<Rc<IdentityPersistence>>,
+}
+
+pub type KeystoreSlot = Rc<RefCell<Keystore>>;
+
+impl Keystore {
+    /// Returns the identity if both keypairs are set
+    pub fn identity(&self, endpoint: &str) -> Option<EndpointIdentity> {
+        Some(EndpointIdentity {
+            endpoint: endpoint.to_string(),
+            signing_key: self.signing_key.clone()?,
+            encryption_key: self.encryption_key.clone()?,
+        })
+    }
+
+    pub fn set_identity(&mut self, identity: EndpointIdentity) {
+        self.signing_key = Some(identity.signing_key);
+        self.encryption_key = Some(identity.encryption_key);
+    }
+
+    /// Returns the signing keypair, fails if it is not set
+    pub fn require_signing_key(&self) -> Result<KeyPair, DatexJsError> {
+        self.signing_key.clone().ok_or_else(|| {
+            DatexJsError::new(
+                JsErrorKind::Crypto,
+                "MissingSigningKey",
+                "No signing key is set for this runtime",
+            )
+        })
+    }
+
+    /// Returns the encryption keypair, fails if it is not set
+    pub fn require_encryption_key(&self) -> Result<KeyPair, DatexJsError> {
+        self.encryption_key.clone().ok_or_else(|| {
+            DatexJsError::new(
+                JsErrorKind::Crypto,
+                "MissingEncryptionKey",
+                "No encryption key is set for this runtime",
+            )
+        })
+    }
+}
+
+/// Encrypts an identity with a password.
+/// A random data key is wrapped with the wrapping key (RFC 3394) and
+/// used to encrypt the identity with AES-CTR. All preceding bytes are
+/// authenticated with an HMAC-SHA256 with a key derived from the data key.
+/// Format: magic, version, KDF identifier, KDF iterations (u32, little
+/// endian), salt, wrapped data key, IV, encrypted identity, MAC
+pub async fn wrap_identity(
+    identity: &EndpointIdentity,
+    wrapping_key: &WrappingKey,
+) -> Result<Vec<u8>, DatexJsError> {
+    let json =
+        JSON::stringify(&to_js_value(identity).map_err(conversion_error)?)
+            .map_err(|_| {
+                invalid_identity("Failed to serialize the identity")
+            })?;
+    let data_key = random_array::<32>()?;
+    let iv = random_array::<IV_LENGTH>()?;
+    let wrapped_key =
+        CryptoImpl::key_wrap_rfc3394(&wrapping_key.key, &data_key)
+            .await
+            .map_err(crypto_error)?;
+    let encrypted = CryptoImpl::aes_ctr_encrypt(
+        &data_key,
+        &iv,
+        String::from(json).as_bytes(),
+    )
+    .await
+    .map_err(crypto_error)?;
+
+    let mut bytes = IDENTITY_MAGIC.to_vec();
+    bytes.push(IDENTITY_VERSION);
+    bytes.push(KDF_PBKDF2_SHA256);
+    bytes.extend_from_slice(&wrapping_key.iterations.to_le_bytes());
+    bytes.extend_from_slice(&wrapping_key.salt);
+    bytes.extend_from_slice(&wrapped_key);
+    bytes.extend_from_slice(&iv);
+    bytes.extend_from_slice(&encrypted);
+    let mac = hmac_sha256(&mac_key(&data_key).await?, &bytes).await?;
+    bytes.extend_from_slice(&mac);
+    Ok(bytes)
+}
+
+async fn mac_key(data_key: &[u8; 32]) -> Result<[u8; 32], DatexJsError> {
+    CryptoImpl::hkdf_sha256(data_key, MAC_KEY_SALT)
+        .await
+        .map_err(crypto_error)
+}
+
+/// Decrypts an identity that was encrypted with `wrap_identity`.
+/// Fails if the password is empty or wrong or the identity was modified.
+pub async fn unwrap_identity(
+    bytes: &[u8],
+    password: &str,
+) -> Result<EndpointIdentity, DatexJsError> {
+    let Some(payload) = bytes.strip_prefix(IDENTITY_MAGIC.as_slice()) else {
+        return Err(invalid_identity("Missing identity header"));
+    };
+    let Some((&version, payload)) = payload.split_first() else {
+        return Err(invalid_identity("Missing identity version"));
+    };
+    if version != IDENTITY_VERSION {
+        return Err(DatexJsError::new(
+            JsErrorKind::Crypto,
+            "UnsupportedIdentityVersion",
+            format!(
+                "Identity version {version} is not supported, expected version {IDENTITY_VERSION}"
+            ),
+        ));
+    }
+    if payload.len()
+        < 5 + SALT_LENGTH + WRAPPED_KEY_LENGTH + IV_LENGTH + MAC_LENGTH
+    {
+        return Err(invalid_identity("The identity is truncated"));
+    }
+    let (authenticated, mac) = bytes.split_at(bytes.len() - MAC_LENGTH);
+    let payload = &payload[..payload.len() - MAC_LENGTH];
+    let (&kdf, payload) = payload.split_first().unwrap();
+    if kdf != KDF_PBKDF2_SHA256 {
+        return Err(invalid_identity(format!(
+            "Unknown key derivation function {kdf}"
+        )));
+    }
+    let (iterations, payload) = payload.split_at(4);
+    let iterations = u32::from_le_bytes(iterations.try_into().unwrap());
+    if iterations == 0 || iterations > MAX_PBKDF2_ITERATIONS {
+        return Err(invalid_identity(format!(
+            "Invalid number of key derivation iterations: {iterations}"
+        )));
+    }
+    let (salt, payload) = payload.split_at(SALT_LENGTH);
+    let (wrapped_key, payload) = payload.split_at(WRAPPED_KEY_LENGTH);
+    let (iv, encrypted) = payload.split_at(IV_LENGTH);
+
+    let salt: [u8; SALT_LENGTH] = salt.try_into().unwrap();
+    let wrapping_key =
+        WrappingKey::derive_with_params(password, salt, iterations).await?;
+    // the integrity check of the key unwrapping fails for a wrong password
+    let data_key: [u8; 32] = CryptoImpl::key_unwrap_rfc3394(
+        &wrapping_key.key,
+        wrapped_key,
+    )
+    .await
+    .ok()
+    .and_then(|key| key.try_into().ok())
+    .ok_or_else(|| {
+        DatexJsError::new(
+            JsErrorKind::Crypto,
+            "InvalidPassword",
+            "The identity could not be decrypted with the given password",
+        )
+    })?;
+    let expected_mac =
+        hmac_sha256(&mac_key(&data_key).await?, authenticated).await?;
+    if !mac_eq(mac, &expected_mac) {
+        return Err(invalid_identity(
+            "The identity was modified, the MAC does not match",
+        ));
+    }
+    let iv: [u8; IV_LENGTH] = iv.try_into().unwrap();
+    let decrypted = CryptoImpl::aes_ctr_decrypt(&data_key, &iv, encrypted)
+        .await
+        .map_err(crypto_error)?;
+    let json = std::str::from_utf8(&decrypted)
+        .map_err(|_| invalid_identity("The identity is not valid UTF-8"))?;
+    let value = JSON::parse(json)
+        .map_err(|_| invalid_identity("The identity is not valid JSON"))?;
+    from_value(value).map_err(conversion_error)
+}
+
+/// Persists the identity of a runtime with a JS object that provides the
+/// methods `load(endpoint) -> Uint8Array | null` and `save(endpoint, data)`,
+/// which may return promises. The identity is stored encrypted.
+pub struct IdentityPersistence {
+    storage: JsValue,
+    wrapping_key: WrappingKey,
+}
+
+impl IdentityPersistence {
+    const METHODS: [&'static str; 2] = ["load", "save"];
+
+    pub async fn new(
+        storage: JsValue,
+        password: &str,
+    ) -> Result<IdentityPersistence, DatexJsError> {
+        for method in Self::METHODS {
+            if !has_method(&storage, method) {
+                return Err(DatexJsError::new(
+                    JsErrorKind::Storage,
+                    "InvalidIdentityStorage",
+                    format!(
+                        "The identity storage does not implement the method '{method}'"
+                    ),
+                ));
+            }
+        }
+        Ok(IdentityPersistence {
+            storage,
+            wrapping_key: WrappingKey::derive(password).await?,
+        })
+    }
+
+    /// Loads the stored identity of the endpoint, if any.
+    /// The stored identity must have been encrypted with the same password.
+    pub async fn load(
+        &self,
+        endpoint: &str,
+        password: &str,
+    ) -> Result<Option<EndpointIdentity>, DatexJsError> {
+        let data = call_method(&self.storage, "load", &[endpoint.into()])
+            .await
+            .map_err(storage_error)?;
+        if data.is_null() || data.is_undefined() {
+            return Ok(None);
+        }
+        let data = data.dyn_into::<Uint8Array>().map_err(|_| {
+            invalid_identity("Expected a Uint8Array from the identity storage")
+        })?;
+        unwrap_identity(&data.to_vec(), password).await.map(Some)
+    }
+
+    pub async fn save(
+        &self,
+        identity: &EndpointIdentity,
+    ) -> Result<(), DatexJsError> {
+        let data = wrap_identity(identity, &self.wrapping_key).await?;
+        call_method(
+            &self.storage,
+            "save",
+            &[
+                identity.endpoint.as_str().into(),
+                Uint8Array::from(data.as_slice()).into(),
+            ],
+        )
+        .await
+        .map(|_| ())
+        .map_err(storage_error)
+    }
+}
+
+fn storage_error(err: JsValue) -> DatexJsError {
+    DatexJsError::new(
+        JsErrorKind::Storage,
+        "IdentityStorageFailed",
+        format!("{err:?}"),
+    )
+}
+
+fn invalid_identity(message: impl Into<String>) -> DatexJsError {
+    DatexJsError::new(JsErrorKind::Crypto, "InvalidIdentity", message)
+}
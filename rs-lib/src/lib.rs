@@ -3,26 +3,39 @@
 
 extern crate core;
 
-use std::sync::Once;
+use std::{cell::RefCell, sync::Once};
 
-use serde_wasm_bindgen::from_value;
 // use datex_cli_core::CLI;
 
-use datex_core::{
-    compiler::{CompileOptions, compile_script, compile_template},
-    decompiler::decompile_body,
-    runtime::execution::{ExecutionInput, ExecutionOptions, execute_dxb_sync},
-};
+use datex_core::{decompiler::decompile_body, runtime::memory::Memory};
 use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
+use web_sys::js_sys::Function;
 
 mod runtime;
 use runtime::JSRuntime;
 
+pub mod block;
+pub mod compiler;
+pub mod crypto;
+pub mod execution_context;
+pub mod keystore;
+pub mod observer;
+pub mod runtime_config;
+pub mod storage;
+use block::parser::ParseBlockOptions;
+use compiler::{JSCompileOptions, compile_to_js_result};
+
 pub mod network;
 
 pub mod js_utils;
+use js_utils::{options_from_js_value, to_js_value};
 pub mod utils;
+use utils::{
+    errors::{DatexJsError, JsErrorKind, conversion_error, decompiler_error},
+    fatal_error,
+    logger::{self, RuntimeLogContext, parse_level_filter, parse_log_filters},
+};
 
 #[cfg(feature = "repl")]
 pub mod repl;
@@ -38,47 +51,187 @@ extern "C" {
 static INIT: Once = Once::new();
 
 #[derive(Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct JSDebugConfig {
     // optional log level for the runtime, can be "error", "warn", "info", "debug" or "trace"
-    // if not specified, no logs will be printed to the console
+    // if not specified, the global log level is used (no logs by default)
     pub log_level: Option<String>,
+    // optional comma separated log filters for single modules,
+    // e.g. "datex_core::network=trace,datex_core::runtime=off"
+    pub log_filters: Option<String>,
+    // optional callback that receives all log records of the runtime as objects
+    // { level, target, message, timestamp, endpoint } instead of the console
+    #[serde(default, with = "serde_wasm_bindgen::preserve")]
+    pub log_sink: JsValue,
+    // optional callback that is called with { message, location, endpoint }
+    // if a panic occurs, the runtime can not be used afterwards
+    #[serde(default, with = "serde_wasm_bindgen::preserve")]
+    pub on_fatal_error: JsValue,
 }
 
 impl JSDebugConfig {
-    pub fn get_log_level(&self) -> Option<log::Level> {
-        self.log_level.as_ref().map(|level| {
-            match level.to_lowercase().as_str() {
-                "error" => log::Level::Error,
-                "warn" => log::Level::Warn,
-                "info" => log::Level::Info,
-                "debug" => log::Level::Debug,
-                "trace" => log::Level::Trace,
-                _ => {
-                    log::warn!(
-                        "Invalid log level '{}', defaulting to 'info'",
-                        level
-                    );
-                    log::Level::Info
-                }
-            }
-        })
+    /// Applies the log level, filters and sink to the log context of a runtime
+    /// Fails if the log level or the filters are invalid
+    pub(crate) fn apply_logging(
+        &self,
+        log_context: &RuntimeLogContext,
+    ) -> Result<(), DatexJsError> {
+        let level = self
+            .log_level
+            .as_deref()
+            .map(parse_level_filter)
+            .transpose()?;
+        let filters = match &self.log_filters {
+            Some(filters) => parse_log_filters(filters)?,
+            None => vec![],
+        };
+        let sink = optional_function(&self.log_sink, "log_sink")?;
+
+        if let Some(level) = level {
+            log_context.set_log_level(None, level);
+        }
+        for (target, level) in filters {
+            log_context.set_log_level(target.as_deref(), level);
+        }
+        log_context.set_log_sink(sink);
+        Ok(())
+    }
+
+    pub(crate) fn fatal_error_callback(
+        &self,
+    ) -> Result<Option<Function>, DatexJsError> {
+        optional_function(&self.on_fatal_error, "on_fatal_error")
     }
 }
 
+/// Returns the function of an optional callback option (null or undefined means None)
+fn optional_function(
+    value: &JsValue,
+    option: &str,
+) -> Result<Option<Function>, DatexJsError> {
+    if value.is_null() || value.is_undefined() {
+        return Ok(None);
+    }
+    value.clone().dyn_into::<Function>().map(Some).map_err(|_| {
+        DatexJsError::new(
+            JsErrorKind::Conversion,
+            "InvalidCallback",
+            format!("The option '{option}' must be a function"),
+        )
+    })
+}
+
 #[wasm_bindgen]
 pub async fn create_runtime(
     config: JsValue,
     debug_config: JsValue,
-) -> JSRuntime {
+) -> Result<JSRuntime, DatexJsError> {
+    // a malformed debug config is rejected instead of being ignored
     let debug_config: Option<JSDebugConfig> =
-        from_value(debug_config).unwrap_or_default();
+        options_from_js_value(debug_config).map_err(|e| {
+            DatexJsError::new(
+                JsErrorKind::Conversion,
+                "InvalidDebugConfig",
+                "Invalid debug config",
+            )
+            .with_cause(e)
+        })?;
+    // the panic hook and the logger are shared by all runtimes,
+    // the debug config is only applied to the log context of the new runtime
     INIT.call_once(|| {
-        console_error_panic_hook::set_once();
-        // create a logger that logs to the browser console, with the log level specified in the debug config
-        if let Some(log_level) = debug_config.and_then(|d| d.get_log_level()) {
-            wasm_logger::init(wasm_logger::Config::new(log_level));
-        }
+        fatal_error::init_panic_hook();
+        logger::init_logger();
     });
 
-    JSRuntime::run(config).await
+    JSRuntime::run(config, debug_config).await
+}
+
+/// Change the global log level after startup, either the default level
+/// or the level of a single target module (e.g. "datex_core::network").
+/// The global level applies to all runtimes without their own log level.
+#[wasm_bindgen]
+pub fn set_log_level(
+    level: &str,
+    target: Option<String>,
+) -> Result<(), DatexJsError> {
+    let level = logger::parse_level_filter(level)?;
+    logger::set_log_level(target.as_deref(), level);
+    Ok(())
+}
+
+/// Set a global callback that receives all log records as objects
+/// { level, target, message, timestamp, endpoint }, or null to log to the console.
+/// Runtimes with their own log sink do not use the global sink.
+#[wasm_bindgen]
+pub fn set_log_sink(sink: Option<Function>) {
+    logger::set_log_sink(sink);
+}
+
+/// Validate a runtime config without creating a runtime.
+/// Returns an object `{ valid, issues }`, where each issue contains
+/// the affected `field` (if any), an error `code` and a `message`.
+#[wasm_bindgen]
+pub fn validate_runtime_config(
+    config: JsValue,
+) -> Result<JsValue, DatexJsError> {
+    to_js_value(&runtime_config::validate_runtime_config(config))
+        .map_err(conversion_error)
+}
+
+/// Compile a script to a DXB body without executing it.
+/// If `{ inserted_values }` are given, the script is compiled as a template
+/// and the DIF values are inserted for the `?` placeholders.
+/// Returns an object containing the compiled body and compiler diagnostics.
+/// Inserted values must not contain references, since no runtime memory
+/// is available.
+#[wasm_bindgen]
+pub fn compile(
+    script: &str,
+    options: JsValue,
+) -> Result<JsValue, DatexJsError> {
+    let options: JSCompileOptions = options_from_js_value(options)?;
+    let memory = RefCell::new(Memory::default());
+    let inserted_values = options
+        .inserted_values
+        .map(|values| {
+            values
+                .into_iter()
+                .map(|value| value.to_value_container(&memory))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .transpose()
+        .map_err(|_| {
+            DatexJsError::new(
+                JsErrorKind::Conversion,
+                "ReferenceNotFound",
+                "Inserted values must not contain references when compiling without a runtime",
+            )
+        })?;
+    compile_to_js_result(script, inserted_values.as_deref())
+}
+
+/// Decompile a raw DXB body (e.g. the body of a block captured by a block
+/// interceptor) to a DATEX source string, using the given decompile options.
+#[wasm_bindgen]
+pub fn decompile_dxb(
+    dxb_body: &[u8],
+    decompile_options: JsValue,
+) -> Result<String, DatexJsError> {
+    let decompile_options = options_from_js_value(decompile_options)?;
+    decompile_body(dxb_body, decompile_options).map_err(decompiler_error)
+}
+
+/// Parse raw block bytes (e.g. a block captured by a block interceptor)
+/// into an object describing the routing header, receivers, block header
+/// flags, signature, encryption status and body.
+/// If `{ decompile: true }` is passed, unencrypted bodies are decompiled
+/// with the optional `decompile_options`.
+#[wasm_bindgen]
+pub fn parse_block(
+    bytes: &[u8],
+    options: JsValue,
+) -> Result<JsValue, DatexJsError> {
+    let options: ParseBlockOptions = options_from_js_value(options)?;
+    to_js_value(&block::parser::parse_block(bytes, options)?)
+        .map_err(conversion_error)
 }
@@ -13,8 +13,8 @@ use datex_core::{
         com_interfaces::com_interface::{
             ComInterfaceUUID,
             factory::{
-                ComInterfaceConfiguration, SendCallback, SendFailure,
-                SendSuccess, SocketConfiguration, SocketProperties,
+                ComInterfaceConfiguration, SendCallback, SendSuccess,
+                SocketConfiguration, SocketProperties,
             },
             properties::ComInterfaceProperties,
             socket::ComInterfaceSocketUUID,
@@ -25,20 +25,29 @@ use datex_core::{
         deserializer::from_value_container, serializer::to_value_container,
     },
     utils::uuid::UUID,
-    values::{
-        core_values::endpoint::Endpoint, value_container::ValueContainer,
-    },
+    values::value_container::ValueContainer,
 };
 use js_sys::{Function, JsFunction1, Object, Promise, Reflect};
 use log::{error, info};
 use serde_wasm_bindgen::from_value;
-use std::{ops::Deref, rc::Rc, str::FromStr};
+use std::{ops::Deref, rc::Rc};
 use wasm_bindgen::prelude::*;
 use wasm_bindgen_futures::{JsFuture, future_to_promise};
 use web_sys::js_sys::{self};
 
-use crate::js_utils::{
-    dif_js_value_to_value_container, value_container_to_dif_js_value,
+use crate::{
+    block::processor::{BlockProcessor, BlockWriter},
+    js_utils::{
+        dif_js_value_to_value_container, parse_endpoint,
+        value_container_to_dif_js_value,
+    },
+    network::errors::JsComInterfaceCreateError,
+    runtime::{ClosedFlag, ensure_open},
+    utils::{
+        callbacks::JsCallbackRegistry,
+        errors::{DatexJsError, JsErrorKind},
+        logger::RuntimeLogContext,
+    },
 };
 
 #[wasm_bindgen]
@@ -46,6 +55,10 @@ use crate::js_utils::{
 pub struct JSComHub {
     // ignore for wasm bindgen
     pub(crate) runtime: Runtime,
+    callbacks: JsCallbackRegistry,
+    log_context: RuntimeLogContext,
+    block_processor: BlockProcessor,
+    closed: ClosedFlag,
 }
 
 // wrapper around AsyncGenerator that implements Drop
@@ -71,8 +84,20 @@ impl Deref for JsReadableStream {
  * Internal impl of the JSRuntime, not exposed to JavaScript
  */
 impl JSComHub {
-    pub fn new(runtime: Runtime) -> JSComHub {
-        let com_hub = JSComHub { runtime };
+    pub fn new(
+        runtime: Runtime,
+        callbacks: JsCallbackRegistry,
+        log_context: RuntimeLogContext,
+        block_processor: BlockProcessor,
+        closed: ClosedFlag,
+    ) -> JSComHub {
+        let com_hub = JSComHub {
+            runtime,
+            callbacks,
+            log_context,
+            block_processor,
+            closed,
+        };
         com_hub.register_default_interface_factories();
         com_hub
     }
@@ -81,6 +106,14 @@ impl JSComHub {
         self.runtime.com_hub()
     }
 
+    pub fn block_processor(&self) -> &BlockProcessor {
+        &self.block_processor
+    }
+
+    fn ensure_open(&self) -> Result<(), DatexJsError> {
+        ensure_open(&self.closed)
+    }
+
     pub(crate) async fn create_interface_internal(
         &self,
         interface_type: String,
@@ -89,17 +122,22 @@ impl JSComHub {
     ) -> Result<ComInterfaceUUID, ComInterfaceCreateError> {
         let runtime = self.runtime.clone();
         let com_hub = runtime.com_hub();
-        let (interface, ready_receiver) = com_hub
-            .create_interface(
-                &interface_type,
-                setup_data,
-                InterfacePriority::from(priority),
-            )
-            .await?;
-        if let Some(ready_receiver) = ready_receiver {
-            let _ = ready_receiver.await;
-        }
-        Ok(interface)
+        // the interfaces of datex-core get the block processor of the
+        // runtime while they are created
+        let creation = self.block_processor.scope(async {
+            let (interface, ready_receiver) = com_hub
+                .create_interface(
+                    &interface_type,
+                    setup_data,
+                    InterfacePriority::from(priority),
+                )
+                .await?;
+            if let Some(ready_receiver) = ready_receiver {
+                let _ = ready_receiver.await;
+            }
+            Ok(interface)
+        });
+        self.log_context.scope(creation).await
     }
 
     // NOTE: must be separate internal funciton since async gen block does not work in combination with
@@ -110,19 +148,33 @@ impl JSComHub {
         factory: js_sys::Function,
     ) {
         let runtime = self.runtime.clone();
+        let factory = self.callbacks.register(factory);
+        // the interface, its sockets and send callbacks are polled by tasks of
+        // the com hub, the log context of the runtime is propagated to them
+        let log_context = self.log_context.clone();
+        let block_processor = self.block_processor.clone();
         self.com_hub().register_dyn_interface_factory(
             interface_type,
             Rc::new(move |setup_data| {
                 let factory = factory.clone();
                 let runtime = runtime.clone();
-
-                Box::pin(async move {
+                let log_context = log_context.clone();
+                let block_processor = block_processor.clone();
+
+                Box::pin(log_context.clone().scope(async move {
+                    if factory.is_released() {
+                        return Err(ComInterfaceCreateError::connection_error_with_details(
+                            "Interface factory was released"
+                        ));
+                    }
+                    // the endpoint of the runtime is passed to the factory, so that
+                    // a factory that is shared by multiple runtimes can distinguish them
                     let interface_configuration_promise = factory
-                        .call1(
-                            &JsValue::UNDEFINED,
+                        .call2(
                             &value_container_to_dif_js_value(
                                 &setup_data,
                             ),
+                            &JsValue::from(runtime.endpoint().to_string()),
                         )
                         .map_err(|e| {
                             error!("Error calling interface factory: {:?}", e);
@@ -154,7 +206,7 @@ impl JSComHub {
                     Ok(ComInterfaceConfiguration::new(
                         properties,
                         has_single_socket,
-                        async gen move {
+                        log_context.clone().scope(async gen move {
                             loop {
                                 let read_result = match JsFuture::from(new_sockets_reader.read()).await {
                                     Ok(result) => result,
@@ -168,21 +220,33 @@ impl JSComHub {
                                     return;
                                 }
 
-                                let (socket_properties, socket_iterator, send_callback) = match JSComHub::parse_socket_configuration(&read_result.get_value()) {
+                                let (socket_properties, socket_uuid, socket_iterator, send_callback) = match JSComHub::parse_socket_configuration(&read_result.get_value()) {
                                     Ok(result) => result,
                                     Err(e) => {
                                         error!("Error parse_socket_configuration: {:?}", e);
                                         return yield Err(());
                                     }
                                 };
-                                let send_callback = Rc::new(send_callback);
+                                // blocks and key announcements of the processor
+                                // are passed to the send callback of the socket
+                                let block_writer: BlockWriter = Rc::new(move |bytes: Vec<u8>| {
+                                    let result = send_callback
+                                        .call1(&JsValue::UNDEFINED, &JsValue::from(bytes))
+                                        .map(|_| ())
+                                        .map_err(|e| error!("Error calling send callback: {:?}", e));
+                                    Box::pin(async move { result })
+                                });
+                                let send_block_writer = block_writer.clone();
                                 let socket_data_reader = socket_iterator.get_reader()
                                     .unchecked_into::<web_sys::ReadableStreamDefaultReader>();
                                 let socket_data_reader_clone = socket_data_reader.clone();
 
+                                let send_log_context = log_context.clone();
+                                let processor = block_processor.clone();
+                                let send_processor = block_processor.clone();
                                 yield Ok(SocketConfiguration::new(
                                     socket_properties,
-                                    Some(async gen move {
+                                    Some(log_context.scope(async gen move {
                                         loop {
                                             let read_result = match JsFuture::from(socket_data_reader.read()).await {
                                                 Ok(result) => result,
@@ -203,31 +267,29 @@ impl JSComHub {
                                                 }
                                             };
                                             let block_bytes = js_sys::Uint8Array::new(&block_bytes).to_vec();
-                                            yield Ok(block_bytes);
+                                            if let Some(block_bytes) = processor.incoming(block_bytes, Some(&socket_uuid), &block_writer).await {
+                                                yield Ok(block_bytes);
+                                            }
                                         }
-                                    }),
+                                    })),
                                     Some(SendCallback::new_async(move |dxb_block| {
-                                        let send_callback = send_callback.clone();
-                                        async move {
-                                            send_callback.call1(&JsValue::UNDEFINED, &JsValue::from(dxb_block.to_bytes()))
-                                                .map_err(|e| {
-                                                    error!("Error calling send callback: {:?}", e);
-                                                    SendFailure(Box::new(dxb_block))
-                                                })
-                                                .map(|_| ())
-                                        }
+                                        let writer = send_block_writer.clone();
+                                        let processor = send_processor.clone();
+                                        send_log_context.scope(async move {
+                                            processor.send(dxb_block, &writer).await
+                                        })
                                     })),
                                     Some(async move || {
                                         let _ = JsFuture::from(socket_data_reader_clone.cancel()).await;
                                     })
                                 ));
                             }
-                        },
+                        }),
                         Some(async move || {
                             let _ = JsFuture::from(new_sockets_reader_clone.cancel()).await;
                         })
                     ))
-                })
+                }))
             }),
         );
     }
@@ -267,7 +329,7 @@ impl JSComHub {
     fn parse_socket_configuration(
         socket_configuration: &JsValue,
     ) -> Result<
-        (SocketProperties, JsReadableStream, Function),
+        (SocketProperties, String, JsReadableStream, Function),
         serde_wasm_bindgen::Error,
     > {
         let properties =
@@ -275,11 +337,8 @@ impl JSComHub {
                 .and_then(|v| v.dyn_into::<Object>())?;
 
         // add uuid to properties since it is not set by the user but is required for the SocketProperties struct
-        Reflect::set(
-            &properties,
-            &"uuid".into(),
-            &ComInterfaceSocketUUID::new().to_string().into(),
-        )?;
+        let uuid = ComInterfaceSocketUUID::new().to_string();
+        Reflect::set(&properties, &"uuid".into(), &uuid.as_str().into())?;
 
         let properties: SocketProperties = from_value(properties.into())?;
 
@@ -294,7 +353,7 @@ impl JSComHub {
             Reflect::get(socket_configuration, &"send_callback".into())
                 .and_then(|v| v.dyn_into::<Function>())?;
 
-        Ok((properties, JsReadableStream(iterator), send_callback))
+        Ok((properties, uuid, JsReadableStream(iterator), send_callback))
     }
 }
 
@@ -316,12 +375,16 @@ impl JSComHub {
         // self.com_hub().register_async_interface_factory::<crate::network::com_interfaces::webrtc_js_interface::WebRTCJSInterface>();
     }
 
+    /// Registers a JS interface factory on the ComHub of this runtime only.
+    /// The factory is called with the setup data and the endpoint of the runtime.
     pub fn register_interface_factory(
         &mut self,
         interface_type: String,
         factory: js_sys::Function,
-    ) {
+    ) -> Result<(), DatexJsError> {
+        self.ensure_open()?;
         self.register_interface_factory_internal(interface_type, factory);
+        Ok(())
     }
 
     pub async fn create_interface(
@@ -329,43 +392,107 @@ impl JSComHub {
         interface_type: String,
         setup_data: JsValue,
         priority: Option<u16>,
-    ) -> Result<String, JsError> {
+    ) -> Result<String, DatexJsError> {
+        self.ensure_open()?;
         let setup_data =
             dif_js_value_to_value_container(setup_data, self.runtime.memory())
-                .map_err(|e| JsError::new(&format!("{e:?}")))?;
+                .map_err(|e| {
+                    DatexJsError::from_debug(
+                        JsErrorKind::Conversion,
+                        "InvalidSetupData",
+                        &e,
+                    )
+                })?;
         let interface = self
             .create_interface_internal(interface_type, setup_data, priority)
             .await
-            .map_err(|e| JsError::new(&format!("{e:?}")))?;
+            .map_err(JsComInterfaceCreateError)?;
         Ok(interface.to_string())
     }
 
     pub async fn remove_interface(
         &self,
         interface_uuid: String,
-    ) -> Result<(), JsError> {
+    ) -> Result<(), DatexJsError> {
         let interface_uuid = ComInterfaceUUID::try_from(interface_uuid)
-            .map_err(|e| JsError::new(&format!("{e:?}")))?;
+            .map_err(|e| {
+                DatexJsError::from_debug(
+                    JsErrorKind::Conversion,
+                    "InvalidInterfaceUUID",
+                    &e,
+                )
+            })?;
         let runtime = self.runtime.clone();
         let com_hub = runtime.com_hub();
         com_hub
             .remove_interface(interface_uuid.clone())
             .await
-            .map_err(|_| JsError::new("Failed to remove interface"))
+            .map_err(|e| {
+                DatexJsError::new(
+                    JsErrorKind::ComHub,
+                    "RemoveInterfaceFailed",
+                    "Failed to remove interface",
+                )
+                .with_cause(DatexJsError::from_debug(
+                    JsErrorKind::ComHub,
+                    "ComHubError",
+                    &e,
+                ))
+            })
+    }
+
+    /// Removes all interfaces (and their sockets) from the com hub.
+    /// All interfaces are removed even if removing one of them fails,
+    /// the first error is returned afterwards
+    pub async fn remove_all_interfaces(&self) -> Result<(), DatexJsError> {
+        let interface_uuids: Vec<String> = self
+            .com_hub()
+            .get_metadata()
+            .interfaces
+            .iter()
+            .map(|interface| interface.uuid.to_string())
+            .collect();
+        let mut first_error = None;
+        for interface_uuid in interface_uuids {
+            if let Err(e) = self.remove_interface(interface_uuid).await {
+                first_error.get_or_insert(e);
+            }
+        }
+        match first_error {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
     }
 
     pub async fn remove_socket(
         &self,
         socket_uuid: String,
-    ) -> Result<(), JsError> {
+    ) -> Result<(), DatexJsError> {
         let socket_uuid = ComInterfaceSocketUUID::try_from(socket_uuid)
-            .map_err(|e| JsError::new(&format!("{e:?}")))?;
+            .map_err(|e| {
+                DatexJsError::from_debug(
+                    JsErrorKind::Conversion,
+                    "InvalidSocketUUID",
+                    &e,
+                )
+            })?;
         let runtime = self.runtime.clone();
         let com_hub = runtime.com_hub();
         com_hub
             .remove_socket(socket_uuid.clone())
             .await
-            .map_err(|_| JsError::new("Failed to remove socket"))
+            .map_err(|e| {
+                DatexJsError::new(
+                    JsErrorKind::ComHub,
+                    "RemoveSocketFailed",
+                    "Failed to remove socket",
+                )
+                .with_cause(DatexJsError::from_debug(
+                    JsErrorKind::ComHub,
+                    "ComHubError",
+                    &e,
+                ))
+            })
     }
 
     pub fn get_metadata_string(&self) -> String {
@@ -381,9 +508,8 @@ impl JSComHub {
     pub async fn get_trace_string(
         &self,
         endpoint: String,
-    ) -> Result<Option<String>, JsError> {
-        let endpoint = Endpoint::from_str(&endpoint)
-            .map_err(|e| JsError::new(&format!("Invalid endpoint format: {:?}", e)))?;
+    ) -> Result<Option<String>, DatexJsError> {
+        let endpoint = parse_endpoint(&endpoint)?;
         let trace = self.com_hub().record_trace(endpoint).await;
         Ok(trace.map(|t| t.to_string()))
     }
@@ -391,9 +517,8 @@ impl JSComHub {
     pub async fn get_trace(
         &self,
         endpoint: String,
-    ) -> Result<Option<JsValue>, JsError> {
-        let endpoint = Endpoint::from_str(&endpoint)
-            .map_err(|e| JsError::new(&format!("Invalid endpoint format: {:?}", e)))?;
+    ) -> Result<Option<JsValue>, DatexJsError> {
+        let endpoint = parse_endpoint(&endpoint)?;
         let trace = self.com_hub().record_trace(endpoint).await;
         Ok(trace.map(|trace| serde_wasm_bindgen::to_value(&trace).unwrap()))
     }
@@ -401,10 +526,11 @@ impl JSComHub {
     pub fn register_outgoing_block_interceptor(
         &self,
         callback: js_sys::Function,
-    ) {
+    ) -> Result<(), DatexJsError> {
+        self.ensure_open()?;
+        let callback = self.callbacks.register(callback);
         self.com_hub().register_outgoing_block_interceptor(
             move |block, socket, endpoints| {
-                let this = JsValue::NULL;
                 let block_bytes =
                     js_sys::Uint8Array::from(block.to_bytes().as_slice());
                 let socket_uuid = JsValue::from_str(&socket.to_string());
@@ -414,7 +540,6 @@ impl JSComHub {
                         .push(&JsValue::from_str(&endpoint.to_string()));
                 }
                 if let Err(e) = callback.call3(
-                    &this,
                     &JsValue::from(block_bytes),
                     &socket_uuid,
                     &endpoints_array,
@@ -426,23 +551,23 @@ impl JSComHub {
                 }
             },
         );
+        Ok(())
     }
 
     pub fn register_incoming_block_interceptor(
         &self,
         callback: js_sys::Function,
-    ) {
+    ) -> Result<(), DatexJsError> {
+        self.ensure_open()?;
+        let callback = self.callbacks.register(callback);
         self.com_hub().register_incoming_block_interceptor(
             move |block, socket| {
-                let this = JsValue::NULL;
                 let block_bytes =
                     js_sys::Uint8Array::from(block.to_bytes().as_slice());
                 let socket_uuid = JsValue::from_str(&socket.to_string());
-                if let Err(e) = callback.call2(
-                    &this,
-                    &JsValue::from(block_bytes),
-                    &socket_uuid,
-                ) {
+                if let Err(e) =
+                    callback.call2(&JsValue::from(block_bytes), &socket_uuid)
+                {
                     error!(
                         "Error in incoming block interceptor callback: {:?}",
                         e
@@ -450,5 +575,6 @@ impl JSComHub {
                 }
             },
         );
+        Ok(())
     }
 }
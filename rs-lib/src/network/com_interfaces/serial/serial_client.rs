@@ -7,7 +7,7 @@ use datex_core::{
             com_interface::{
                 factory::{
                     ComInterfaceAsyncFactory, ComInterfaceAsyncFactoryResult,
-                    ComInterfaceConfiguration, SendCallback, SendFailure,
+                    ComInterfaceConfiguration, SendCallback,
                     SocketConfiguration, SocketProperties,
                 },
                 properties::{ComInterfaceProperties, InterfaceDirection},
@@ -18,6 +18,12 @@ use datex_core::{
 };
 
 use log::{debug, error};
+use std::rc::Rc;
+
+use crate::{
+    block::processor::{BlockProcessor, BlockWriter},
+    utils::logger::{RuntimeLogContext, in_scope},
+};
 use wasm_bindgen::JsCast;
 use wasm_bindgen_futures::JsFuture;
 use web_sys::{
@@ -61,14 +67,30 @@ impl SerialClientInterfaceSetupDataJS {
             .unwrap();
         let writable = port.writable();
         let writer = writable.get_writer().unwrap();
+        let close_reader = reader.clone();
+        let close_writer = writer.clone();
+        // the reader and send callback are polled by tasks of the com hub,
+        // the log context of the runtime is propagated to them
+        let log_context = RuntimeLogContext::current();
+        let processor = BlockProcessor::current();
+        let send_processor = processor.clone();
+        // blocks and key announcements of the processor are written
+        // to the serial port
+        let block_writer: BlockWriter = Rc::new(move |bytes: Vec<u8>| {
+            let js_array = Uint8Array::from(bytes.as_slice());
+            let write = JsFuture::from(writer.write_with_chunk(&js_array));
+            Box::pin(async move { write.await.map(|_| ()).map_err(|_| ()) })
+        });
+        let send_block_writer = block_writer.clone();
 
         Ok(ComInterfaceConfiguration::new_single_socket(
             ComInterfaceProperties {
                 ..Self::get_default_properties()
             },
-            SocketConfiguration::new_in_out(
+            SocketConfiguration::new(
                 SocketProperties::new(InterfaceDirection::InOut, 1),
-                async gen move {
+                // the reader ends when the reader is cancelled on close
+                Some(in_scope(log_context.as_ref(), async gen move {
                     loop {
                         let result = JsFuture::from(reader.read()).await;
                         match result {
@@ -96,7 +118,12 @@ impl SerialClientInterfaceSetupDataJS {
                                         .unwrap()
                                         .to_vec();
                                     debug!("Received bytes: {bytes:?}");
-                                    yield Ok(bytes);
+                                    if let Some(bytes) = processor
+                                        .incoming(bytes, None, &block_writer)
+                                        .await
+                                    {
+                                        yield Ok(bytes);
+                                    }
                                 }
                             }
                             Err(e) => {
@@ -105,17 +132,22 @@ impl SerialClientInterfaceSetupDataJS {
                             }
                         }
                     }
-                },
-                SendCallback::new_async(move |block: DXBBlock| {
-                    let js_array =
-                        Uint8Array::from(block.to_bytes().as_slice());
-                    let promise = writer.write_with_chunk(&js_array);
-                    async move {
-                        JsFuture::from(promise)
-                            .await
-                            .map_err(|e| SendFailure(Box::new(block)))
-                            .map(|_| ())
-                    }
+                })),
+                Some(SendCallback::new_async(move |block: DXBBlock| {
+                    let writer = send_block_writer.clone();
+                    let processor = send_processor.clone();
+                    in_scope(log_context.as_ref(), async move {
+                        processor.send(block, &writer).await
+                    })
+                })),
+                Some(async move || {
+                    // cancel the pending read and release the stream locks
+                    // so that the port can be closed
+                    let _ = JsFuture::from(close_reader.cancel()).await;
+                    close_reader.release_lock();
+                    let _ = JsFuture::from(close_writer.close()).await;
+                    close_writer.release_lock();
+                    let _ = JsFuture::from(port.close()).await;
                 }),
             ),
         ))
@@ -1,13 +1,16 @@
 use std::{cell::RefCell, rc::Rc};
 
-use crate::define_registry;
+use crate::{
+    define_registry,
+    utils::errors::{DatexJsError, JsErrorKind},
+};
 use datex_core::network::com_hub::InterfacePriority;
 use datex_core::network::com_interfaces::{
     com_interface::ComInterface,
     default_com_interfaces::webrtc::matchbox_client_interface::MatchboxClientInterface,
 };
 use log::error;
-use wasm_bindgen::{prelude::wasm_bindgen, JsError, JsValue};
+use wasm_bindgen::{prelude::wasm_bindgen, JsValue};
 use wasm_bindgen_futures::future_to_promise;
 use web_sys::js_sys::Promise;
 
@@ -21,10 +24,20 @@ impl MatchboxClientRegistry {
         future_to_promise(async move {
             let mut webrtc_interface =
                 MatchboxClientInterface::new_reliable(&address_clone, None)
-                    .map_err(|e| JsError::new(&format!("{e:?}")))?;
+                    .map_err(|e| {
+                        DatexJsError::from_debug(
+                            JsErrorKind::Network,
+                            "CreateInterfaceFailed",
+                            &e,
+                        )
+                    })?;
             webrtc_interface.open().await.map_err(|e| {
                 error!("Failed to open WebRTC interface: {e:?}");
-                JsError::new(&format!("{e:?}"))
+                DatexJsError::from_debug(
+                    JsErrorKind::Network,
+                    "OpenInterfaceFailed",
+                    &e,
+                )
             })?;
             let interface_uuid = webrtc_interface.get_uuid().clone();
             com_hub
@@ -32,7 +45,13 @@ impl MatchboxClientRegistry {
                     Rc::new(RefCell::new(webrtc_interface)),
                     InterfacePriority::default(),
                 )
-                .map_err(|e| JsError::new(&format!("{e:?}")))?;
+                .map_err(|e| {
+                    DatexJsError::from_debug(
+                        JsErrorKind::ComHub,
+                        "AddInterfaceFailed",
+                        &e,
+                    )
+                })?;
             Ok(JsValue::from_str(&interface_uuid.0.to_string()))
         })
     }
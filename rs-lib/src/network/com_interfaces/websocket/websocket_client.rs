@@ -12,7 +12,9 @@ use std::{
 };
 
 use datex_core::{
-    channel::mpsc::{UnboundedReceiver, create_unbounded_channel},
+    channel::mpsc::{
+        UnboundedReceiver, UnboundedSender, create_unbounded_channel,
+    },
     global::dxb_block::DXBBlock,
     network::{
         com_hub::errors::ComInterfaceCreateError,
@@ -20,8 +22,7 @@ use datex_core::{
             com_interface::{
                 factory::{
                     ComInterfaceAsyncFactoryResult, ComInterfaceConfiguration,
-                    SendCallback, SendFailure, SocketConfiguration,
-                    SocketProperties,
+                    SendCallback, SocketConfiguration, SocketProperties,
                 },
                 properties::{ComInterfaceProperties, InterfaceDirection},
             },
@@ -33,6 +34,11 @@ use datex_core::{
     },
 };
 use log::info;
+
+use crate::{
+    block::processor::{BlockProcessor, BlockWriter},
+    utils::logger::{RuntimeLogContext, in_scope},
+};
 use url::Url;
 use wasm_bindgen::{JsCast, prelude::Closure};
 use web_sys::js_sys;
@@ -66,32 +72,53 @@ impl WebSocketClientInterfaceSetupDataJS {
             Self::create_websocket_client_connection(address.clone()).await?;
         let ws_rc = Rc::new(Mutex::new(ws.clone()));
 
-        // TODO: cleanup reader task
-        let mut reader =
+        let (mut reader, handlers) =
             Self::create_incoming_data_reader(ws.clone(), None).await;
+        // the reader and send callback are polled by tasks of the com hub,
+        // the log context of the runtime is propagated to them
+        let log_context = RuntimeLogContext::current();
+        let processor = BlockProcessor::current();
+        let send_processor = processor.clone();
+        // blocks and key announcements of the processor are written
+        // to the websocket
+        let block_writer: BlockWriter = Rc::new(move |bytes: Vec<u8>| {
+            let result = ws_rc
+                .lock()
+                .unwrap()
+                .send_with_u8_array(&bytes)
+                .map_err(|_| ());
+            Box::pin(async move { result })
+        });
+        let send_block_writer = block_writer.clone();
         Ok(ComInterfaceConfiguration::new_single_socket(
             ComInterfaceProperties {
                 name: Some(self.url.clone()),
                 ..Self::get_default_properties()
             },
-            SocketConfiguration::new_in_out(
+            SocketConfiguration::new(
                 SocketProperties::new(InterfaceDirection::InOut, 1),
-                async gen move {
-                    loop {
-                        while let Some(data) = reader.next().await {
+                // the reader ends when the websocket is closed
+                Some(in_scope(log_context.as_ref(), async gen move {
+                    while let Some(data) = reader.next().await {
+                        if let Some(data) =
+                            processor.incoming(data, None, &block_writer).await
+                        {
                             yield Ok(data);
                         }
                     }
-                },
-                SendCallback::new_async(move |block: DXBBlock| {
-                    let ws = ws_rc.clone();
-                    async move {
-                        ws.lock()
-                            .unwrap()
-                            .send_with_u8_array(&block.to_bytes())
-                            .map_err(|_| SendFailure(Box::new(block)))?;
-                        Ok(())
-                    }
+                })),
+                Some(SendCallback::new_async(move |block: DXBBlock| {
+                    let writer = send_block_writer.clone();
+                    let processor = send_processor.clone();
+                    in_scope(log_context.as_ref(), async move {
+                        processor.send(block, &writer).await
+                    })
+                })),
+                Some(async move || {
+                    // clear all event handlers and end the reader explicitly,
+                    // the onclose handler is not called anymore after clearing
+                    handlers.clear(&ws);
+                    let _ = ws.close();
                 }),
             ),
         ))
@@ -205,21 +232,20 @@ impl WebSocketClientInterfaceSetupDataJS {
         let fail_cell = Rc::new(RefCell::new(Some(fail_tx)));
 
         // onopen
-        {
+        let onopen = {
             let open_cell = Rc::clone(&open_cell);
-            let onopen = Closure::once(move |_e: web_sys::Event| {
+            Closure::once(move |_e: web_sys::Event| {
                 if let Some(tx) = open_cell.borrow_mut().take() {
                     let _ = tx.send(());
                 }
-            });
-            ws.set_onopen(Some(onopen.as_ref().unchecked_ref()));
-            onopen.forget();
-        }
+            })
+        };
+        ws.set_onopen(Some(onopen.as_ref().unchecked_ref()));
 
         // onerror
-        {
+        let onerror = {
             let fail_cell = Rc::clone(&fail_cell);
-            let onerror = Closure::once(move |e: web_sys::ErrorEvent| {
+            Closure::once(move |e: web_sys::ErrorEvent| {
                 if let Some(tx) = fail_cell.borrow_mut().take() {
                     let _ = tx.send(
                         ComInterfaceCreateError::connection_error_with_details(
@@ -227,15 +253,14 @@ impl WebSocketClientInterfaceSetupDataJS {
                         ),
                     );
                 }
-            });
-            ws.set_onerror(Some(onerror.as_ref().unchecked_ref()));
-            onerror.forget();
-        }
+            })
+        };
+        ws.set_onerror(Some(onerror.as_ref().unchecked_ref()));
 
         // onclose (before open)
-        {
+        let onclose = {
             let fail_cell = Rc::clone(&fail_cell);
-            let onclose = Closure::once(move |_e: web_sys::Event| {
+            Closure::once(move |_e: web_sys::Event| {
                 if let Some(tx) = fail_cell.borrow_mut().take() {
                     let _ = tx.send(
                         ComInterfaceCreateError::connection_error_with_details(
@@ -243,10 +268,9 @@ impl WebSocketClientInterfaceSetupDataJS {
                         ),
                     );
                 }
-            });
-            ws.set_onclose(Some(onclose.as_ref().unchecked_ref()));
-            onclose.forget();
-        }
+            })
+        };
+        ws.set_onclose(Some(onclose.as_ref().unchecked_ref()));
 
         futures::pin_mut!(open_rx);
         futures::pin_mut!(fail_rx);
@@ -257,60 +281,100 @@ impl WebSocketClientInterfaceSetupDataJS {
 
         use futures::{FutureExt, select};
 
-        select! {
-            _ = open_rx.fuse() => Ok(ws),
+        let result = select! {
+            _ = open_rx.fuse() => Ok(ws.clone()),
             err = fail_rx.fuse() => Err(err.unwrap_or(ComInterfaceCreateError::connection_error())),
             _ = timeout.fuse() => {
                 // Close the socket to avoid dangling connection attempt
                 let _ = ws.close();
                 Err(ComInterfaceCreateError::connection_error_with_details("Connection timed out"))
             }
-        }
+        };
+
+        // the connection handlers are not needed anymore, they are dropped
+        // after being removed from the websocket
+        ws.set_onopen(None);
+        ws.set_onerror(None);
+        ws.set_onclose(None);
+        result
     }
 
     /// Creates an incoming data reader that listens for messages and passes them via the returned receiver.
-    /// Also handles close and error events to clean up resources.
+    /// The receiver ends when the websocket is closed.
+    /// The returned handlers must be kept alive as long as the websocket is used.
     async fn create_incoming_data_reader(
         ws: web_sys::WebSocket,
-        mut close_tx: Option<oneshot::Sender<()>>,
-    ) -> UnboundedReceiver<Vec<u8>> {
-        let (mut tx, rx) = create_unbounded_channel::<Vec<u8>>();
+        close_tx: Option<oneshot::Sender<()>>,
+    ) -> (UnboundedReceiver<Vec<u8>>, WebSocketHandlers) {
+        let (tx, rx) = create_unbounded_channel::<Vec<u8>>();
+        // the sender is dropped when the websocket is closed to end the receiver
+        let tx = Rc::new(RefCell::new(Some(tx)));
 
-        let onmessage =
+        let onmessage = {
+            let tx = tx.clone();
             Closure::wrap(Box::new(move |e: web_sys::MessageEvent| {
-                if let Ok(buf) = e.data().dyn_into::<js_sys::ArrayBuffer>() {
+                if let Ok(buf) = e.data().dyn_into::<js_sys::ArrayBuffer>()
+                    && let Some(tx) = tx.borrow_mut().as_mut()
+                {
                     let array = js_sys::Uint8Array::new(&buf);
                     let mut data = vec![0; array.byte_length() as usize];
                     array.copy_to(&mut data[..]);
                     let _ = tx.start_send(data);
                 }
-            }) as Box<dyn FnMut(_)>);
+            }) as Box<dyn FnMut(_)>)
+        };
         ws.set_onmessage(Some(onmessage.as_ref().unchecked_ref()));
-        onmessage.forget();
 
         // onerror
-        let onerror = Closure::once(move |_: web_sys::ErrorEvent| {
+        let onerror = Closure::wrap(Box::new(move |_: web_sys::ErrorEvent| {
             // pass
-        });
+        }) as Box<dyn FnMut(_)>);
         ws.set_onerror(Some(onerror.as_ref().unchecked_ref()));
-        onerror.forget();
 
-        let ws_clone = ws.clone();
         // onclose
-        let onclose = Closure::once(move |e: web_sys::Event| {
-            if let Some(close_tx) = close_tx.take() {
-                let _ = close_tx.send(());
-                // clear event handlers
-                ws_clone.set_onmessage(None);
-                ws_clone.set_onopen(None);
-                ws_clone.set_onerror(None);
-                ws_clone.set_onclose(None);
-            }
-        });
+        let close_tx = RefCell::new(close_tx);
+        let onclose = {
+            let tx = tx.clone();
+            Closure::wrap(Box::new(move |_e: web_sys::Event| {
+                tx.borrow_mut().take();
+                if let Some(close_tx) = close_tx.borrow_mut().take() {
+                    let _ = close_tx.send(());
+                }
+            }) as Box<dyn FnMut(_)>)
+        };
         ws.set_onclose(Some(onclose.as_ref().unchecked_ref()));
-        onclose.forget();
 
-        rx
+        (
+            rx,
+            WebSocketHandlers {
+                tx,
+                _onmessage: onmessage,
+                _onerror: onerror,
+                _onclose: onclose,
+            },
+        )
+    }
+}
+
+/// Event handler closures of an open websocket,
+/// the closures are kept alive until the handlers are dropped
+struct WebSocketHandlers {
+    /// Sender of the incoming data reader, taken when the websocket is closed
+    tx: Rc<RefCell<Option<UnboundedSender<Vec<u8>>>>>,
+    _onmessage: Closure<dyn FnMut(web_sys::MessageEvent)>,
+    _onerror: Closure<dyn FnMut(web_sys::ErrorEvent)>,
+    _onclose: Closure<dyn FnMut(web_sys::Event)>,
+}
+
+impl WebSocketHandlers {
+    /// Removes the handlers from the websocket and drops the sender of the
+    /// incoming data reader so that the reader ends. The closures are
+    /// released when the handlers are dropped
+    fn clear(&self, ws: &web_sys::WebSocket) {
+        ws.set_onmessage(None);
+        ws.set_onerror(None);
+        ws.set_onclose(None);
+        self.tx.borrow_mut().take();
     }
 }
 
@@ -1,11 +1,13 @@
-use crate::wrap_error_for_js;
+use crate::{utils::errors::JsErrorKind, wrap_error_for_js};
 
 wrap_error_for_js!(
     JsComInterfaceError,
-    datex_core::network::com_interfaces::com_interface::error::ComInterfaceError
+    datex_core::network::com_interfaces::com_interface::error::ComInterfaceError,
+    JsErrorKind::ComHub
 );
 
 wrap_error_for_js!(
     JsComInterfaceCreateError,
-    datex_core::network::com_hub::errors::ComInterfaceCreateError
+    datex_core::network::com_hub::errors::ComInterfaceCreateError,
+    JsErrorKind::ComHub
 );
@@ -0,0 +1,477 @@
+use std::{
+    cell::RefCell,
+    rc::{Rc, Weak},
+};
+
+use datex_core::shared_values::{
+    observers::{ObserveOptions, TransceiverId},
+    pointer_address::PointerAddress,
+};
+use serde::Deserialize;
+use wasm_bindgen::{JsCast, JsValue, closure::Closure};
+use web_sys::js_sys::{self, Array, Date, Function, Reflect};
+
+use crate::utils::{
+    callbacks::JsCallback,
+    errors::{DatexJsError, JsErrorKind, conversion_error},
+    logger::spawn_local_in_current_scope,
+};
+
+/// Fallback interval for animation frame coalescing
+/// if `requestAnimationFrame` is not available (e.g. in Deno or Node.js)
+const FALLBACK_FRAME_MS: u32 = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Coalesce {
+    // updates are collected until the next microtask
+    Microtask,
+    // updates are collected until the next animation frame
+    AnimationFrame,
+}
+
+/// Options that control when the updates of a pointer are delivered
+/// to a JS observer. Without options, every update is delivered immediately.
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct DeliveryOptions {
+    #[serde(default)]
+    pub coalesce: Option<Coalesce>,
+    // deliver at most once per interval (leading and trailing)
+    #[serde(default)]
+    pub throttle_ms: Option<u32>,
+    // deliver after no update was received for the interval
+    #[serde(default)]
+    pub debounce_ms: Option<u32>,
+    // deliver the current value as a replace update
+    // instead of all collected updates
+    #[serde(default)]
+    pub latest_only: bool,
+}
+
+impl DeliveryOptions {
+    fn validate(&self) -> Result<(), DatexJsError> {
+        let modes = [
+            self.coalesce.is_some(),
+            self.throttle_ms.is_some(),
+            self.debounce_ms.is_some(),
+        ];
+        if modes.iter().filter(|mode| **mode).count() > 1 {
+            return Err(DatexJsError::new(
+                JsErrorKind::Conversion,
+                "ConflictingDeliveryOptions",
+                "Only one of 'coalesce', 'throttle_ms' and 'debounce_ms' can be set",
+            ));
+        }
+        Ok(())
+    }
+
+    fn is_immediate(&self) -> bool {
+        self.coalesce.is_none()
+            && self.throttle_ms.is_none()
+            && self.debounce_ms.is_none()
+    }
+}
+
+/// Observe options passed from JS, the DATEX observe options
+/// extended with the delivery options of the JS observer
+#[derive(Deserialize)]
+pub struct JSObserveOptions {
+    #[serde(flatten)]
+    pub options: ObserveOptions,
+    #[serde(flatten)]
+    pub delivery: DeliveryOptions,
+}
+
+impl JSObserveOptions {
+    pub fn parse(
+        value: JsValue,
+    ) -> Result<(ObserveOptions, DeliveryOptions), DatexJsError> {
+        let options: JSObserveOptions =
+            serde_wasm_bindgen::from_value(value).map_err(conversion_error)?;
+        options.delivery.validate()?;
+        Ok((options.options, options.delivery))
+    }
+}
+
+/// An update of an observed pointer, serialized as DIF update
+pub struct ObserverUpdate {
+    pub source_id: TransceiverId,
+    pub update: JsValue,
+}
+
+/// Returns the current value of the observed pointer as a `Replace`
+/// DIF update from the given source, `None` if the pointer is not in memory
+type CurrentValue = Box<dyn Fn(TransceiverId) -> Option<JsValue>>;
+
+#[derive(Default)]
+struct DeliveryState {
+    pending: Vec<ObserverUpdate>,
+    // set if the pending updates contain the updates of a batch
+    batch: bool,
+    // set while a coalesced flush is scheduled
+    scheduled: bool,
+    // generation of the pending timer, timers of older generations are ignored
+    timer: Option<u32>,
+    generation: u32,
+    // timestamp of the last delivery, used for throttling
+    last_delivery: f64,
+}
+
+/// Delivers the updates of a pointer to a JS observer callback
+/// according to the delivery options of the observer
+pub struct ObserverDelivery {
+    callback: JsCallback,
+    options: RefCell<DeliveryOptions>,
+    state: RefCell<DeliveryState>,
+    current_value: CurrentValue,
+}
+
+impl ObserverDelivery {
+    pub fn new(
+        callback: JsCallback,
+        options: DeliveryOptions,
+        current_value: impl Fn(TransceiverId) -> Option<JsValue> + 'static,
+    ) -> Rc<Self> {
+        Rc::new(ObserverDelivery {
+            callback,
+            options: RefCell::new(options),
+            state: RefCell::new(DeliveryState::default()),
+            current_value: Box::new(current_value),
+        })
+    }
+
+    /// Changes the delivery options, pending updates are delivered first
+    pub fn set_options(self: &Rc<Self>, options: DeliveryOptions) {
+        self.flush();
+        self.options.replace(options);
+    }
+
+    /// Drops all pending updates and cancels scheduled deliveries
+    pub fn cancel(&self) {
+        let mut state = self.state.borrow_mut();
+        state.pending.clear();
+        state.batch = false;
+        state.scheduled = false;
+        state.timer = None;
+    }
+
+    /// Delivers the updates of one notification, immediately or
+    /// scheduled depending on the delivery options. The updates of a
+    /// batch are always delivered as an array
+    pub fn deliver(self: &Rc<Self>, updates: Vec<ObserverUpdate>, batch: bool) {
+        let options = self.options.borrow().clone();
+        if options.is_immediate() {
+            self.call(updates, batch, options.latest_only);
+            return;
+        }
+        {
+            let mut state = self.state.borrow_mut();
+            state.pending.extend(updates);
+            state.batch |= batch;
+        }
+
+        if let Some(coalesce) = options.coalesce {
+            let scheduled =
+                std::mem::replace(&mut self.state.borrow_mut().scheduled, true);
+            if !scheduled {
+                self.schedule_coalesced(coalesce);
+            }
+        } else if let Some(interval) = options.throttle_ms {
+            let (elapsed, timer_pending) = {
+                let state = self.state.borrow();
+                (Date::now() - state.last_delivery, state.timer.is_some())
+            };
+            if timer_pending {
+                // the pending updates are delivered by the trailing timer
+            } else if elapsed >= interval as f64 {
+                self.flush();
+            } else {
+                self.schedule_timer((interval as f64 - elapsed) as u32);
+            }
+        } else if let Some(interval) = options.debounce_ms {
+            self.schedule_timer(interval);
+        }
+    }
+
+    /// Delivers all pending updates
+    pub fn flush(self: &Rc<Self>) {
+        let (pending, batch) = {
+            let mut state = self.state.borrow_mut();
+            state.scheduled = false;
+            state.timer = None;
+            (
+                std::mem::take(&mut state.pending),
+                std::mem::take(&mut state.batch),
+            )
+        };
+        if pending.is_empty() {
+            return;
+        }
+        self.state.borrow_mut().last_delivery = Date::now();
+        let latest_only = self.options.borrow().latest_only;
+        self.call(pending, batch, latest_only);
+    }
+
+    /// Calls the callback with a single update, or with an array if
+    /// multiple updates or the updates of a batch are delivered together.
+    /// With `latest_only`, the callback is called with a single `Replace`
+    /// update containing the current value of the pointer.
+    fn call(
+        &self,
+        mut updates: Vec<ObserverUpdate>,
+        batch: bool,
+        latest_only: bool,
+    ) {
+        let notification = if latest_only {
+            let Some(latest) = updates.pop() else {
+                return;
+            };
+            // the latest update is delivered if the pointer was removed
+            (self.current_value)(latest.source_id).unwrap_or(latest.update)
+        } else {
+            match updates.len() {
+                0 => return,
+                1 if !batch => updates.remove(0).update,
+                _ => updates
+                    .into_iter()
+                    .map(|update| update.update)
+                    .collect::<Array>()
+                    .into(),
+            }
+        };
+        let _ = self.callback.call1(&notification);
+    }
+
+    /// Schedules a flush after the interval, a previously scheduled timer
+    /// is cancelled. The pending updates are delivered immediately if
+    /// `setTimeout` is not available.
+    fn schedule_timer(self: &Rc<Self>, interval: u32) {
+        let generation = {
+            let mut state = self.state.borrow_mut();
+            state.generation = state.generation.wrapping_add(1);
+            state.timer = Some(state.generation);
+            state.generation
+        };
+        let delivery = Rc::downgrade(self);
+        let scheduled = set_timeout(interval, move || {
+            let Some(delivery) = delivery.upgrade() else {
+                return;
+            };
+            let due = delivery.state.borrow().timer == Some(generation);
+            if due {
+                delivery.flush();
+            }
+        });
+        if !scheduled {
+            self.flush();
+        }
+    }
+
+    fn schedule_coalesced(self: &Rc<Self>, coalesce: Coalesce) {
+        let delivery = Rc::downgrade(self);
+        match coalesce {
+            // spawned futures are polled in a microtask
+            Coalesce::Microtask => {
+                spawn_local_in_current_scope(
+                    async move { flush_weak(&delivery) },
+                )
+            }
+            Coalesce::AnimationFrame => {
+                let request_animation_frame = Reflect::get(
+                    &js_sys::global(),
+                    &"requestAnimationFrame".into(),
+                )
+                .ok()
+                .and_then(|function| function.dyn_into::<Function>().ok());
+                match request_animation_frame {
+                    Some(request_animation_frame) => {
+                        let callback = Closure::once_into_js(move || {
+                            flush_weak(&delivery)
+                        });
+                        let _ = request_animation_frame
+                            .call1(&JsValue::NULL, &callback);
+                    }
+                    None => self.schedule_timer(FALLBACK_FRAME_MS),
+                }
+            }
+        }
+    }
+}
+
+/// Calls the global `setTimeout`, the callback is freed after it was called.
+/// Returns false if the callback could not be scheduled.
+fn set_timeout(interval: u32, callback: impl FnOnce() + 'static) -> bool {
+    let set_timeout = Reflect::get(&js_sys::global(), &"setTimeout".into())
+        .ok()
+        .and_then(|function| function.dyn_into::<Function>().ok());
+    let Some(set_timeout) = set_timeout else {
+        return false;
+    };
+    set_timeout
+        .call2(
+            &JsValue::NULL,
+            &Closure::once_into_js(callback),
+            &JsValue::from(interval),
+        )
+        .is_ok()
+}
+
+fn flush_weak(delivery: &Weak<ObserverDelivery>) {
+    if let Some(delivery) = delivery.upgrade() {
+        delivery.flush();
+    }
+}
+
+/// Collects the notifications of JS pointer observers while a batch of
+/// updates is applied, so that every observer is notified once per batch.
+/// Batches can be nested, the notifications of an inner batch are
+/// delivered with the outermost batch
+#[derive(Clone, Default)]
+pub struct NotificationBatch(
+    Rc<RefCell<Vec<Vec<(Rc<ObserverDelivery>, ObserverUpdate)>>>>,
+);
+
+impl NotificationBatch {
+    /// Starts collecting notifications instead of delivering them
+    pub fn start(&self) {
+        self.0.borrow_mut().push(vec![]);
+    }
+
+    /// Delivers a notification to an observer,
+    /// or queues it if a batch is active
+    pub fn notify(
+        &self,
+        delivery: &Rc<ObserverDelivery>,
+        update: ObserverUpdate,
+    ) {
+        if let Some(queue) = self.0.borrow_mut().last_mut() {
+            queue.push((delivery.clone(), update));
+            return;
+        }
+        delivery.deliver(vec![update], false);
+    }
+
+    /// Ends the innermost batch. The collected notifications are passed to
+    /// the enclosing batch, or delivered if the batch is not nested.
+    /// Every observer is called with an array of its updates of the batch.
+    pub fn finish(&self) {
+        let queue = {
+            let mut batches = self.0.borrow_mut();
+            let Some(queue) = batches.pop() else {
+                return;
+            };
+            if let Some(outer) = batches.last_mut() {
+                outer.extend(queue);
+                return;
+            }
+            queue
+        };
+        let mut grouped: Vec<(Rc<ObserverDelivery>, Vec<ObserverUpdate>)> =
+            vec![];
+        for (delivery, update) in queue {
+            match grouped.iter_mut().find(|(d, _)| Rc::ptr_eq(d, &delivery)) {
+                Some((_, updates)) => updates.push(update),
+                None => grouped.push((delivery, vec![update])),
+            }
+        }
+        for (delivery, updates) in grouped {
+            delivery.deliver(updates, true);
+        }
+    }
+
+    /// Ends the innermost batch without delivering its notifications
+    pub fn discard(&self) {
+        self.0.borrow_mut().pop();
+    }
+}
+
+/// A pointer observer that was registered from JavaScript
+pub struct JsObserver {
+    pub address: PointerAddress,
+    pub observer_id: u32,
+    pub delivery: Rc<ObserverDelivery>,
+}
+
+/// Pointer observers that were registered from JavaScript with the
+/// delivery of their updates. The deliveries of removed observers are
+/// cancelled, so that no pending updates are delivered afterwards.
+#[derive(Clone, Default)]
+pub struct ObserverRegistry(Rc<RefCell<Vec<JsObserver>>>);
+
+impl ObserverRegistry {
+    pub fn add(
+        &self,
+        address: PointerAddress,
+        observer_id: u32,
+        delivery: Rc<ObserverDelivery>,
+    ) {
+        self.0.borrow_mut().push(JsObserver {
+            address,
+            observer_id,
+            delivery,
+        });
+    }
+
+    pub fn delivery(
+        &self,
+        address: &PointerAddress,
+        observer_id: u32,
+    ) -> Option<Rc<ObserverDelivery>> {
+        self.0
+            .borrow()
+            .iter()
+            .find(|observer| observer.is(address, observer_id))
+            .map(|observer| observer.delivery.clone())
+    }
+
+    /// Removes an observer, returns false if it was not registered
+    pub fn remove(&self, address: &PointerAddress, observer_id: u32) -> bool {
+        let mut observers = self.0.borrow_mut();
+        let Some(index) = observers
+            .iter()
+            .position(|observer| observer.is(address, observer_id))
+        else {
+            return false;
+        };
+        observers.remove(index).delivery.cancel();
+        true
+    }
+
+    /// Removes all observers of a pointer
+    pub fn remove_pointer(&self, address: &PointerAddress) -> Vec<JsObserver> {
+        let (removed, observers): (Vec<_>, Vec<_>) = self
+            .0
+            .take()
+            .into_iter()
+            .partition(|observer| &observer.address == address);
+        self.0.replace(observers);
+        for observer in &removed {
+            observer.delivery.cancel();
+        }
+        removed
+    }
+
+    /// Removes all observers
+    pub fn take_all(&self) -> Vec<JsObserver> {
+        let observers = self.0.take();
+        for observer in &observers {
+            observer.delivery.cancel();
+        }
+        observers
+    }
+
+    /// Returns the number of observers of a pointer
+    pub fn count(&self, address: &PointerAddress) -> usize {
+        self.0
+            .borrow()
+            .iter()
+            .filter(|observer| &observer.address == address)
+            .count()
+    }
+}
+
+impl JsObserver {
+    fn is(&self, address: &PointerAddress, observer_id: u32) -> bool {
+        &self.address == address && self.observer_id == observer_id
+    }
+}
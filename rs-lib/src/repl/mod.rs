@@ -1,14 +1,22 @@
 use datex_core::runtime::execution::context::{
     ExecutionContext, ExecutionMode,
 };
-use wasm_bindgen::{JsError, JsValue, prelude::wasm_bindgen};
+use wasm_bindgen::{JsValue, prelude::wasm_bindgen};
 
-use crate::{js_utils::js_error, runtime::JSRuntime};
+use crate::{
+    js_utils::options_from_js_value,
+    runtime::JSRuntime,
+    utils::{
+        errors::DatexJsError,
+        limits::{ExecutionLimits, run_with_limits},
+    },
+};
 
 #[wasm_bindgen]
 pub struct Repl {
     runtime: JSRuntime,
     execution_context: ExecutionContext,
+    execution_limits: ExecutionLimits,
 }
 
 #[wasm_bindgen]
@@ -30,16 +38,33 @@ impl Repl {
         Self {
             runtime: runtime.clone(),
             execution_context,
+            execution_limits: ExecutionLimits::default(),
         }
     }
 
-    pub async fn execute(&mut self, script: &str) -> Result<JsValue, JsError> {
-        let result = self
-            .runtime
-            .runtime()
-            .execute(script, &[], Some(&mut self.execution_context))
-            .await
-            .map_err(js_error)?;
+    /// Set the execution limits that are applied to every following execution
+    /// (e.g. `{ max_time_ms: 1000 }`, null or undefined removes all limits)
+    pub fn set_execution_limits(
+        &mut self,
+        limits: JsValue,
+    ) -> Result<(), DatexJsError> {
+        self.execution_limits = options_from_js_value(limits)?;
+        Ok(())
+    }
+
+    pub async fn execute(&mut self, script: &str) -> Result<JsValue, JsValue> {
+        let limits = self.execution_limits.clone();
+        let result = run_with_limits(
+            async {
+                self.runtime
+                    .runtime()
+                    .execute(script, &[], Some(&mut self.execution_context))
+                    .await
+                    .map_err(|e| DatexJsError::from(&e).into())
+            },
+            &limits,
+        )
+        .await?;
         Ok(self.runtime.maybe_value_container_to_dif(result))
     }
 }
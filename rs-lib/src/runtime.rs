@@ -1,6 +1,45 @@
 use crate::{
-    js_utils::{js_array, js_error, to_js_value},
+    JSDebugConfig,
+    block::{
+        encryption::{
+            BlockEncryption, BlockEncryptionSlot, decrypt_block, encrypt_block,
+        },
+        parse_block_bytes,
+        processor::BlockProcessor,
+        signing::{
+            BlockSignatures, BlockSignaturesSlot, VerificationPolicy,
+            check_key_length, sign_block, verify_block,
+        },
+    },
+    compiler::{JSCompileOptions, compile_to_js_result},
+    crypto::JSCrypto,
+    execution_context::{ExecutionContextRegistry, JSExecutionContextOptions},
+    js_utils::{js_object, options_from_js_value, parse_endpoint, to_js_value},
+    keystore::{
+        EndpointIdentity, IdentityPersistence, KeyPair, Keystore, KeystoreSlot,
+        WrappingKey, unwrap_identity, wrap_identity,
+    },
     network::com_hub::JSComHub,
+    observer::{
+        JSObserveOptions, NotificationBatch, ObserverDelivery,
+        ObserverRegistry, ObserverUpdate,
+    },
+    storage::{
+        PointerStorage, PointerStorageSlot,
+        encoding::{SharedValueState, encode_pointer, load_pointer},
+        js_storage::JsPointerStorage,
+        memory_storage::InMemoryPointerStorage,
+        snapshot::{create_snapshot, restore_snapshot},
+        write_through::WriteThrough,
+    },
+    utils::{
+        abort::run_abortable,
+        callbacks::JsCallbackRegistry,
+        errors::{DatexJsError, JsErrorKind, ToDatexJsError, conversion_error},
+        fatal_error::FatalErrorHandler,
+        limits::{ExecutionLimits, run_with_limits},
+        logger::{RuntimeLogContext, parse_level_filter},
+    },
 };
 use datex_core::{
     self,
@@ -15,56 +54,116 @@ use datex_core::{
         update::{DIFUpdate, DIFUpdateData},
         value::DIFValueContainer,
     },
-    global::{
-        dxb_block::DXBBlock,
-        protocol_structures::block_header::{BlockHeader, FlagsAndTimestamp},
-    },
+    network::com_hub::ResponseError,
     serde::deserializer::DatexDeserializer,
     shared_values::observers::{ObserveOptions, TransceiverId},
     values::{
         core_values::endpoint::Endpoint, value_container::ValueContainer,
     },
 };
-use datex_crypto_facade::crypto::Crypto;
 use std::borrow::Cow;
 
-use crate::js_utils::cast_from_dif_js_value;
+use crate::runtime_config::parse_runtime_config;
 use datex_core::{
-    crypto::CryptoImpl,
     runtime::{
-        Runtime, RuntimeConfig, RuntimeInternal, RuntimeRunner, memory::Memory,
+        Runtime, RuntimeInternal, RuntimeRunner,
+        execution::{
+            ExecutionError, ExecutionInput, ExecutionOptions,
+            ScriptExecutionError, context::ExecutionContext, execute_dxb,
+            execute_dxb_sync,
+        },
     },
     shared_values::{
         pointer_address::PointerAddress,
         shared_container::SharedContainerMutability,
     },
 };
-use js_sys::Function;
+use futures::{FutureExt, channel::oneshot, select};
+use gloo_timers::future::TimeoutFuture;
+use js_sys::{Function, Reflect, Uint8Array};
+use log::error;
+use serde::{Deserialize, Serialize};
 use serde_wasm_bindgen::from_value;
-use std::{cell::RefCell, fmt::Display, rc::Rc};
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
 use wasm_bindgen::prelude::*;
 use wasm_bindgen_futures::{future_to_promise, spawn_local};
-use web_sys::js_sys::Promise;
+use web_sys::AbortSignal;
 
 #[wasm_bindgen(getter_with_clone)]
 #[derive(Clone)]
 pub struct JSRuntime {
     runtime: Runtime,
     pub com_hub: JSComHub,
+    execution_contexts: ExecutionContextRegistry,
+    log_context: RuntimeLogContext,
+    fatal_error_handler: Rc<FatalErrorHandler>,
+    callbacks: JsCallbackRegistry,
+    observers: ObserverRegistry,
+    pointers: PointerRegistry,
+    temporary_pointers: TemporaryPointers,
+    pointer_storage: PointerStorageSlot,
+    write_through: WriteThrough,
+    notifications: NotificationBatch,
+    signatures: BlockSignaturesSlot,
+    encryption: BlockEncryptionSlot,
+    keystore: KeystoreSlot,
+    shutdown_sender: Rc<RefCell<Option<oneshot::Sender<()>>>>,
+    closed: ClosedFlag,
 }
 
-#[derive(Debug, PartialEq)]
-enum ConversionError {
-    InvalidValue,
+/// Set when the runtime was closed, shared with the DIF handles and the
+/// ComHub of the runtime so that they can not be used after close
+pub(crate) type ClosedFlag = Rc<Cell<bool>>;
+
+/// Fails with a `RuntimeClosed` error if the runtime was closed
+pub(crate) fn ensure_open(closed: &ClosedFlag) -> Result<(), DatexJsError> {
+    if closed.get() {
+        return Err(DatexJsError::new(
+            JsErrorKind::Runtime,
+            "RuntimeClosed",
+            "The runtime was closed and can not be used anymore",
+        ));
+    }
+    Ok(())
 }
-impl Display for ConversionError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            ConversionError::InvalidValue => write!(f, "Invalid value"),
+
+/// Pointers that were created from JavaScript and are owned by the runtime
+type PointerRegistry = Rc<RefCell<Vec<OwnedPointer>>>;
+
+/// A pointer owned by the runtime with the number of JS references to it
+struct OwnedPointer {
+    address: PointerAddress,
+    ref_count: u32,
+}
+
+impl OwnedPointer {
+    fn new(address: PointerAddress) -> Self {
+        OwnedPointer {
+            address,
+            ref_count: 1,
         }
     }
 }
 
+/// Temporary local copies of pointers that are created to validate the
+/// updates of a batch. They are never owned by the runtime or written
+/// through to the pointer storage, and are excluded from `list_pointers`
+/// and snapshots until they are removed at the end of the validation
+type TemporaryPointers = Rc<RefCell<Vec<PointerAddress>>>;
+
+#[derive(Debug, Default, Deserialize)]
+struct JSRemoteExecutionOptions {
+    // timeout in milliseconds for the response of the remote endpoint
+    timeout: Option<u32>,
+}
+
+impl JSRemoteExecutionOptions {
+    const DEFAULT_TIMEOUT_MS: u32 = 30_000;
+}
+
 /**
  * Internal impl of the JSRuntime, not exposed to JavaScript
  */
@@ -73,27 +172,64 @@ impl JSRuntime {
         &self.runtime
     }
 
-    pub async fn run(config: JsValue) -> JSRuntime {
-        let config: RuntimeConfig =
-            cast_from_dif_js_value(config, &RefCell::new(Memory::default()))
-                .unwrap();
+    pub async fn run(
+        config: JsValue,
+        debug_config: Option<JSDebugConfig>,
+    ) -> Result<JSRuntime, DatexJsError> {
+        let config = parse_runtime_config(config)?;
         let runtime_runner = RuntimeRunner::new(config);
+        let endpoint = runtime_runner.runtime.endpoint().to_string();
+        let log_context = RuntimeLogContext::new(endpoint.clone());
+        let fatal_error_callback = match &debug_config {
+            Some(debug_config) => {
+                debug_config.apply_logging(&log_context)?;
+                debug_config.fatal_error_callback()?
+            }
+            None => None,
+        };
+        let fatal_error_handler =
+            FatalErrorHandler::new(endpoint, fatal_error_callback);
         // Note: JSRuntime::new must be called before runtime run to initialize com interface factories
-        let js_runtime = JSRuntime::new(runtime_runner.runtime.clone());
+        let js_runtime = JSRuntime::new(
+            runtime_runner.runtime.clone(),
+            log_context.clone(),
+            fatal_error_handler,
+        );
 
-        let (initialized_sender, initialized_receiver) =
-            futures::channel::oneshot::channel();
+        let (initialized_sender, initialized_receiver) = oneshot::channel();
+        let (shutdown_sender, shutdown_receiver) = oneshot::channel::<()>();
+        js_runtime.shutdown_sender.replace(Some(shutdown_sender));
 
-        spawn_local(async {
-            runtime_runner
+        // all logs of the runtime loop are attributed to this runtime.
+        // Tasks that are spawned by datex-core inside the loop are polled
+        // independently and use the global log configuration, the interfaces
+        // created by this crate propagate the log context to their readers
+        // and send callbacks (see `logger::in_current_scope`).
+        // The interfaces of the runtime config get the block processor of
+        // the runtime the same way
+        let block_processor = js_runtime.com_hub.block_processor().clone();
+        spawn_local(log_context.scope(block_processor.scope(async {
+            let run = runtime_runner
                 .run_forever(async |_| {
                     // Runtime is initialized and ready to use, we can now resolve the promise and return the JSRuntime instance to JavaScript
                     let _ = initialized_sender.send(());
                 })
-                .await;
-        });
-        initialized_receiver.await.unwrap();
-        js_runtime
+                .fuse();
+            futures::pin_mut!(run);
+            // the runtime loop is dropped when the runtime is closed
+            select! {
+                _ = run => {},
+                _ = shutdown_receiver.fuse() => {},
+            }
+        })));
+        initialized_receiver.await.map_err(|_| {
+            DatexJsError::new(
+                JsErrorKind::Runtime,
+                "RuntimeInitFailed",
+                "The runtime stopped before it was initialized",
+            )
+        })?;
+        Ok(js_runtime)
     }
 
     pub fn maybe_value_container_to_dif(
@@ -110,158 +246,251 @@ impl JSRuntime {
         }
     }
 
-    fn new(runtime: Runtime) -> JSRuntime {
-        let com_hub = JSComHub::new(runtime.clone());
-        JSRuntime { runtime, com_hub }
+    fn new(
+        runtime: Runtime,
+        log_context: RuntimeLogContext,
+        fatal_error_handler: Rc<FatalErrorHandler>,
+    ) -> JSRuntime {
+        let callbacks = JsCallbackRegistry::default();
+        let signatures = BlockSignaturesSlot::default();
+        let keystore = KeystoreSlot::default();
+        let encryption = BlockEncryptionSlot::default();
+        let block_processor = BlockProcessor::new(
+            runtime.endpoint().to_string(),
+            keystore.clone(),
+            signatures.clone(),
+            encryption.clone(),
+        );
+        let closed = ClosedFlag::default();
+        let com_hub = JSComHub::new(
+            runtime.clone(),
+            callbacks.clone(),
+            log_context.clone(),
+            block_processor,
+            closed.clone(),
+        );
+        let pointer_storage = PointerStorageSlot::default();
+        let write_through = WriteThrough::new(
+            &runtime.internal,
+            pointer_storage.clone(),
+            log_context.clone(),
+        );
+        JSRuntime {
+            runtime,
+            log_context,
+            fatal_error_handler,
+            com_hub,
+            execution_contexts: ExecutionContextRegistry::default(),
+            callbacks,
+            observers: ObserverRegistry::default(),
+            pointers: PointerRegistry::default(),
+            temporary_pointers: TemporaryPointers::default(),
+            pointer_storage,
+            write_through,
+            notifications: NotificationBatch::default(),
+            signatures,
+            encryption,
+            keystore,
+            shutdown_sender: Rc::new(RefCell::new(None)),
+            closed,
+        }
     }
-}
 
-/**
- * Exposed properties and methods for JavaScript
- */
-#[wasm_bindgen]
-impl JSRuntime {
-    pub async fn crypto_test_tmp(&self) -> Promise {
-        future_to_promise(async move {
-            let something = b"yellow submarineyellow submarine".to_owned();
-            let some_check =
-                "9At2nzU19GjL8F4WFRyB7RZSGLemMGUMVBZAMChfndF2".to_owned();
-
-            let based = CryptoImpl::enc_b58(&something).as_bytes().to_vec();
-            let unbased = CryptoImpl::dec_b58_32(&some_check).unwrap();
-            assert_eq!(something, unbased);
-            assert_eq!(some_check.as_bytes().to_vec(), based);
-
-            // Hashes
-            let mut ikm = Vec::from([0u8; 32]);
-            let hash = CryptoImpl::hash_sha256(&ikm).await.unwrap();
-            assert_eq!(
-                hash,
-                [
-                    102, 104, 122, 173, 248, 98, 189, 119, 108, 143, 193, 139,
-                    142, 159, 142, 32, 8, 151, 20, 133, 110, 226, 51, 179, 144,
-                    42, 89, 29, 13, 95, 41, 37
-                ]
-            );
-            let salt = Vec::from([0u8; 16]);
-            let hash_a = CryptoImpl::hkdf_sha256(&ikm, &salt).await.unwrap();
-            ikm[0] = 1u8;
-            let hash_b = CryptoImpl::hkdf_sha256(&ikm, &salt).await.unwrap();
-            assert_ne!(hash_a, hash_b);
-            assert_ne!(hash_a.to_vec(), ikm);
-            assert_eq!(
-                hash_a,
-                [
-                    223, 114, 4, 84, 111, 27, 238, 120, 184, 83, 36, 167, 137,
-                    140, 161, 25, 179, 135, 224, 19, 134, 209, 174, 240, 55,
-                    120, 29, 74, 138, 3, 106, 238
-                ]
-            );
+    fn ensure_open(&self) -> Result<(), DatexJsError> {
+        ensure_open(&self.closed)
+    }
 
-            // Checks gen_ed25519, sig_ed25519, ver_ed25519 against itself
-            let data = b"Some message to  sign".to_vec();
-            let other_data = b"Some message to sign".to_vec();
+    /// Replaces the pointer storage, the owned pointers are written
+    /// through to the new storage
+    fn replace_pointer_storage(
+        &self,
+        storage: Option<Rc<dyn PointerStorage>>,
+    ) -> Result<(), DatexJsError> {
+        self.write_through.detach_all();
+        self.pointer_storage.replace(storage);
+        let addresses = self
+            .pointers
+            .borrow()
+            .iter()
+            .map(|pointer| pointer.address.clone())
+            .collect::<Vec<_>>();
+        for address in addresses {
+            self.write_through.attach(&address)?;
+        }
+        Ok(())
+    }
 
-            // Generate key and signature
-            let (pub_key, pri_key) = CryptoImpl::gen_ed25519().await.unwrap();
-            assert_eq!(pub_key.len(), 44_usize);
-            assert_eq!(pri_key.len(), 48_usize);
+    fn identity(&self) -> Result<EndpointIdentity, DatexJsError> {
+        self.keystore
+            .borrow()
+            .identity(&self.endpoint())
+            .ok_or_else(|| {
+                DatexJsError::new(
+                    JsErrorKind::Crypto,
+                    "MissingIdentity",
+                    "No identity was generated or imported for this runtime",
+                )
+            })
+    }
 
-            let sig = CryptoImpl::sig_ed25519(&pri_key, &data).await.unwrap();
-            assert_eq!(sig.len(), 64_usize);
+    /// Replaces the keys of the runtime, the new encryption key is
+    /// announced with the next encrypted block
+    fn set_identity(&self, identity: EndpointIdentity) {
+        self.keystore.borrow_mut().set_identity(identity);
+        self.encryption.borrow_mut().announced.clear();
+    }
 
-            // Verify key, signature and data
-            let ver = CryptoImpl::ver_ed25519(&pub_key, &sig, &data)
-                .await
-                .unwrap();
-            assert!(ver);
+    /// Saves the identity to the identity storage, if a storage is set
+    /// and both keypairs exist
+    async fn persist_identity(&self) -> Result<(), DatexJsError> {
+        let (persistence, identity) = {
+            let keystore = self.keystore.borrow();
+            (
+                keystore.persistence.clone(),
+                keystore.identity(&self.endpoint()),
+            )
+        };
+        match (persistence, identity) {
+            (Some(persistence), Some(identity)) => {
+                persistence.save(&identity).await
+            }
+            _ => Ok(()),
+        }
+    }
 
-            // Falsify other data
-            let ver = CryptoImpl::ver_ed25519(&pub_key, &sig, &other_data)
-                .await
-                .unwrap();
-            assert!(!ver);
+    /// Execute a script in the named execution context,
+    /// or in a new temporary context if no name is given
+    async fn execute_in_context(
+        &self,
+        script: &str,
+        values: &[ValueContainer],
+        context: Option<&str>,
+    ) -> Result<Option<ValueContainer>, JsValue> {
+        self.log_context
+            .scope(async {
+                match context {
+                    Some(name) => {
+                        let mut context =
+                            self.execution_contexts.acquire(name)?;
+                        let result = self
+                            .runtime
+                            .execute(script, values, Some(&mut *context))
+                            .await;
+                        context.record_result(&result);
+                        result
+                    }
+                    None => self.runtime.execute(script, values, None).await,
+                }
+                .map_err(|e| JsValue::from(DatexJsError::from(&e)))
+            })
+            .await
+    }
 
-            // Falsify other key
-            let (other_pub_key, other_pri_key) =
-                CryptoImpl::gen_ed25519().await.unwrap();
-            let ver = CryptoImpl::ver_ed25519(&other_pub_key, &sig, &data)
-                .await
-                .unwrap();
-            assert!(!ver);
+    /// Synchronous variant of `execute_in_context`
+    fn execute_sync_in_context(
+        &self,
+        script: &str,
+        values: &[ValueContainer],
+        context: Option<&str>,
+    ) -> Result<Option<ValueContainer>, JsValue> {
+        let _log_context = self.log_context.enter();
+        match context {
+            Some(name) => {
+                let mut context = self.execution_contexts.acquire(name)?;
+                let result = self.runtime.execute_sync(
+                    script,
+                    values,
+                    Some(&mut *context),
+                );
+                context.record_result(&result);
+                result
+            }
+            None => self.runtime.execute_sync(script, values, None),
+        }
+        .map_err(|e| JsValue::from(DatexJsError::from(&e)))
+    }
 
-            // Falsify other signature
-            let other_sig = CryptoImpl::sig_ed25519(&other_pri_key, &data)
-                .await
-                .unwrap();
-            let ver = CryptoImpl::ver_ed25519(&pub_key, &other_sig, &data)
-                .await
-                .unwrap();
-            assert!(!ver);
-
-            // ECDH derivation
-            let (ser_pub, ser_pri) = CryptoImpl::gen_x25519().await.unwrap();
-            let (cli_pub, cli_pri) = CryptoImpl::gen_x25519().await.unwrap();
-            assert_eq!(ser_pub.len(), 44_usize);
-            assert_eq!(ser_pri.len(), 48_usize);
-
-            let cli_sec =
-                CryptoImpl::derive_x25519(&cli_pri, &ser_pub).await.unwrap();
-            let ser_sec =
-                CryptoImpl::derive_x25519(&ser_pri, &cli_pub).await.unwrap();
-
-            assert_eq!(cli_sec, ser_sec);
-            assert_eq!(cli_sec.len(), 32_usize);
-
-            // AES CTR with random key
-            let random_bytes: [u8; 32] =
-                CryptoImpl::random_bytes(32).try_into().unwrap();
-
-            let msg: Vec<u8> = b"Some message".to_vec();
-            let ctr_iv: [u8; 16] = [0u8; 16];
-
-            let ctr_ciphered =
-                CryptoImpl::aes_ctr_encrypt(&random_bytes, &ctr_iv, &msg)
-                    .await
-                    .unwrap();
-
-            let ctr_deciphered = CryptoImpl::aes_ctr_decrypt(
-                &random_bytes,
-                &ctr_iv,
-                &ctr_ciphered,
-            )
+    /// Execute a DXB body in the named execution context, or in the
+    /// runtime's memory if no name is given, with the given values bound
+    /// to the placeholders of the body
+    async fn execute_dxb_in_context(
+        &self,
+        dxb_body: &[u8],
+        values: Vec<ValueContainer>,
+        context: Option<&str>,
+    ) -> Result<Option<ValueContainer>, JsValue> {
+        let options = Self::dxb_execution_options(values);
+        self.log_context
+            .scope(async {
+                match context {
+                    Some(name) => {
+                        let mut context =
+                            self.execution_contexts.acquire(name)?;
+                        let result =
+                            context.execute_dxb(dxb_body, options, true).await;
+                        context.record_result(&result);
+                        result
+                    }
+                    None => {
+                        execute_dxb(ExecutionInput::new(
+                            dxb_body,
+                            options,
+                            Some(self.runtime.internal.clone()),
+                        ))
+                        .await
+                    }
+                }
+                .js_err(JsErrorKind::Execution, "ExecutionFailed")
+                .map_err(JsValue::from)
+            })
             .await
-            .unwrap();
+    }
 
-            assert_eq!(msg, ctr_deciphered);
-            assert_ne!(msg, ctr_ciphered);
-
-            // AES key wrapping
-            let wrapped =
-                CryptoImpl::key_wrap_rfc3394(&random_bytes, &random_bytes)
-                    .await
-                    .unwrap();
-            let unwrapped =
-                CryptoImpl::key_unwrap_rfc3394(&random_bytes, &wrapped)
-                    .await
-                    .unwrap();
-            assert_eq!(random_bytes.to_vec(), unwrapped);
-            // assert_ne!(wrapped, unwrapped);
-
-            let js_array = js_array(&[
-                hash.to_vec(),
-                hash_a.to_vec(),
-                hash_b.to_vec(),
-                ser_pub.to_vec(),
-                pub_key.to_vec(),
-                cli_sec.to_vec(),
-                ser_sec.to_vec(),
-                random_bytes.to_vec(),
-                wrapped.to_vec(),
-                unwrapped.to_vec(),
-            ]);
-            Ok(js_array)
-        })
+    /// Synchronous variant of `execute_dxb_in_context`
+    fn execute_dxb_sync_in_context(
+        &self,
+        dxb_body: &[u8],
+        values: Vec<ValueContainer>,
+        context: Option<&str>,
+    ) -> Result<Option<ValueContainer>, JsValue> {
+        let options = Self::dxb_execution_options(values);
+        let _log_context = self.log_context.enter();
+        match context {
+            Some(name) => {
+                let mut context = self.execution_contexts.acquire(name)?;
+                let result = context.execute_dxb_sync(dxb_body, options, true);
+                context.record_result(&result);
+                result
+            }
+            None => execute_dxb_sync(ExecutionInput::new(
+                dxb_body,
+                options,
+                Some(self.runtime.internal.clone()),
+            )),
+        }
+        .js_err(JsErrorKind::Execution, "ExecutionFailed")
+        .map_err(JsValue::from)
+    }
+
+    /// Execution options with the given values bound to the placeholders
+    /// of a DXB body
+    fn dxb_execution_options(values: Vec<ValueContainer>) -> ExecutionOptions {
+        ExecutionOptions {
+            inserted_values: values,
+            ..ExecutionOptions::default()
+        }
+    }
+}
+
+/**
+ * Exposed properties and methods for JavaScript
+ */
+#[wasm_bindgen]
+impl JSRuntime {
+    /// The crypto primitives used by the runtime
+    #[wasm_bindgen(getter)]
+    pub fn crypto(&self) -> JSCrypto {
+        JSCrypto
     }
 
     #[wasm_bindgen(getter)]
@@ -274,115 +503,275 @@ impl JSRuntime {
         self.runtime.endpoint().to_string()
     }
 
-    pub fn _create_block(
-        &self,
-        body: Option<Vec<u8>>,
-        receivers: Vec<String>,
-    ) -> Vec<u8> {
-        let mut block = DXBBlock {
-            block_header: BlockHeader {
-                flags_and_timestamp: FlagsAndTimestamp::default()
-                    .with_is_end_of_context(true)
-                    .with_is_end_of_section(true),
-                ..BlockHeader::default()
-            },
-            body: body.unwrap_or_default(),
-            ..Default::default()
-        };
+    /// Change the log level of this runtime, either the default level
+    /// or the level of a single target module (e.g. "datex_core::network")
+    pub fn set_log_level(
+        &self,
+        level: &str,
+        target: Option<String>,
+    ) -> Result<(), DatexJsError> {
+        self.ensure_open()?;
+        let level = parse_level_filter(level)?;
+        self.log_context.set_log_level(target.as_deref(), level);
+        Ok(())
+    }
 
-        block.recalculate_struct();
-        block.set_receivers(
-            receivers
-                .into_iter()
-                .map(|r| Endpoint::try_from(r))
-                .collect::<Result<Vec<Endpoint>, _>>()
-                .unwrap(),
-        );
-        block.to_bytes()
+    /// Set a callback that receives all log records of this runtime,
+    /// or null to use the global log sink or the console
+    pub fn set_log_sink(
+        &self,
+        sink: Option<Function>,
+    ) -> Result<(), DatexJsError> {
+        self.ensure_open()?;
+        self.log_context.set_log_sink(sink);
+        Ok(())
+    }
+
+    /// Set a callback that is called with { message, location, endpoint }
+    /// if a panic occurs, or null to remove the callback
+    pub fn set_on_fatal_error(
+        &self,
+        callback: Option<Function>,
+    ) -> Result<(), DatexJsError> {
+        self.ensure_open()?;
+        self.fatal_error_handler.set_callback(callback);
+        Ok(())
+    }
+
+    /// Returns true if this runtime is poisoned by a panic. A poisoned
+    /// runtime can not be used anymore.
+    /// Note: a panic aborts the whole wasm instance, so it poisons all
+    /// runtimes of the instance (including runtimes created afterwards),
+    /// not only the runtime that caused it. The wasm module must be
+    /// reloaded to create a working runtime.
+    pub fn is_poisoned(&self) -> bool {
+        self.fatal_error_handler.is_poisoned()
     }
 
+    /// Compile a script to a DXB body without executing it.
+    /// If `{ inserted_values }` are given, the script is compiled as a
+    /// template and the DIF values are inserted for the `?` placeholders.
+    /// Returns an object containing the compiled body and compiler diagnostics.
+    pub fn compile(
+        &self,
+        script: &str,
+        options: JsValue,
+    ) -> Result<JsValue, DatexJsError> {
+        self.ensure_open()?;
+        let options: JSCompileOptions = options_from_js_value(options)?;
+        let inserted_values = options
+            .inserted_values
+            .map(|values| {
+                values
+                    .into_iter()
+                    .map(|value| self.dif_value_to_value_container(value))
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .transpose()?;
+        compile_to_js_result(script, inserted_values.as_deref())
+    }
+
+    /// Execute a script and return the decompiled result as a string.
+    /// The execution can be cancelled with an optional AbortSignal, in which
+    /// case the promise is rejected with the abort reason of the signal.
+    /// Optional execution limits (e.g. `{ max_time_ms: 1000 }`) reject the
+    /// promise with an error with code `TimeLimitExceeded` if they are exceeded.
     pub async fn execute_with_string_result(
         &self,
         script: &str,
         dif_values: Option<Vec<JsValue>>,
         decompile_options: JsValue,
-    ) -> Result<String, JsError> {
-        let val = &self
-            .js_values_to_value_containers(dif_values)
-            .map_err(js_error)?;
-        let result = self
-            .runtime
-            .execute(script, val, None)
-            .await
-            .map_err(js_error)?;
+        abort_signal: Option<AbortSignal>,
+        limits: JsValue,
+        context: Option<String>,
+    ) -> Result<String, JsValue> {
+        self.ensure_open()?;
+        let limits: ExecutionLimits = options_from_js_value(limits)?;
+        let val = &self.js_values_to_value_containers(dif_values)?;
+        let result = run_abortable(
+            run_with_limits(
+                self.execute_in_context(script, val, context.as_deref()),
+                &limits,
+            ),
+            abort_signal,
+        )
+        .await?;
         match result {
             None => Ok("".to_string()),
             Some(result) => Ok(decompile_value(
                 &result,
-                from_value(decompile_options).unwrap_or_default(),
+                options_from_js_value(decompile_options)?,
             )),
         }
     }
 
+    /// Execute a script and return the result as a DIF value.
+    /// The execution can be cancelled with an optional AbortSignal, in which
+    /// case the promise is rejected with the abort reason of the signal.
+    /// Optional execution limits (e.g. `{ max_time_ms: 1000 }`) reject the
+    /// promise with an error with code `TimeLimitExceeded` if they are exceeded.
+    /// If the name of an execution context created with `create_context` is
+    /// given, the script is executed in this context and its state is kept.
     pub async fn execute(
         &self,
         script: &str,
         dif_values: Option<Vec<JsValue>>,
+        abort_signal: Option<AbortSignal>,
+        limits: JsValue,
+        context: Option<String>,
     ) -> Result<JsValue, JsValue> {
-        let result = self
-            .runtime
-            .execute(
-                script,
-                &self
-                    .js_values_to_value_containers(dif_values)
-                    .map_err(js_error)?,
-                None,
-            )
-            .await
-            .map_err(js_error)?;
+        self.ensure_open()?;
+        let limits: ExecutionLimits = options_from_js_value(limits)?;
+        let values = self.js_values_to_value_containers(dif_values)?;
+        let result = run_abortable(
+            run_with_limits(
+                self.execute_in_context(script, &values, context.as_deref()),
+                &limits,
+            ),
+            abort_signal,
+        )
+        .await?;
         Ok(self.maybe_value_container_to_dif(result))
     }
 
+    /// Synchronous variant of `execute_with_string_result`.
+    /// Synchronous executions can not be interrupted, so no execution
+    /// limits are supported.
     pub fn execute_sync_with_string_result(
         &self,
         script: &str,
         dif_values: Option<Vec<JsValue>>,
         decompile_options: JsValue,
+        context: Option<String>,
     ) -> Result<String, JsValue> {
-        let input = self
-            .runtime
-            .execute_sync(
-                script,
-                &self
-                    .js_values_to_value_containers(dif_values)
-                    .map_err(js_error)?,
-                None,
-            )
-            .map_err(js_error)?;
+        self.ensure_open()?;
+        let values = self.js_values_to_value_containers(dif_values)?;
+        let input =
+            self.execute_sync_in_context(script, &values, context.as_deref())?;
         match input {
             None => Ok("".to_string()),
             Some(result) => Ok(decompile_value(
                 &result,
-                from_value(decompile_options).unwrap_or_default(),
+                options_from_js_value(decompile_options)?,
             )),
         }
     }
 
+    /// Synchronous variant of `execute`.
+    /// Synchronous executions can not be interrupted, so no execution
+    /// limits are supported.
     pub fn execute_sync(
         &self,
         script: &str,
         dif_values: Option<Vec<JsValue>>,
+        context: Option<String>,
     ) -> Result<JsValue, JsValue> {
-        let result = self
-            .runtime
-            .execute_sync(
-                script,
-                &self
-                    .js_values_to_value_containers(dif_values)
-                    .map_err(js_error)?,
-                None,
-            )
-            .map_err(js_error)?;
+        self.ensure_open()?;
+        let values = self.js_values_to_value_containers(dif_values)?;
+        let result =
+            self.execute_sync_in_context(script, &values, context.as_deref())?;
+        Ok(self.maybe_value_container_to_dif(result))
+    }
+
+    /// Execute a script on a remote endpoint and return the result as a DIF value.
+    /// The script is compiled locally, sent to the endpoint via the ComHub
+    /// and the promise resolves when the response block is received.
+    /// Rejects with an error of kind `network` and code `RemoteTimeout` if no
+    /// response is received within `options.timeout` milliseconds
+    /// (default: 30 seconds), with code `EndpointUnreachable` if the block
+    /// could not be routed to the endpoint, or with code
+    /// `RemoteExecutionFailed` if the remote execution failed.
+    pub async fn execute_remote(
+        &self,
+        endpoint: String,
+        script: &str,
+        dif_values: Option<Vec<JsValue>>,
+        options: JsValue,
+    ) -> Result<JsValue, JsValue> {
+        self.ensure_open()?;
+        let options: JSRemoteExecutionOptions = options_from_js_value(options)?;
+        let endpoint = parse_endpoint(&endpoint)?;
+        let values = self.js_values_to_value_containers(dif_values)?;
+        let timeout_ms = options
+            .timeout
+            .unwrap_or(JSRemoteExecutionOptions::DEFAULT_TIMEOUT_MS);
+
+        let mut context = ExecutionContext::remote(endpoint.clone());
+        let execution = self
+            .log_context
+            .scope(self.runtime.execute(script, &values, Some(&mut context)))
+            .fuse();
+        let timeout = TimeoutFuture::new(timeout_ms).fuse();
+        futures::pin_mut!(execution, timeout);
+
+        let result = select! {
+            result = execution => result
+                .map_err(|e| remote_execution_error(&endpoint, &e))?,
+            _ = timeout => {
+                return Err(DatexJsError::new(
+                    JsErrorKind::Network,
+                    "RemoteTimeout",
+                    format!(
+                        "No response from {endpoint} within {timeout_ms}ms"
+                    ),
+                )
+                .into());
+            }
+        };
+        Ok(self.maybe_value_container_to_dif(result))
+    }
+
+    /// Execute a precompiled DXB body (e.g. returned by `compile`) and
+    /// return the result as a DIF value.
+    /// The optional DIF values are bound to the execution as inserted values
+    /// for the placeholders of the body, so a body can be compiled once and
+    /// executed with different values.
+    /// The execution can be cancelled with an optional AbortSignal and
+    /// limited with optional execution limits (see `execute`).
+    /// If the name of an execution context created with `create_context` is
+    /// given, the body is executed in this context and its state is kept.
+    pub async fn execute_dxb(
+        &self,
+        dxb_body: Vec<u8>,
+        dif_values: Option<Vec<JsValue>>,
+        abort_signal: Option<AbortSignal>,
+        limits: JsValue,
+        context: Option<String>,
+    ) -> Result<JsValue, JsValue> {
+        self.ensure_open()?;
+        let limits: ExecutionLimits = options_from_js_value(limits)?;
+        let values = self.js_values_to_value_containers(dif_values)?;
+        let result = run_abortable(
+            run_with_limits(
+                self.execute_dxb_in_context(
+                    &dxb_body,
+                    values,
+                    context.as_deref(),
+                ),
+                &limits,
+            ),
+            abort_signal,
+        )
+        .await?;
+        Ok(self.maybe_value_container_to_dif(result))
+    }
+
+    /// Synchronous variant of `execute_dxb`.
+    /// Fails if the DXB body contains instructions that require async
+    /// execution (e.g. remote calls). No execution limits are supported.
+    pub fn execute_dxb_sync(
+        &self,
+        dxb_body: &[u8],
+        dif_values: Option<Vec<JsValue>>,
+        context: Option<String>,
+    ) -> Result<JsValue, JsValue> {
+        self.ensure_open()?;
+        let values = self.js_values_to_value_containers(dif_values)?;
+        let result = self.execute_dxb_sync_in_context(
+            dxb_body,
+            values,
+            context.as_deref(),
+        )?;
         Ok(self.maybe_value_container_to_dif(result))
     }
 
@@ -390,20 +779,19 @@ impl JSRuntime {
         &self,
         dif_value: JsValue,
         decompile_options: JsValue,
-    ) -> Result<String, JsError> {
-        let value_container = self
-            .js_value_to_value_container(dif_value)
-            .map_err(js_error)?;
+    ) -> Result<String, DatexJsError> {
+        self.ensure_open()?;
+        let value_container = self.js_value_to_value_container(dif_value)?;
         Ok(decompile_value(
             &value_container,
-            from_value(decompile_options).unwrap_or_default(),
+            options_from_js_value(decompile_options)?,
         ))
     }
 
     fn js_values_to_value_containers(
         &self,
         js_values: Option<Vec<JsValue>>,
-    ) -> Result<Vec<ValueContainer>, ConversionError> {
+    ) -> Result<Vec<ValueContainer>, DatexJsError> {
         js_values
             .unwrap_or_default()
             .into_iter()
@@ -412,29 +800,575 @@ impl JSRuntime {
     }
 
     /// Convert a JsValue (DIFValue) to a ValueContainer
-    /// Returns Err(()) if the conversion fails (invalid json or ref not found)
+    /// Fails if the value is not a valid DIF value or references a pointer
+    /// that is not in memory
     fn js_value_to_value_container(
         &self,
         js_value: JsValue,
-    ) -> Result<ValueContainer, ConversionError> {
+    ) -> Result<ValueContainer, DatexJsError> {
         // convert JsValue to DIFValue
-        let dif_value: DIFValueContainer = from_value(js_value).unwrap();
-        // convert DIFValue to ValueContainer
-        if let Ok(value_container) =
-            dif_value.to_value_container(self.runtime.memory())
-        {
-            Ok(value_container)
-        } else {
-            // ref not found
-            Err(ConversionError::InvalidValue)
-        }
+        let dif_value: DIFValueContainer =
+            from_value(js_value).map_err(conversion_error)?;
+        self.dif_value_to_value_container(dif_value)
+    }
+
+    /// Convert a DIFValue to a ValueContainer
+    /// Fails if the value references a pointer that is not in memory
+    fn dif_value_to_value_container(
+        &self,
+        dif_value: DIFValueContainer,
+    ) -> Result<ValueContainer, DatexJsError> {
+        dif_value
+            .to_value_container(self.runtime.memory())
+            .map_err(|_| {
+                DatexJsError::new(
+                    JsErrorKind::Conversion,
+                    "ReferenceNotFound",
+                    "The DIF value references a pointer that is not in memory",
+                )
+            })
+    }
+
+    /// Create a named execution context that keeps its state (e.g. declared
+    /// variables) between executions. Returns the name of the context, which
+    /// can be passed to `execute`, `execute_sync`, `execute_dxb` and
+    /// `execute_dxb_sync`.
+    pub fn create_context(
+        &self,
+        options: JsValue,
+    ) -> Result<String, DatexJsError> {
+        self.ensure_open()?;
+        let options: JSExecutionContextOptions =
+            options_from_js_value(options)?;
+        self.execution_contexts.create(options, &self.runtime)
+    }
+
+    /// Get the names of all execution contexts
+    pub fn list_contexts(&self) -> Result<Vec<String>, DatexJsError> {
+        self.ensure_open()?;
+        Ok(self.execution_contexts.names())
+    }
+
+    /// Get information about an execution context
+    pub fn inspect_context(&self, name: &str) -> Result<JsValue, DatexJsError> {
+        self.ensure_open()?;
+        let info = self.execution_contexts.info(name)?;
+        to_js_value(&info).map_err(conversion_error)
+    }
+
+    /// Reset an execution context, dropping its state
+    pub fn reset_context(&self, name: &str) -> Result<(), DatexJsError> {
+        self.ensure_open()?;
+        self.execution_contexts.reset(name, &self.runtime)
+    }
+
+    /// Remove an execution context
+    pub fn dispose_context(&self, name: &str) -> Result<(), DatexJsError> {
+        self.ensure_open()?;
+        self.execution_contexts.dispose(name)
     }
 
     /// Get a handle to the DIF interface of the runtime
     pub fn dif(&self) -> RuntimeDIFHandle {
         RuntimeDIFHandle {
             internal: self.runtime.internal.clone(),
+            endpoint: self.endpoint(),
+            callbacks: self.callbacks.clone(),
+            observers: self.observers.clone(),
+            pointers: self.pointers.clone(),
+            temporary_pointers: self.temporary_pointers.clone(),
+            pointer_storage: self.pointer_storage.clone(),
+            write_through: self.write_through.clone(),
+            notifications: self.notifications.clone(),
+            log_context: self.log_context.clone(),
+            closed: self.closed.clone(),
+        }
+    }
+
+    /// Set a JS pointer storage object with the methods `get(address)`,
+    /// `put(address, data)`, `delete(address)` and `list()` (which may return
+    /// promises), or null to remove the storage.
+    /// Pointers that are not in memory are loaded from the storage into
+    /// memory before they are requested from the network. Pointers created
+    /// via DIF and pointers loaded from the storage are written through to
+    /// the storage on every update (including updates by scripts and remote
+    /// endpoints) and deleted from the storage when they are removed from
+    /// memory, see `release_pointer`.
+    /// When the storage is set, all pointers owned by the runtime are stored.
+    pub fn set_pointer_storage(
+        &self,
+        storage: JsValue,
+    ) -> Result<(), DatexJsError> {
+        self.ensure_open()?;
+        let storage: Option<Rc<dyn PointerStorage>> =
+            if storage.is_null() || storage.is_undefined() {
+                None
+            } else {
+                Some(Rc::new(JsPointerStorage::new(storage)?))
+            };
+        self.replace_pointer_storage(storage)
+    }
+
+    /// Use a pointer storage that keeps all stored pointers in memory
+    pub fn use_in_memory_pointer_storage(&self) -> Result<(), DatexJsError> {
+        self.ensure_open()?;
+        self.replace_pointer_storage(Some(Rc::new(
+            InMemoryPointerStorage::default(),
+        )))
+    }
+
+    /// Get the addresses of all pointers in the pointer storage
+    pub async fn list_stored_pointers(
+        &self,
+    ) -> Result<Vec<String>, DatexJsError> {
+        self.ensure_open()?;
+        let Some(storage) = self.pointer_storage.borrow().clone() else {
+            return Ok(vec![]);
+        };
+        Ok(storage
+            .list()
+            .await?
+            .iter()
+            .map(PointerAddress::to_address_string)
+            .collect())
+    }
+
+    /// Serialize all local pointers in memory (with their values, allowed
+    /// types and mutability) into a versioned binary snapshot
+    pub fn snapshot(&self) -> Result<Vec<u8>, DatexJsError> {
+        self.ensure_open()?;
+        let temporary_pointers = self.temporary_pointers.borrow();
+        let addresses = self
+            .runtime
+            .internal
+            .pointer_addresses()
+            .into_iter()
+            .filter(|address| {
+                matches!(address, PointerAddress::Local(_))
+                    && !temporary_pointers.contains(address)
+            })
+            .collect::<Vec<_>>();
+        create_snapshot(&self.runtime.internal, &addresses)
+    }
+
+    /// Restore the pointers of a snapshot created with `snapshot` under
+    /// their original addresses and return the addresses.
+    /// Fails without restoring any pointer with an `AddressInUse` error if
+    /// one of the addresses is already in memory.
+    /// The restored pointers are owned by the runtime with one reference
+    /// (released with `release_pointer`) and are written through to the
+    /// pointer storage.
+    pub fn restore(
+        &self,
+        snapshot: &[u8],
+    ) -> Result<Vec<String>, DatexJsError> {
+        self.ensure_open()?;
+        let addresses = restore_snapshot(&self.runtime.internal, snapshot)?;
+        let mut restored = Vec::with_capacity(addresses.len());
+        for address in addresses {
+            if let Err(e) = self.write_through.attach(&address) {
+                error!(
+                    "Failed to store pointer {}: {e}",
+                    address.to_address_string()
+                );
+            }
+            restored.push(address.to_address_string());
+            self.pointers.borrow_mut().push(OwnedPointer::new(address));
+        }
+        Ok(restored)
+    }
+
+    /// Generate a new identity (Ed25519 signing and X25519 encryption
+    /// keypair) for the endpoint of this runtime, replacing the current keys.
+    /// Returns the public keys, see `public_keys`.
+    pub async fn generate_identity(&self) -> Result<JsValue, DatexJsError> {
+        self.ensure_open()?;
+        let identity = EndpointIdentity::generate(self.endpoint()).await?;
+        self.set_identity(identity);
+        self.persist_identity().await?;
+        self.public_keys()
+    }
+
+    /// Replace the signing keypair ("signing"), the encryption keypair
+    /// ("encryption") or both (null) with newly generated keys.
+    /// Returns the new public keys, see `public_keys`.
+    pub async fn rotate_keys(
+        &self,
+        kind: Option<String>,
+    ) -> Result<JsValue, DatexJsError> {
+        self.ensure_open()?;
+        let (signing, encryption) = match kind.as_deref() {
+            None => (true, true),
+            Some("signing") => (true, false),
+            Some("encryption") => (false, true),
+            Some(kind) => {
+                return Err(DatexJsError::new(
+                    JsErrorKind::Conversion,
+                    "InvalidKeyKind",
+                    format!(
+                        "Invalid key kind '{kind}', expected 'signing' or 'encryption'"
+                    ),
+                ));
+            }
+        };
+        let signing_key = match signing {
+            true => Some(KeyPair::generate_signing().await?),
+            false => None,
+        };
+        let encryption_key = match encryption {
+            true => Some(KeyPair::generate_encryption().await?),
+            false => None,
+        };
+        {
+            let mut keystore = self.keystore.borrow_mut();
+            if signing_key.is_some() {
+                keystore.signing_key = signing_key;
+            }
+            if encryption_key.is_some() {
+                keystore.encryption_key = encryption_key;
+                self.encryption.borrow_mut().announced.clear();
+            }
+        }
+        self.persist_identity().await?;
+        self.public_keys()
+    }
+
+    /// Get the public keys of the endpoint of this runtime as
+    /// `{ signing_key, encryption_key }` (SPKI, null if not set)
+    pub fn public_keys(&self) -> Result<JsValue, DatexJsError> {
+        self.ensure_open()?;
+        let keystore = self.keystore.borrow();
+        let public_key = |key: &Option<KeyPair>| {
+            key.as_ref()
+                .map(|key| Uint8Array::from(key.public_key.as_slice()).into())
+                .unwrap_or(JsValue::NULL)
+        };
+        Ok(js_object(vec![
+            ("signing_key", public_key(&keystore.signing_key)),
+            ("encryption_key", public_key(&keystore.encryption_key)),
+        ])
+        .into())
+    }
+
+    /// Export the identity of this runtime, encrypted and authenticated with
+    /// a key derived from the given password.
+    /// The key is derived with PBKDF2-HMAC-SHA256 (600000 iterations), the
+    /// salt and the iteration count are stored in the export.
+    /// Fails with `EmptyPassword` if the password is empty.
+    pub async fn export_identity(
+        &self,
+        password: &str,
+    ) -> Result<Vec<u8>, DatexJsError> {
+        self.ensure_open()?;
+        let identity = self.identity()?;
+        let wrapping_key = WrappingKey::derive(password).await?;
+        wrap_identity(&identity, &wrapping_key).await
+    }
+
+    /// Import an identity that was exported with `export_identity`,
+    /// the identity must belong to the endpoint of this runtime
+    pub async fn import_identity(
+        &self,
+        identity: Vec<u8>,
+        password: &str,
+    ) -> Result<(), DatexJsError> {
+        self.ensure_open()?;
+        let identity = unwrap_identity(&identity, password).await?;
+        if identity.endpoint != self.endpoint() {
+            return Err(DatexJsError::new(
+                JsErrorKind::Crypto,
+                "EndpointMismatch",
+                format!(
+                    "The identity belongs to {}, not to {}",
+                    identity.endpoint,
+                    self.endpoint()
+                ),
+            ));
+        }
+        self.set_identity(identity);
+        self.persist_identity().await
+    }
+
+    /// Set a JS object with the methods `load(endpoint)` and
+    /// `save(endpoint, data)` (which may return promises) that persists the
+    /// identity of this runtime encrypted with the given password, or null to
+    /// remove the storage. The password is used like in `export_identity`
+    /// and must have high entropy.
+    /// If the storage contains an identity for the endpoint, it is loaded and
+    /// true is returned. Otherwise, the current identity (if any) is saved.
+    pub async fn set_identity_storage(
+        &self,
+        storage: JsValue,
+        password: Option<String>,
+    ) -> Result<bool, DatexJsError> {
+        self.ensure_open()?;
+        if storage.is_null() || storage.is_undefined() {
+            self.keystore.borrow_mut().persistence = None;
+            return Ok(false);
+        }
+        let password = password.ok_or_else(|| {
+            DatexJsError::new(
+                JsErrorKind::Crypto,
+                "MissingPassword",
+                "A password is required to store the identity",
+            )
+        })?;
+        let persistence =
+            Rc::new(IdentityPersistence::new(storage, &password).await?);
+        let stored = persistence.load(&self.endpoint(), &password).await?;
+        let loaded = stored.is_some();
+        self.keystore.borrow_mut().persistence = Some(persistence);
+        if let Some(identity) = stored {
+            self.set_identity(identity);
+        }
+        if !loaded {
+            self.persist_identity().await?;
+        }
+        Ok(loaded)
+    }
+
+    /// Add the Ed25519 public key (SPKI) of an endpoint,
+    /// used to verify blocks and encryption key announcements that were
+    /// sent by the endpoint
+    pub fn add_trusted_key(
+        &self,
+        endpoint: &str,
+        public_key: Vec<u8>,
+    ) -> Result<(), DatexJsError> {
+        self.ensure_open()?;
+        let endpoint = parse_endpoint(endpoint)?;
+        check_key_length(&public_key, false)?;
+        self.signatures
+            .borrow_mut()
+            .trusted_keys
+            .insert(endpoint.to_string(), public_key);
+        Ok(())
+    }
+
+    pub fn remove_trusted_key(
+        &self,
+        endpoint: &str,
+    ) -> Result<(), DatexJsError> {
+        self.ensure_open()?;
+        let endpoint = parse_endpoint(endpoint)?;
+        self.signatures
+            .borrow_mut()
+            .trusted_keys
+            .remove(&endpoint.to_string());
+        Ok(())
+    }
+
+    /// Set the signature verification policy for blocks that are received
+    /// by the interfaces of this runtime: "off" (default), "verify" (report
+    /// invalid signatures) or "require" (drop all blocks without a valid
+    /// signature of a trusted key, including data of stream based interfaces
+    /// that is not a complete block).
+    /// With both "verify" and "require", signed blocks whose timestamp is
+    /// outside of the accepted window ("expired") and blocks that were
+    /// already received ("replayed") are dropped.
+    /// Blocks that violate the policy are logged and passed to the optional
+    /// callback as `{ sender, status, socket, dropped }`.
+    /// Blocks that are sent by this runtime are signed automatically if a
+    /// signing key is set.
+    pub fn set_verification_policy(
+        &self,
+        policy: &str,
+        on_verification_failure: Option<Function>,
+    ) -> Result<(), DatexJsError> {
+        self.ensure_open()?;
+        let policy = VerificationPolicy::parse(policy)?;
+        let mut signatures = self.signatures.borrow_mut();
+        signatures.policy = policy;
+        signatures.on_verification_failure = on_verification_failure;
+        Ok(())
+    }
+
+    /// Sign a serialized block (e.g. created with a `BlockBuilder`)
+    /// with the signing key of this runtime
+    pub async fn sign_block(
+        &self,
+        block: Vec<u8>,
+    ) -> Result<Vec<u8>, DatexJsError> {
+        self.ensure_open()?;
+        let key = self.keystore.borrow().require_signing_key()?;
+        sign_block(&block, &key).await
+    }
+
+    /// Verify the signature of a serialized block against the trusted key
+    /// of its sender. Returns "unsigned", "valid", "invalid" or "unknown_key".
+    pub async fn verify_block(
+        &self,
+        block: Vec<u8>,
+    ) -> Result<String, DatexJsError> {
+        self.ensure_open()?;
+        let block = parse_block_bytes(&block)?;
+        let public_key = self
+            .signatures
+            .borrow()
+            .trusted_keys
+            .get(&block.routing_header.sender.to_string())
+            .cloned();
+        Ok(verify_block(&block, public_key.as_deref())
+            .await?
+            .as_str()
+            .to_string())
+    }
+
+    /// Add the X25519 public key (SPKI) of an endpoint, used to derive the
+    /// key for end-to-end encrypted blocks exchanged with the endpoint.
+    /// Keys are also exchanged automatically on first contact with endpoints
+    /// that have a trusted key (see `add_trusted_key`), a manually added key
+    /// replaces the announced key
+    pub fn add_peer_encryption_key(
+        &self,
+        endpoint: &str,
+        public_key: Vec<u8>,
+    ) -> Result<(), DatexJsError> {
+        self.ensure_open()?;
+        let endpoint = parse_endpoint(endpoint)?;
+        self.encryption
+            .borrow_mut()
+            .set_peer_key(endpoint.to_string(), public_key);
+        Ok(())
+    }
+
+    /// Remove the public key of an endpoint, a new key is exchanged
+    /// on the next contact
+    pub fn remove_peer_encryption_key(
+        &self,
+        endpoint: &str,
+    ) -> Result<(), DatexJsError> {
+        self.ensure_open()?;
+        let endpoint = parse_endpoint(endpoint)?;
+        self.encryption
+            .borrow_mut()
+            .remove_peer_key(&endpoint.to_string());
+        Ok(())
+    }
+
+    /// Get the public keys of all endpoints that blocks can be encrypted
+    /// for, as an object mapping endpoint names to keys
+    pub fn peer_encryption_keys(&self) -> Result<JsValue, DatexJsError> {
+        self.ensure_open()?;
+        let encryption = self.encryption.borrow();
+        Ok(js_object(
+            encryption
+                .peer_keys()
+                .map(|(endpoint, key)| {
+                    (endpoint.as_str(), Uint8Array::from(key.as_slice()))
+                })
+                .collect(),
+        )
+        .into())
+    }
+
+    /// Encrypt all requests and responses of this runtime that are sent to
+    /// a single endpoint. The keys are exchanged on first contact with
+    /// endpoints that have a trusted key, until the key of an endpoint is
+    /// known, blocks are sent to it unencrypted.
+    /// Requires a signing and an encryption key, see `generate_identity`
+    pub fn set_encryption_enabled(
+        &self,
+        enabled: bool,
+    ) -> Result<(), DatexJsError> {
+        self.ensure_open()?;
+        self.encryption.borrow_mut().enabled = enabled;
+        Ok(())
+    }
+
+    /// Require end-to-end encryption for all requests and responses
+    /// exchanged with an endpoint. Blocks sent to the endpoint are encrypted,
+    /// if its key is not known yet, the keys are exchanged before the block
+    /// is sent. The key of an endpoint without a trusted key must be added
+    /// with `add_peer_encryption_key`. Unencrypted blocks sent to or received from the endpoint
+    /// are dropped, logged and passed to the `on_unencrypted_block` callback.
+    pub fn require_encryption(
+        &self,
+        endpoint: &str,
+        required: bool,
+    ) -> Result<(), DatexJsError> {
+        self.ensure_open()?;
+        let endpoint = parse_endpoint(endpoint)?.to_string();
+        let mut encryption = self.encryption.borrow_mut();
+        if required {
+            encryption.required.insert(endpoint);
+        } else {
+            encryption.required.remove(&endpoint);
+        }
+        Ok(())
+    }
+
+    /// Set a callback that is called with `{ endpoint, direction, socket }`
+    /// for dropped unencrypted blocks exchanged with an endpoint that
+    /// requires encryption, or null to only log these blocks
+    pub fn set_on_unencrypted_block(
+        &self,
+        callback: Option<Function>,
+    ) -> Result<(), DatexJsError> {
+        self.ensure_open()?;
+        self.encryption.borrow_mut().on_unencrypted_block = callback;
+        Ok(())
+    }
+
+    /// Encrypt the body of a serialized block for its receiver, using a key
+    /// derived from the encryption key of this runtime and the public
+    /// encryption key of the receiver. The encrypted body is authenticated
+    /// with an HMAC over the header, IV and ciphertext
+    pub async fn encrypt_block(
+        &self,
+        block: Vec<u8>,
+    ) -> Result<Vec<u8>, DatexJsError> {
+        self.ensure_open()?;
+        let key = self.keystore.borrow().require_encryption_key()?;
+        encrypt_block(&block, &self.endpoint(), &key, &self.encryption).await
+    }
+
+    /// Decrypt the body of a block that was encrypted by its sender
+    /// with `encrypt_block`. Fails with an `InvalidMac` error if the
+    /// block was modified
+    pub async fn decrypt_block(
+        &self,
+        block: Vec<u8>,
+    ) -> Result<Vec<u8>, DatexJsError> {
+        self.ensure_open()?;
+        let key = self.keystore.borrow().require_encryption_key()?;
+        decrypt_block(&block, &self.endpoint(), &key, &self.encryption).await
+    }
+
+    /// Close the runtime: removes all pointer observers, interfaces and
+    /// sockets, releases all registered JS callbacks (interceptors, observers,
+    /// interface factories) and stops the runtime loop.
+    /// The teardown is always completed, if an interface could not be
+    /// removed, the error is returned afterwards.
+    /// The runtime (and its DIF handles) can not be used anymore after it
+    /// was closed, calls fail with a `RuntimeClosed` error. Closing an
+    /// already closed runtime has no effect.
+    pub async fn close(&self) -> Result<(), DatexJsError> {
+        if self.closed.replace(true) {
+            return Ok(());
         }
+        for observer in self.observers.take_all() {
+            let _ = self
+                .runtime
+                .internal
+                .unobserve_pointer(observer.address, observer.observer_id);
+        }
+        // interfaces are removed before the callbacks are released, since
+        // the close handlers of JS interfaces are registered callbacks
+        let interfaces_removed = self.com_hub.remove_all_interfaces().await;
+        self.callbacks.release_all();
+        self.write_through.detach_all();
+        self.pointer_storage.replace(None);
+        self.signatures.replace(BlockSignatures::default());
+        self.encryption.replace(BlockEncryption::default());
+        self.keystore.replace(Keystore::default());
+        self.log_context.set_log_sink(None);
+        self.fatal_error_handler.set_callback(None);
+        self.execution_contexts.clear();
+        if let Some(shutdown_sender) = self.shutdown_sender.take() {
+            let _ = shutdown_sender.send(());
+        }
+        interfaces_removed
     }
 
     /// Start the LSP server, returning a JS function to send messages to Rust
@@ -448,68 +1382,356 @@ impl JSRuntime {
 #[wasm_bindgen]
 pub struct RuntimeDIFHandle {
     internal: Rc<RuntimeInternal>,
+    endpoint: String,
+    callbacks: JsCallbackRegistry,
+    observers: ObserverRegistry,
+    pointers: PointerRegistry,
+    temporary_pointers: TemporaryPointers,
+    pointer_storage: PointerStorageSlot,
+    write_through: WriteThrough,
+    notifications: NotificationBatch,
+    log_context: RuntimeLogContext,
+    closed: ClosedFlag,
+}
+
+#[derive(Deserialize)]
+struct JSBatchUpdate {
+    address: String,
+    update: DIFUpdateData,
+}
+
+/// Filter for `list_pointers`, all conditions must match
+#[derive(Debug, Default, Deserialize)]
+struct JSPointerFilter {
+    // only pointers that are (or are not) owned by the runtime
+    #[serde(default)]
+    owned: Option<bool>,
+    // only pointers that have (or have no) JS observers
+    #[serde(default)]
+    observed: Option<bool>,
+    #[serde(default)]
+    address_prefix: Option<String>,
+    // only pointers with an approximate size of at least this many bytes
+    #[serde(default)]
+    min_size: Option<usize>,
+    // compute the approximate size of every pointer
+    #[serde(default)]
+    with_size: bool,
+}
+
+#[derive(Serialize, Default)]
+struct PointerDescription {
+    address: String,
+    // the endpoint of the pointer address, none for internal pointers
+    owner: Option<String>,
+    #[serde(with = "serde_wasm_bindgen::preserve")]
+    allowed_type: JsValue,
+    // name of the shared container mutability
+    mutability: Option<String>,
+    // number of JS references if the pointer is owned by the runtime
+    ref_count: Option<u32>,
+    observer_count: usize,
+    // endpoints that subscribed to the updates of the pointer
+    subscribers: Vec<String>,
+    // length of the serialized pointer in bytes, only computed if requested
+    size: Option<usize>,
+    // the error if the pointer could not be described
+    error: Option<String>,
 }
 
 #[wasm_bindgen]
 impl RuntimeDIFHandle {
+    /// Applies the updates of a batch, the previous value of every updated
+    /// pointer is stored before its first update
+    fn apply_updates(
+        &mut self,
+        transceiver_id: TransceiverId,
+        updates: &[(PointerAddress, DIFUpdateData)],
+        previous_values: &mut Vec<(PointerAddress, DIFValueContainer)>,
+    ) -> Result<(), DatexJsError> {
+        for (address, update) in updates {
+            if !previous_values.iter().any(|(a, _)| a == address) {
+                let value = self.current_value(address)?;
+                previous_values.push((address.clone(), value));
+            }
+            DIFInterface::update(self, transceiver_id, address.clone(), update)
+                .js_err(JsErrorKind::DIF, "UpdateFailed")?;
+        }
+        Ok(())
+    }
+
+    /// Applies the updates of a batch to temporary copies of the updated
+    /// pointers, so that a failing update is detected before any observer
+    /// or remote subscriber is notified. The copies are local pointers
+    /// (also for remote pointers), see `TemporaryPointers`
+    fn validate_updates(
+        &self,
+        transceiver_id: TransceiverId,
+        updates: &[(PointerAddress, DIFUpdateData)],
+    ) -> Result<(), DatexJsError> {
+        let mut copies: Vec<(PointerAddress, PointerAddress)> = vec![];
+        let result = self.apply_to_copies(transceiver_id, updates, &mut copies);
+        for (_, copy) in copies {
+            if let Err(e) = self.internal.remove_pointer(copy.clone()) {
+                error!(
+                    "Failed to remove temporary pointer {}: {e:?}",
+                    copy.to_address_string()
+                );
+            }
+            self.temporary_pointers
+                .borrow_mut()
+                .retain(|address| *address != copy);
+        }
+        result
+    }
+
+    /// Applies the updates of a batch to copies of the updated pointers,
+    /// the copy of every updated pointer is created before its first update
+    fn apply_to_copies(
+        &self,
+        transceiver_id: TransceiverId,
+        updates: &[(PointerAddress, DIFUpdateData)],
+        copies: &mut Vec<(PointerAddress, PointerAddress)>,
+    ) -> Result<(), DatexJsError> {
+        for (address, update) in updates {
+            let copy = match copies.iter().find(|(a, _)| a == address) {
+                Some((_, copy)) => copy.clone(),
+                None => {
+                    let (value, allowed_type, mutability) =
+                        SharedValueState::of_pointer(&self.internal, address)?
+                            .into_parts()?;
+                    let copy = DIFInterface::create_pointer(
+                        self,
+                        value,
+                        allowed_type,
+                        mutability,
+                    )
+                    .js_err(JsErrorKind::DIF, "CreatePointerFailed")?;
+                    self.temporary_pointers.borrow_mut().push(copy.clone());
+                    copies.push((address.clone(), copy.clone()));
+                    copy
+                }
+            };
+            DIFInterface::update(self, transceiver_id, copy, update)
+                .js_err(JsErrorKind::DIF, "UpdateFailed")?;
+        }
+        Ok(())
+    }
+
+    /// Restores the previous values of a failed batch. Returns an error
+    /// with the addresses of the pointers that could not be restored.
+    fn revert_updates(
+        &self,
+        transceiver_id: TransceiverId,
+        previous_values: Vec<(PointerAddress, DIFValueContainer)>,
+    ) -> Result<(), DatexJsError> {
+        let mut failed = vec![];
+        for (address, value) in previous_values.into_iter().rev() {
+            if let Err(e) = DIFInterface::update(
+                self,
+                transceiver_id,
+                address.clone(),
+                &DIFUpdateData::Replace { value },
+            ) {
+                error!(
+                    "Failed to revert pointer {}: {e:?}",
+                    address.to_address_string()
+                );
+                failed.push(address.to_address_string());
+            }
+        }
+        if failed.is_empty() {
+            Ok(())
+        } else {
+            Err(DatexJsError::new(
+                JsErrorKind::DIF,
+                "BatchRevertFailed",
+                format!(
+                    "Failed to revert the batch updates of {}",
+                    failed.join(", ")
+                ),
+            ))
+        }
+    }
+
+    /// Adds the allowed type, mutability and (if requested) size of a
+    /// pointer in memory to its description
+    fn describe_pointer(
+        &self,
+        address: &PointerAddress,
+        with_size: bool,
+        description: &mut PointerDescription,
+    ) -> Result<(), DatexJsError> {
+        let state = SharedValueState::of_pointer(&self.internal, address)?;
+        let mutability = SharedContainerMutability::try_from(state.mutability)
+            .map_err(|_| {
+                DatexJsError::new(
+                    JsErrorKind::Conversion,
+                    "InvalidMutability",
+                    format!("Invalid mutability: {}", state.mutability),
+                )
+            })?;
+        description.mutability = Some(format!("{mutability:?}"));
+        description.allowed_type = state.allowed_type;
+        if with_size {
+            description.size =
+                Some(encode_pointer(&self.internal, address)?.len());
+        }
+        Ok(())
+    }
+
+    /// Returns the endpoint that owns a pointer, the own endpoint for local
+    /// pointers and none for internal pointers
+    fn pointer_owner(&self, address: &PointerAddress) -> Option<String> {
+        match address {
+            PointerAddress::Local(_) => Some(self.endpoint.clone()),
+            PointerAddress::Remote(_) => {
+                address.endpoint().map(|endpoint| endpoint.to_string())
+            }
+            PointerAddress::Internal(_) => None,
+        }
+    }
+
+    /// Returns true if the pointer is still used outside of JS, i.e. it is
+    /// referenced by a DATEX value in memory (e.g. a variable of an
+    /// execution context) or subscribed by a remote endpoint
+    fn has_other_owners(&self, address: &PointerAddress) -> bool {
+        self.internal.is_pointer_referenced(address)
+            || !self.internal.pointer_subscribers(address).is_empty()
+    }
+
+    /// Returns the current value of a pointer in memory
+    fn current_value(
+        &self,
+        address: &PointerAddress,
+    ) -> Result<DIFValueContainer, DatexJsError> {
+        pointer_value(&self.internal, address)
+    }
+
+    fn ensure_open(&self) -> Result<(), DatexJsError> {
+        ensure_open(&self.closed)
+    }
+
     fn js_value_to_pointer_address(
         address: &str,
-    ) -> Result<PointerAddress, JsError> {
-        PointerAddress::try_from(address)
-            .map_err(|_| js_error(ConversionError::InvalidValue))
+    ) -> Result<PointerAddress, DatexJsError> {
+        PointerAddress::try_from(address).map_err(|_| {
+            DatexJsError::new(
+                JsErrorKind::Conversion,
+                "InvalidPointerAddress",
+                format!("Invalid pointer address: {address}"),
+            )
+        })
     }
 
+    /// Observe the updates of a pointer. In addition to the DATEX observe
+    /// options, the delivery of updates to the callback can be configured:
+    /// `coalesce` ("microtask" or "animation_frame"), `throttle_ms` or
+    /// `debounce_ms` collect updates and deliver them together as an array,
+    /// with `latest_only`, a single `Replace` update with the current value
+    /// of the pointer is delivered instead of the collected updates.
+    /// If `setTimeout` is not available, throttled and debounced updates
+    /// are delivered immediately.
     pub fn observe_pointer(
         &self,
         transceiver_id: TransceiverId,
         address: &str,
         observe_options: JsValue,
         callback: &Function,
-    ) -> Result<u32, JsError> {
+    ) -> Result<u32, DatexJsError> {
+        self.ensure_open()?;
         let address = RuntimeDIFHandle::js_value_to_pointer_address(address)?;
-        let cb = callback.clone();
-        let observe_options: ObserveOptions =
-            from_value(observe_options).map_err(js_error)?;
+        let (observe_options, delivery_options) =
+            JSObserveOptions::parse(observe_options)?;
+        // the delivery is owned by the observer in memory, so the runtime
+        // is only referenced weakly
+        let internal = Rc::downgrade(&self.internal);
+        let pointer_address = address.clone();
+        let current_value = move |source_id: TransceiverId| {
+            let value =
+                pointer_value(&internal.upgrade()?, &pointer_address).ok()?;
+            to_js_value(&DIFUpdate {
+                source_id,
+                data: Cow::Owned(DIFUpdateData::Replace { value }),
+            })
+            .ok()
+        };
+        let delivery = ObserverDelivery::new(
+            self.callbacks.register(callback.clone()),
+            delivery_options,
+            current_value,
+        );
+        let observer_delivery = delivery.clone();
+        let notifications = self.notifications.clone();
         let observer = move |update_data: &DIFUpdateData,
                              source_id: TransceiverId| {
-            let js_value = to_js_value(&DIFUpdate {
+            let update = to_js_value(&DIFUpdate {
                 source_id,
                 data: Cow::Borrowed(update_data),
             })
             .unwrap();
-            let _ = cb.call1(&JsValue::NULL, &js_value);
+            notifications.notify(
+                &observer_delivery,
+                ObserverUpdate { source_id, update },
+            );
         };
-        self.internal
-            .observe_pointer(transceiver_id, address, observe_options, observer)
-            .map_err(js_error)
+        let observer_id = self
+            .internal
+            .observe_pointer(
+                transceiver_id,
+                address.clone(),
+                observe_options,
+                observer,
+            )
+            .js_err(JsErrorKind::DIF, "ObserveFailed")?;
+        self.observers.add(address, observer_id, delivery);
+        Ok(observer_id)
     }
 
     pub fn unobserve_pointer(
         &self,
         address: &str,
         observer_id: u32,
-    ) -> Result<(), JsError> {
+    ) -> Result<(), DatexJsError> {
+        self.ensure_open()?;
         let address = RuntimeDIFHandle::js_value_to_pointer_address(address)?;
+        self.observers.remove(&address, observer_id);
         DIFInterface::unobserve_pointer(self, address, observer_id)
-            .map_err(js_error)
+            .js_err(JsErrorKind::DIF, "UnobserveFailed")
     }
 
+    /// Change the DATEX observe options and the delivery options of an
+    /// observer registered with `observe_pointer`. Pending updates are
+    /// delivered with the previous delivery options.
     pub fn update_observer_options(
         &self,
         address: &str,
         observer_id: u32,
         observe_options: JsValue,
-    ) -> Result<(), JsError> {
+    ) -> Result<(), DatexJsError> {
+        self.ensure_open()?;
         let address = RuntimeDIFHandle::js_value_to_pointer_address(address)?;
-        let observe_options: ObserveOptions =
-            from_value(observe_options).map_err(js_error)?;
+        let (observe_options, delivery_options) =
+            JSObserveOptions::parse(observe_options)?;
+        let delivery =
+            self.observers.delivery(&address, observer_id).ok_or_else(|| {
+                DatexJsError::new(
+                    JsErrorKind::DIF,
+                    "ObserverNotFound",
+                    format!(
+                        "Observer {observer_id} of pointer {} is not registered",
+                        address.to_address_string()
+                    ),
+                )
+            })?;
+        delivery.set_options(delivery_options);
         DIFInterface::update_observer_options(
             self,
             address,
             observer_id,
             observe_options,
         )
-        .map_err(js_error)
+        .js_err(JsErrorKind::DIF, "UpdateObserverOptionsFailed")
     }
 
     pub fn update(
@@ -517,26 +1739,84 @@ impl RuntimeDIFHandle {
         transceiver_id: TransceiverId,
         address: &str,
         update: JsValue,
-    ) -> Result<(), JsError> {
+    ) -> Result<(), DatexJsError> {
+        self.ensure_open()?;
         let address = Self::js_value_to_pointer_address(address)?;
         let dif_update_data: DIFUpdateData =
-            from_value(update).map_err(js_error)?;
-        DIFInterface::update(self, transceiver_id, address, &dif_update_data)
-            .map_err(js_error)
+            from_value(update).map_err(conversion_error)?;
+        DIFInterface::update(
+            self,
+            transceiver_id,
+            address.clone(),
+            &dif_update_data,
+        )
+        .js_err(JsErrorKind::DIF, "UpdateFailed")
+    }
+
+    /// Apply multiple updates `[{ address, update }, ...]` atomically.
+    /// The updates are first applied to temporary copies of the pointers,
+    /// if an update fails, no pointer is changed and the error is returned.
+    /// The copies are removed before the updates are applied and are not
+    /// listed, stored or owned by the runtime.
+    /// Each JS observer is notified once after all updates were applied,
+    /// with an array of the updates of its pointer.
+    /// If an update fails although it was applied to the copy, the
+    /// previous updates of the batch are reverted and the error is
+    /// returned, or a `BatchRevertFailed` error caused by it if not all
+    /// pointers could be restored.
+    pub fn update_batch(
+        &mut self,
+        transceiver_id: TransceiverId,
+        updates: JsValue,
+    ) -> Result<(), DatexJsError> {
+        self.ensure_open()?;
+        let updates: Vec<JSBatchUpdate> =
+            from_value(updates).map_err(conversion_error)?;
+        // all addresses are validated before the first update is applied
+        let updates = updates
+            .into_iter()
+            .map(|update| {
+                Ok((
+                    Self::js_value_to_pointer_address(&update.address)?,
+                    update.update,
+                ))
+            })
+            .collect::<Result<Vec<_>, DatexJsError>>()?;
+        self.validate_updates(transceiver_id, &updates)?;
+
+        let mut previous_values = vec![];
+        self.notifications.start();
+        match self.apply_updates(transceiver_id, &updates, &mut previous_values)
+        {
+            Ok(()) => {
+                self.notifications.finish();
+                Ok(())
+            }
+            Err(error) => {
+                let reverted =
+                    self.revert_updates(transceiver_id, previous_values);
+                self.notifications.discard();
+                match reverted {
+                    Ok(()) => Err(error),
+                    Err(revert_error) => Err(revert_error.with_cause(error)),
+                }
+            }
+        }
     }
 
     pub fn apply(
         &mut self,
         callee: JsValue,
         value: JsValue,
-    ) -> Result<JsValue, JsError> {
+    ) -> Result<JsValue, DatexJsError> {
+        self.ensure_open()?;
         let dif_callee: DIFValueContainer =
-            from_value(callee).map_err(js_error)?;
+            from_value(callee).map_err(conversion_error)?;
         let dif_value: DIFValueContainer =
-            from_value(value).map_err(js_error)?;
+            from_value(value).map_err(conversion_error)?;
         let result = DIFInterface::apply(self, dif_callee, dif_value)
-            .map_err(js_error)?;
-        to_js_value(&result).map_err(js_error)
+            .js_err(JsErrorKind::DIF, "ApplyFailed")?;
+        to_js_value(&result).map_err(conversion_error)
     }
 
     pub fn create_pointer(
@@ -544,37 +1824,216 @@ impl RuntimeDIFHandle {
         value: JsValue,
         allowed_type: JsValue,
         mutability: u8,
-    ) -> Result<String, JsError> {
+    ) -> Result<String, DatexJsError> {
+        self.ensure_open()?;
         let dif_value: DIFValueContainer =
-            from_value(value).map_err(js_error)?;
+            from_value(value).map_err(conversion_error)?;
         let dif_allowed_type: Option<DIFTypeDefinition> =
             if allowed_type.is_null() || allowed_type.is_undefined() {
                 None
             } else {
-                Some(from_value(allowed_type).map_err(js_error)?)
+                Some(from_value(allowed_type).map_err(conversion_error)?)
             };
         let dif_mutability = SharedContainerMutability::try_from(mutability)
-            .map_err(|_| js_error(ConversionError::InvalidValue))?;
+            .map_err(|_| {
+                DatexJsError::new(
+                    JsErrorKind::Conversion,
+                    "InvalidMutability",
+                    format!("Invalid mutability: {mutability}"),
+                )
+            })?;
         let address = DIFInterface::create_pointer(
             self,
             dif_value,
             dif_allowed_type,
             dif_mutability,
         )
-        .map_err(js_error)?;
-        Ok(address.to_address_string())
+        .js_err(JsErrorKind::DIF, "CreatePointerFailed")?;
+        // the pointer is created even if it can not be stored
+        if let Err(e) = self.write_through.attach(&address) {
+            error!(
+                "Failed to store pointer {}: {e}",
+                address.to_address_string()
+            );
+        }
+        let address_string = address.to_address_string();
+        self.pointers.borrow_mut().push(OwnedPointer::new(address));
+        Ok(address_string)
+    }
+
+    /// Whether the runtime of this handle was closed
+    #[wasm_bindgen(getter)]
+    pub fn closed(&self) -> bool {
+        self.closed.get()
+    }
+
+    /// Whether the pointer was created or restored by this runtime
+    /// and was not released yet
+    pub fn owns_pointer(&self, address: &str) -> Result<bool, DatexJsError> {
+        self.ensure_open()?;
+        let address = Self::js_value_to_pointer_address(address)?;
+        Ok(self
+            .pointers
+            .borrow()
+            .iter()
+            .any(|pointer| pointer.address == address))
+    }
+
+    /// List all pointers in memory, optionally filtered with
+    /// `{ owned, observed, address_prefix, min_size }`.
+    /// Each pointer is described with its address, owner endpoint, allowed
+    /// type, mutability, reference count (for pointers owned by the runtime),
+    /// JS observer count and remote subscribers. The approximate size is
+    /// only computed with `with_size: true` or a `min_size` filter, since
+    /// the pointer has to be serialized.
+    /// Pointers that can not be described are included with an `error`.
+    pub fn list_pointers(
+        &self,
+        filter: JsValue,
+    ) -> Result<JsValue, DatexJsError> {
+        self.ensure_open()?;
+        let filter: JSPointerFilter = options_from_js_value(filter)?;
+
+        let mut descriptions = vec![];
+        for address in self.internal.pointer_addresses() {
+            if self.temporary_pointers.borrow().contains(&address) {
+                continue;
+            }
+            let address_string = address.to_address_string();
+            let ref_count = self
+                .pointers
+                .borrow()
+                .iter()
+                .find(|pointer| pointer.address == address)
+                .map(|pointer| pointer.ref_count);
+            let observer_count = self.observers.count(&address);
+            if filter.owned.is_some_and(|o| o != ref_count.is_some())
+                || filter.observed.is_some_and(|o| o != (observer_count > 0))
+                || filter
+                    .address_prefix
+                    .as_ref()
+                    .is_some_and(|prefix| !address_string.starts_with(prefix))
+            {
+                continue;
+            }
+            let mut description = PointerDescription {
+                address: address_string,
+                owner: self.pointer_owner(&address),
+                allowed_type: JsValue::NULL,
+                ref_count,
+                observer_count,
+                subscribers: self
+                    .internal
+                    .pointer_subscribers(&address)
+                    .iter()
+                    .map(Endpoint::to_string)
+                    .collect(),
+                ..Default::default()
+            };
+            if let Err(error) = self.describe_pointer(
+                &address,
+                filter.with_size || filter.min_size.is_some(),
+                &mut description,
+            ) {
+                description.error = Some(error.to_string());
+            } else if let (Some(min_size), Some(size)) =
+                (filter.min_size, description.size)
+                && size < min_size
+            {
+                continue;
+            }
+            descriptions.push(description);
+        }
+        to_js_value(&descriptions).map_err(conversion_error)
+    }
+
+    /// Add a reference to a pointer owned by the runtime.
+    /// A created pointer starts with one reference.
+    /// Returns the new reference count.
+    pub fn retain_pointer(&self, address: &str) -> Result<u32, DatexJsError> {
+        self.ensure_open()?;
+        let address = Self::js_value_to_pointer_address(address)?;
+        let mut pointers = self.pointers.borrow_mut();
+        let pointer = pointers
+            .iter_mut()
+            .find(|pointer| pointer.address == address)
+            .ok_or_else(|| pointer_not_owned(&address))?;
+        pointer.ref_count += 1;
+        Ok(pointer.ref_count)
+    }
+
+    /// Remove a reference to a pointer owned by the runtime and return the
+    /// remaining reference count. The observer that was registered for the
+    /// released reference (if any) is removed, other observers are kept.
+    /// When the last reference is released, the pointer is no longer owned
+    /// by the runtime and its remaining JS observers are removed. It is only
+    /// removed from memory and deleted from the pointer storage if
+    /// datex-core reports no other owners (DATEX values, e.g. variables of
+    /// an execution context, or remote subscribers), otherwise it stays in
+    /// memory (and in the storage) as long as it is used by them.
+    pub fn release_pointer(
+        &self,
+        address: &str,
+        observer_id: Option<u32>,
+    ) -> Result<u32, DatexJsError> {
+        self.ensure_open()?;
+        let address = Self::js_value_to_pointer_address(address)?;
+        let ref_count = {
+            let mut pointers = self.pointers.borrow_mut();
+            let index = pointers
+                .iter()
+                .position(|pointer| pointer.address == address)
+                .ok_or_else(|| pointer_not_owned(&address))?;
+            pointers[index].ref_count -= 1;
+            let ref_count = pointers[index].ref_count;
+            if ref_count == 0 {
+                pointers.remove(index);
+            }
+            ref_count
+        };
+        if let Some(observer_id) = observer_id {
+            self.observers.remove(&address, observer_id);
+            if let Err(e) = self
+                .internal
+                .unobserve_pointer(address.clone(), observer_id)
+            {
+                error!("Failed to remove observer {observer_id}: {e:?}");
+            }
+        }
+        if ref_count > 0 {
+            return Ok(ref_count);
+        }
+
+        for observer in self.observers.remove_pointer(&address) {
+            let observer_id = observer.observer_id;
+            if let Err(e) = self
+                .internal
+                .unobserve_pointer(observer.address, observer_id)
+            {
+                error!("Failed to remove observer {observer_id}: {e:?}");
+            }
+        }
+        if !self.has_other_owners(&address) {
+            self.write_through.detach(&address);
+            self.write_through.delete(&address);
+            self.internal
+                .remove_pointer(address)
+                .js_err(JsErrorKind::DIF, "RemovePointerFailed")?;
+        }
+        Ok(0)
     }
 
     /// Resolve a pointer address synchronously if it's in memory, otherwise return an error
     pub fn resolve_pointer_address_sync(
         &self,
         address: &str,
-    ) -> Result<JsValue, JsError> {
+    ) -> Result<JsValue, DatexJsError> {
+        self.ensure_open()?;
         let address = Self::js_value_to_pointer_address(address)?;
         let result =
             DIFInterface::resolve_pointer_address_in_memory(self, address)
-                .map_err(js_error)?;
-        to_js_value(&result).map_err(js_error)
+                .js_err(JsErrorKind::DIF, "PointerNotFound")?;
+        to_js_value(&result).map_err(conversion_error)
     }
 
     /// Resolve a pointer address, returning a Promise
@@ -583,23 +2042,69 @@ impl RuntimeDIFHandle {
     pub fn resolve_pointer_address(
         &self,
         address: &str,
-    ) -> Result<JsValue, JsError> {
+    ) -> Result<JsValue, DatexJsError> {
+        self.ensure_open()?;
         if let Ok(sync) = self.resolve_pointer_address_sync(address) {
             return Ok(sync);
         }
         let address = Self::js_value_to_pointer_address(address)?;
         let runtime = self.internal.clone();
+        let storage = self.pointer_storage.borrow().clone();
+        let write_through = self.write_through.clone();
         Ok(future_to_promise(async move {
+            if let Some(storage) = storage
+                && let Some(data) =
+                    storage.get(&address).await.map_err(DatexJsError::from)?
+            {
+                // the pointer might have been loaded by a concurrent resolve
+                if runtime
+                    .resolve_pointer_address_in_memory(address.clone())
+                    .is_err()
+                {
+                    load_pointer(&runtime, &address, &data)?;
+                }
+                write_through.attach(&address)?;
+                let result = runtime
+                    .resolve_pointer_address_in_memory(address)
+                    .js_err(JsErrorKind::DIF, "PointerNotFound")?;
+                return Ok(to_js_value(&result).map_err(conversion_error)?);
+            }
             let result = runtime
                 .resolve_pointer_address_external(address)
                 .await
-                .map_err(js_error)?;
-            Ok(to_js_value(&result).map_err(js_error)?)
+                .js_err(JsErrorKind::DIF, "ResolvePointerFailed")?;
+            Ok(to_js_value(&result).map_err(conversion_error)?)
         })
         .unchecked_into())
     }
 }
 
+/// Returns the current value of a pointer in memory
+fn pointer_value(
+    internal: &RuntimeInternal,
+    address: &PointerAddress,
+) -> Result<DIFValueContainer, DatexJsError> {
+    let reference = internal
+        .resolve_pointer_address_in_memory(address.clone())
+        .js_err(JsErrorKind::DIF, "PointerNotFound")?;
+    let reference = to_js_value(&reference).map_err(conversion_error)?;
+    let value = Reflect::get(&reference, &"value".into()).map_err(|e| {
+        DatexJsError::from_debug(JsErrorKind::DIF, "InvalidPointerValue", &e)
+    })?;
+    from_value(value).map_err(conversion_error)
+}
+
+fn pointer_not_owned(address: &PointerAddress) -> DatexJsError {
+    DatexJsError::new(
+        JsErrorKind::DIF,
+        "PointerNotOwned",
+        format!(
+            "Pointer {} is not owned by the runtime",
+            address.to_address_string()
+        ),
+    )
+}
+
 impl DIFInterface for RuntimeDIFHandle {
     fn update(
         &self,
@@ -678,5 +2183,29 @@ impl DIFInterface for RuntimeDIFHandle {
     }
 }
 
-#[cfg(test)]
-mod tests {}
+/// Distinguishes network and routing failures of a remote execution
+/// from errors of the execution on the remote endpoint
+fn remote_execution_error(
+    endpoint: &Endpoint,
+    err: &ScriptExecutionError,
+) -> DatexJsError {
+    let (code, message) = match err {
+        ScriptExecutionError::ExecutionError(
+            ExecutionError::ResponseError(ResponseError::NotReachable(_)),
+        ) => (
+            "EndpointUnreachable",
+            format!("Endpoint {endpoint} is not reachable"),
+        ),
+        ScriptExecutionError::ExecutionError(
+            ExecutionError::ResponseError(
+                ResponseError::NoResponseAfterTimeout(..),
+            ),
+        ) => ("RemoteTimeout", format!("No response from {endpoint}")),
+        _ => (
+            "RemoteExecutionFailed",
+            format!("Remote execution on {endpoint} failed"),
+        ),
+    };
+    DatexJsError::new(JsErrorKind::Network, code, message)
+        .with_cause(DatexJsError::from(err))
+}
@@ -0,0 +1,195 @@
+use std::{cell::RefCell, collections::BTreeMap};
+
+use datex_core::{
+    runtime::{RuntimeConfig, memory::Memory},
+    serde::deserializer::from_value_container,
+    values::value_container::ValueContainer,
+};
+use serde::{
+    Deserialize, Deserializer, Serialize,
+    de::{self, IgnoredAny, Visitor},
+    forward_to_deserialize_any,
+};
+use wasm_bindgen::JsValue;
+
+use crate::{
+    js_utils::js_value_to_dif_value_container,
+    utils::errors::{DatexJsError, JsErrorKind},
+};
+
+/// A single problem found in a runtime config
+#[derive(Debug, Clone, Serialize)]
+pub struct RuntimeConfigIssue {
+    /// name of the affected top level field, None if the issue
+    /// concerns the config as a whole
+    pub field: Option<String>,
+    pub code: String,
+    pub message: String,
+}
+
+/// Result of `validate_runtime_config` that is returned to JavaScript
+#[derive(Debug, Serialize)]
+pub struct RuntimeConfigValidation {
+    pub valid: bool,
+    pub issues: Vec<RuntimeConfigIssue>,
+}
+
+/// Parses a DIF runtime config passed from JavaScript.
+/// All invalid or unknown fields are listed in the error message.
+pub fn parse_runtime_config(
+    config: JsValue,
+) -> Result<RuntimeConfig, DatexJsError> {
+    check_runtime_config(config).map_err(|issues| {
+        let issues = issues
+            .iter()
+            .map(|issue| issue.message.as_str())
+            .collect::<Vec<_>>()
+            .join("; ");
+        DatexJsError::new(
+            JsErrorKind::Conversion,
+            "InvalidRuntimeConfig",
+            format!("Invalid runtime config: {issues}"),
+        )
+    })
+}
+
+/// Validates a DIF runtime config without creating a runtime
+pub fn validate_runtime_config(config: JsValue) -> RuntimeConfigValidation {
+    match check_runtime_config(config) {
+        Ok(_) => RuntimeConfigValidation {
+            valid: true,
+            issues: vec![],
+        },
+        Err(issues) => RuntimeConfigValidation {
+            valid: false,
+            issues,
+        },
+    }
+}
+
+fn check_runtime_config(
+    config: JsValue,
+) -> Result<RuntimeConfig, Vec<RuntimeConfigIssue>> {
+    if config.is_null() || config.is_undefined() {
+        return Err(vec![config_issue(
+            "InvalidConfig",
+            "the config must be an object",
+        )]);
+    }
+
+    let value_container = js_value_to_dif_value_container(config)
+        .and_then(|dif| {
+            dif.to_value_container(&RefCell::new(Memory::default()))
+                .map_err(|e| {
+                    DatexJsError::new(
+                        JsErrorKind::Conversion,
+                        "ReferenceNotFound",
+                        format!("{e:?}"),
+                    )
+                })
+        })
+        .map_err(|err| vec![config_issue(err.code, err.message)])?;
+
+    let mut issues = unknown_field_issues(&value_container)?;
+    match from_value_container::<RuntimeConfig>(&value_container) {
+        Ok(config) if issues.is_empty() => Ok(config),
+        Ok(_) => Err(issues),
+        Err(err) => {
+            let message = err.to_string();
+            // serde error messages mention the affected field in backticks
+            let field = runtime_config_fields()
+                .iter()
+                .find(|field| message.contains(&format!("`{field}`")));
+            issues.push(match field {
+                Some(field) => RuntimeConfigIssue {
+                    field: Some(field.to_string()),
+                    code: "InvalidField".to_string(),
+                    message: format!("invalid field '{field}': {message}"),
+                },
+                None => config_issue("InvalidConfig", message),
+            });
+            Err(issues)
+        }
+    }
+}
+
+/// Returns an issue for each top level field that is not a field of `RuntimeConfig`
+fn unknown_field_issues(
+    value_container: &ValueContainer,
+) -> Result<Vec<RuntimeConfigIssue>, Vec<RuntimeConfigIssue>> {
+    let entries =
+        from_value_container::<BTreeMap<String, IgnoredAny>>(value_container)
+            .map_err(|_| {
+            vec![config_issue(
+                "InvalidConfig",
+                "the config must be an object",
+            )]
+        })?;
+
+    let known_fields = runtime_config_fields();
+    // no field names available (e.g. flattened fields), unknown fields can not be detected
+    if known_fields.is_empty() {
+        return Ok(vec![]);
+    }
+    Ok(entries
+        .keys()
+        .filter(|key| !known_fields.contains(&key.as_str()))
+        .map(|key| RuntimeConfigIssue {
+            field: Some(key.clone()),
+            code: "UnknownField".to_string(),
+            message: format!(
+                "unknown field '{key}', expected one of: {}",
+                known_fields.join(", ")
+            ),
+        })
+        .collect())
+}
+
+fn config_issue(
+    code: impl Into<String>,
+    message: impl Into<String>,
+) -> RuntimeConfigIssue {
+    RuntimeConfigIssue {
+        field: None,
+        code: code.into(),
+        message: message.into(),
+    }
+}
+
+/// Returns the field names of `RuntimeConfig`, taken from its `Deserialize` implementation
+fn runtime_config_fields() -> &'static [&'static str] {
+    let mut fields: &'static [&'static str] = &[];
+    let _ = RuntimeConfig::deserialize(FieldNameCollector(&mut fields));
+    fields
+}
+
+/// A deserializer that only records the field names that are passed
+/// to `deserialize_struct` and fails afterwards
+struct FieldNameCollector<'a>(&'a mut &'static [&'static str]);
+
+impl<'de> Deserializer<'de> for FieldNameCollector<'_> {
+    type Error = de::value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(
+        self,
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        Err(de::Error::custom("expected a struct"))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        *self.0 = fields;
+        Err(de::Error::custom("field names collected"))
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map enum identifier ignored_any
+    }
+}
@@ -0,0 +1,175 @@
+use datex_core::{
+    dif::{
+        interface::DIFInterface, r#type::DIFTypeDefinition,
+        value::DIFValueContainer,
+    },
+    runtime::RuntimeInternal,
+    shared_values::{
+        pointer_address::PointerAddress,
+        shared_container::SharedContainerMutability,
+    },
+};
+use serde::{Deserialize, Serialize};
+use serde_wasm_bindgen::from_value;
+use wasm_bindgen::JsValue;
+use web_sys::js_sys::JSON;
+
+use crate::{
+    js_utils::to_js_value,
+    utils::errors::{
+        DatexJsError, JsErrorKind, ToDatexJsError, conversion_error,
+    },
+};
+
+/// Magic bytes at the start of every stored pointer
+const STORED_POINTER_MAGIC: &[u8; 4] = b"DXMS";
+/// Version of the stored pointer format, incremented on incompatible changes
+const STORED_POINTER_VERSION: u8 = 1;
+
+/// A shared value as returned by `resolve_pointer_address_in_memory`
+#[derive(Serialize, Deserialize)]
+pub struct SharedValueState {
+    #[serde(with = "serde_wasm_bindgen::preserve")]
+    pub value: JsValue,
+    #[serde(with = "serde_wasm_bindgen::preserve")]
+    pub allowed_type: JsValue,
+    #[serde(rename = "mut")]
+    pub mutability: u8,
+}
+
+impl SharedValueState {
+    /// Returns the state of a pointer in memory
+    pub fn of_pointer(
+        internal: &RuntimeInternal,
+        address: &PointerAddress,
+    ) -> Result<SharedValueState, DatexJsError> {
+        let reference = internal
+            .resolve_pointer_address_in_memory(address.clone())
+            .js_err(JsErrorKind::DIF, "PointerNotFound")?;
+        from_value(to_js_value(&reference).map_err(conversion_error)?)
+            .map_err(conversion_error)
+    }
+
+    /// Converts the state to the value, allowed type and mutability
+    /// that a pointer is created with
+    pub fn into_parts(
+        self,
+    ) -> Result<
+        (
+            DIFValueContainer,
+            Option<DIFTypeDefinition>,
+            SharedContainerMutability,
+        ),
+        DatexJsError,
+    > {
+        let value: DIFValueContainer =
+            from_value(self.value).map_err(conversion_error)?;
+        let allowed_type: Option<DIFTypeDefinition> = if self
+            .allowed_type
+            .is_null()
+            || self.allowed_type.is_undefined()
+        {
+            None
+        } else {
+            Some(from_value(self.allowed_type).map_err(conversion_error)?)
+        };
+        let mutability = SharedContainerMutability::try_from(self.mutability)
+            .map_err(|_| {
+            DatexJsError::new(
+                JsErrorKind::Conversion,
+                "InvalidMutability",
+                format!("Invalid mutability: {}", self.mutability),
+            )
+        })?;
+        Ok((value, allowed_type, mutability))
+    }
+}
+
+// the shared value fields are not flattened, since flattening
+// is not supported for preserved JS values
+#[derive(Serialize, Deserialize)]
+struct StoredPointer {
+    address: String,
+    #[serde(with = "serde_wasm_bindgen::preserve")]
+    value: JsValue,
+    #[serde(with = "serde_wasm_bindgen::preserve")]
+    allowed_type: JsValue,
+    #[serde(rename = "mut")]
+    mutability: u8,
+}
+
+/// Serializes a pointer with its value, allowed type and mutability to
+/// be stored in a pointer storage.
+/// The data consists of the magic bytes, the format version and the
+/// pointer as UTF-8 encoded DIF (JSON).
+pub fn encode_pointer(
+    internal: &RuntimeInternal,
+    address: &PointerAddress,
+) -> Result<Vec<u8>, DatexJsError> {
+    let state = SharedValueState::of_pointer(internal, address)?;
+    let pointer = StoredPointer {
+        address: address.to_address_string(),
+        value: state.value,
+        allowed_type: state.allowed_type,
+        mutability: state.mutability,
+    };
+    let json =
+        JSON::stringify(&to_js_value(&pointer).map_err(conversion_error)?)
+            .map_err(|_| {
+                invalid_stored_pointer("Failed to serialize the pointer")
+            })?;
+    let mut bytes = STORED_POINTER_MAGIC.to_vec();
+    bytes.push(STORED_POINTER_VERSION);
+    bytes.extend_from_slice(String::from(json).as_bytes());
+    Ok(bytes)
+}
+
+/// Deserializes a pointer that was serialized with `encode_pointer`
+/// to a DIF shared value `{ value, allowed_type, mut }`
+pub fn decode_pointer(bytes: &[u8]) -> Result<JsValue, DatexJsError> {
+    let Some(payload) = bytes.strip_prefix(STORED_POINTER_MAGIC.as_slice())
+    else {
+        return Err(invalid_stored_pointer("Missing header"));
+    };
+    let Some((&version, payload)) = payload.split_first() else {
+        return Err(invalid_stored_pointer("Missing format version"));
+    };
+    if version != STORED_POINTER_VERSION {
+        return Err(invalid_stored_pointer(format!(
+            "Format version {version} is not supported, expected version {STORED_POINTER_VERSION}"
+        )));
+    }
+    let json = std::str::from_utf8(payload)
+        .map_err(|_| invalid_stored_pointer("Payload is not valid UTF-8"))?;
+    let value = JSON::parse(json)
+        .map_err(|_| invalid_stored_pointer("Payload is not valid JSON"))?;
+    let pointer: StoredPointer = from_value(value).map_err(conversion_error)?;
+    to_js_value(&SharedValueState {
+        value: pointer.value,
+        allowed_type: pointer.allowed_type,
+        mutability: pointer.mutability,
+    })
+    .map_err(conversion_error)
+}
+
+fn invalid_stored_pointer(message: impl Into<String>) -> DatexJsError {
+    DatexJsError::new(JsErrorKind::Storage, "InvalidStorageData", message)
+}
+
+/// Inserts a pointer that was serialized with `encode_pointer` into the
+/// memory of the runtime under its stored address, so that it can be
+/// resolved from memory afterwards.
+/// Fails if the address is already in use
+pub fn load_pointer(
+    internal: &RuntimeInternal,
+    address: &PointerAddress,
+    bytes: &[u8],
+) -> Result<(), DatexJsError> {
+    let state: SharedValueState =
+        from_value(decode_pointer(bytes)?).map_err(conversion_error)?;
+    let (value, allowed_type, mutability) = state.into_parts()?;
+    internal
+        .create_pointer_at(address.clone(), value, allowed_type, mutability)
+        .js_err(JsErrorKind::Storage, "LoadPointerFailed")?;
+    Ok(())
+}
@@ -0,0 +1,115 @@
+use async_trait::async_trait;
+use datex_core::shared_values::pointer_address::PointerAddress;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::js_sys::{Array, Uint8Array};
+
+use crate::{
+    js_utils::{call_method, has_method},
+    storage::{PointerStorage, PointerStorageError},
+};
+
+/// A pointer storage that is implemented in JavaScript.
+/// The storage object must provide the methods
+/// `get(address) -> Uint8Array | null`, `put(address, data)`,
+/// `delete(address)` and `list() -> string[]`, which may return promises.
+pub struct JsPointerStorage {
+    storage: JsValue,
+}
+
+impl JsPointerStorage {
+    const METHODS: [&'static str; 4] = ["get", "put", "delete", "list"];
+
+    pub fn new(storage: JsValue) -> Result<Self, PointerStorageError> {
+        for method in Self::METHODS {
+            if !has_method(&storage, method) {
+                return Err(PointerStorageError::Backend(format!(
+                    "The pointer storage does not implement the method '{method}'"
+                )));
+            }
+        }
+        Ok(JsPointerStorage { storage })
+    }
+
+    /// Calls a method of the storage object and awaits the result
+    async fn call(
+        &self,
+        method: &str,
+        args: &[JsValue],
+    ) -> Result<JsValue, PointerStorageError> {
+        call_method(&self.storage, method, args)
+            .await
+            .map_err(|e| PointerStorageError::Backend(format!("{e:?}")))
+    }
+}
+
+#[async_trait(?Send)]
+impl PointerStorage for JsPointerStorage {
+    async fn get(
+        &self,
+        address: &PointerAddress,
+    ) -> Result<Option<Vec<u8>>, PointerStorageError> {
+        let data = self
+            .call("get", &[address.to_address_string().into()])
+            .await?;
+        if data.is_null() || data.is_undefined() {
+            return Ok(None);
+        }
+        data.dyn_into::<Uint8Array>()
+            .map(|data| Some(data.to_vec()))
+            .map_err(|_| {
+                PointerStorageError::InvalidData(format!(
+                    "Expected a Uint8Array for pointer {}",
+                    address.to_address_string()
+                ))
+            })
+    }
+
+    async fn put(
+        &self,
+        address: &PointerAddress,
+        data: Vec<u8>,
+    ) -> Result<(), PointerStorageError> {
+        self.call(
+            "put",
+            &[
+                address.to_address_string().into(),
+                Uint8Array::from(data.as_slice()).into(),
+            ],
+        )
+        .await
+        .map(|_| ())
+    }
+
+    async fn delete(
+        &self,
+        address: &PointerAddress,
+    ) -> Result<(), PointerStorageError> {
+        self.call("delete", &[address.to_address_string().into()])
+            .await
+            .map(|_| ())
+    }
+
+    async fn list(&self) -> Result<Vec<PointerAddress>, PointerStorageError> {
+        let addresses = self.call("list", &[]).await?;
+        if !Array::is_array(&addresses) {
+            return Err(PointerStorageError::InvalidData(
+                "Expected an array of pointer addresses".to_string(),
+            ));
+        }
+        Array::from(&addresses)
+            .iter()
+            .map(|address| {
+                let address = address.as_string().ok_or_else(|| {
+                    PointerStorageError::InvalidData(
+                        "Expected a pointer address string".to_string(),
+                    )
+                })?;
+                PointerAddress::try_from(address.as_str()).map_err(|_| {
+                    PointerStorageError::InvalidData(format!(
+                        "Invalid pointer address '{address}'"
+                    ))
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,58 @@
+use std::{cell::RefCell, collections::HashMap};
+
+use async_trait::async_trait;
+use datex_core::shared_values::pointer_address::PointerAddress;
+
+use crate::storage::{PointerStorage, PointerStorageError};
+
+/// A pointer storage that keeps all pointers in memory,
+/// used for tests and as a reference implementation
+#[derive(Default)]
+pub struct InMemoryPointerStorage {
+    // pointers by address string
+    pointers: RefCell<HashMap<String, (PointerAddress, Vec<u8>)>>,
+}
+
+#[async_trait(?Send)]
+impl PointerStorage for InMemoryPointerStorage {
+    async fn get(
+        &self,
+        address: &PointerAddress,
+    ) -> Result<Option<Vec<u8>>, PointerStorageError> {
+        Ok(self
+            .pointers
+            .borrow()
+            .get(&address.to_address_string())
+            .map(|(_, data)| data.clone()))
+    }
+
+    async fn put(
+        &self,
+        address: &PointerAddress,
+        data: Vec<u8>,
+    ) -> Result<(), PointerStorageError> {
+        self.pointers
+            .borrow_mut()
+            .insert(address.to_address_string(), (address.clone(), data));
+        Ok(())
+    }
+
+    async fn delete(
+        &self,
+        address: &PointerAddress,
+    ) -> Result<(), PointerStorageError> {
+        self.pointers
+            .borrow_mut()
+            .remove(&address.to_address_string());
+        Ok(())
+    }
+
+    async fn list(&self) -> Result<Vec<PointerAddress>, PointerStorageError> {
+        Ok(self
+            .pointers
+            .borrow()
+            .values()
+            .map(|(address, _)| address.clone())
+            .collect())
+    }
+}
@@ -0,0 +1,70 @@
+use std::{cell::RefCell, fmt::Display, rc::Rc};
+
+use async_trait::async_trait;
+use datex_core::shared_values::pointer_address::PointerAddress;
+
+use crate::utils::errors::{DatexJsError, JsErrorKind};
+
+pub mod encoding;
+pub mod js_storage;
+pub mod memory_storage;
+pub mod snapshot;
+pub mod write_through;
+
+#[derive(Debug, Clone)]
+pub enum PointerStorageError {
+    // the storage backend failed, e.g. an IndexedDB transaction was aborted
+    Backend(String),
+    // the storage returned data that is not a valid stored pointer
+    InvalidData(String),
+}
+
+impl Display for PointerStorageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PointerStorageError::Backend(message) => {
+                write!(f, "Pointer storage error: {message}")
+            }
+            PointerStorageError::InvalidData(message) => {
+                write!(f, "Invalid data in pointer storage: {message}")
+            }
+        }
+    }
+}
+
+impl From<PointerStorageError> for DatexJsError {
+    fn from(err: PointerStorageError) -> Self {
+        let code = match &err {
+            PointerStorageError::Backend(_) => "StorageBackendFailed",
+            PointerStorageError::InvalidData(_) => "InvalidStorageData",
+        };
+        DatexJsError::new(JsErrorKind::Storage, code, err.to_string())
+    }
+}
+
+/// A persistent storage for pointers, e.g. IndexedDB or OPFS.
+/// Pointers are stored as opaque bytes (see `encoding::encode_pointer`),
+/// so that a storage does not need to know about the DIF format.
+#[async_trait(?Send)]
+pub trait PointerStorage {
+    async fn get(
+        &self,
+        address: &PointerAddress,
+    ) -> Result<Option<Vec<u8>>, PointerStorageError>;
+
+    async fn put(
+        &self,
+        address: &PointerAddress,
+        data: Vec<u8>,
+    ) -> Result<(), PointerStorageError>;
+
+    async fn delete(
+        &self,
+        address: &PointerAddress,
+    ) -> Result<(), PointerStorageError>;
+
+    async fn list(&self) -> Result<Vec<PointerAddress>, PointerStorageError>;
+}
+
+/// The pointer storage of a runtime, shared with its DIF handles
+pub type PointerStorageSlot = Rc<RefCell<Option<Rc<dyn PointerStorage>>>>;
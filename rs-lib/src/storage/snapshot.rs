@@ -0,0 +1,208 @@
+use std::collections::HashSet;
+
+use datex_core::{
+    runtime::RuntimeInternal, shared_values::pointer_address::PointerAddress,
+};
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::js_sys::{Array, Object, Reflect};
+
+use crate::{
+    storage::encoding::{decode_pointer, encode_pointer, load_pointer},
+    utils::errors::{DatexJsError, JsErrorKind},
+};
+
+/// Magic bytes at the start of every memory snapshot
+const SNAPSHOT_MAGIC: &[u8; 4] = b"DXSN";
+/// Version of the snapshot format, incremented on incompatible changes
+const SNAPSHOT_VERSION: u8 = 1;
+
+/// Serializes the given pointers with their values, allowed types and
+/// mutability into a binary snapshot.
+/// The snapshot consists of the magic bytes, the format version, the
+/// number of pointers (u32) and for each pointer the length of its address
+/// (u16), the address, the length of its data (u32) and the pointer
+/// encoded with `encode_pointer`. All integers are little endian.
+pub fn create_snapshot(
+    internal: &RuntimeInternal,
+    addresses: &[PointerAddress],
+) -> Result<Vec<u8>, DatexJsError> {
+    let mut bytes = SNAPSHOT_MAGIC.to_vec();
+    bytes.push(SNAPSHOT_VERSION);
+    bytes.extend_from_slice(&(addresses.len() as u32).to_le_bytes());
+    for address in addresses {
+        let address_string = address.to_address_string();
+        let data = encode_pointer(internal, address)?;
+        bytes.extend_from_slice(&(address_string.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(address_string.as_bytes());
+        bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&data);
+    }
+    Ok(bytes)
+}
+
+/// Inserts all pointers of a snapshot into the memory of the runtime under
+/// their original addresses and returns the addresses.
+/// The snapshot is validated before any pointer is inserted: fails with
+/// `AddressInUse` if one of the addresses is already in memory and with
+/// `CyclicSnapshotReferences` if pointers of the snapshot reference each
+/// other in a cycle, since a pointer can only be inserted after the
+/// pointers its value references.
+pub fn restore_snapshot(
+    internal: &RuntimeInternal,
+    bytes: &[u8],
+) -> Result<Vec<PointerAddress>, DatexJsError> {
+    let mut pointers = decode_snapshot(bytes)?;
+    for (address, _, _) in &pointers {
+        if internal
+            .resolve_pointer_address_in_memory(address.clone())
+            .is_ok()
+        {
+            return Err(DatexJsError::new(
+                JsErrorKind::Storage,
+                "AddressInUse",
+                format!(
+                    "Pointer {} is already in memory",
+                    address.to_address_string()
+                ),
+            ));
+        }
+    }
+
+    // pointers are inserted after all pointers of the snapshot they reference
+    let snapshot_addresses = pointers
+        .iter()
+        .map(|(address, _, _)| address.to_address_string())
+        .collect::<HashSet<_>>();
+    let mut pending = snapshot_addresses.clone();
+    let mut ordered = Vec::with_capacity(pointers.len());
+    while !pointers.is_empty() {
+        let next = pointers
+            .iter()
+            .position(|(_, _, references)| {
+                references.iter().all(|reference| {
+                    !snapshot_addresses.contains(reference)
+                        || !pending.contains(reference)
+                })
+            })
+            .ok_or_else(|| {
+                DatexJsError::new(
+                    JsErrorKind::Storage,
+                    "CyclicSnapshotReferences",
+                    "The snapshot contains cyclic references between pointers, which can not be restored",
+                )
+            })?;
+        let (address, data, _) = pointers.remove(next);
+        pending.remove(&address.to_address_string());
+        ordered.push((address, data));
+    }
+
+    for (address, data) in &ordered {
+        load_pointer(internal, address, data)?;
+    }
+    Ok(ordered.into_iter().map(|(address, _)| address).collect())
+}
+
+/// Splits a snapshot into the addresses, the encoded pointers and the
+/// addresses referenced by the pointer values
+fn decode_snapshot(
+    bytes: &[u8],
+) -> Result<Vec<(PointerAddress, Vec<u8>, Vec<String>)>, DatexJsError> {
+    let Some(payload) = bytes.strip_prefix(SNAPSHOT_MAGIC.as_slice()) else {
+        return Err(invalid_snapshot("Missing snapshot header"));
+    };
+    let Some((&version, payload)) = payload.split_first() else {
+        return Err(invalid_snapshot("Missing snapshot version"));
+    };
+    if version != SNAPSHOT_VERSION {
+        return Err(DatexJsError::new(
+            JsErrorKind::Storage,
+            "UnsupportedSnapshotVersion",
+            format!(
+                "Snapshot version {version} is not supported, expected version {SNAPSHOT_VERSION}"
+            ),
+        ));
+    }
+
+    let mut reader = SnapshotReader(payload);
+    let count = u32::from_le_bytes(reader.take_array()?);
+    let mut pointers = vec![];
+    for _ in 0..count {
+        let address_length = u16::from_le_bytes(reader.take_array()?);
+        let address =
+            std::str::from_utf8(reader.take(address_length as usize)?)
+                .map_err(|_| {
+                    invalid_snapshot("Pointer address is not valid UTF-8")
+                })?;
+        let address = PointerAddress::try_from(address).map_err(|_| {
+            invalid_snapshot(format!("Invalid pointer address: {address}"))
+        })?;
+        let data_length = u32::from_le_bytes(reader.take_array()?);
+        let data = reader.take(data_length as usize)?.to_vec();
+        let state = decode_pointer(&data)?;
+        let mut references = vec![];
+        if let Ok(value) = Reflect::get(&state, &"value".into()) {
+            collect_references(&value, &mut references);
+        }
+        pointers.push((address, data, references));
+    }
+    if !reader.0.is_empty() {
+        return Err(invalid_snapshot("Unexpected data after the last pointer"));
+    }
+    Ok(pointers)
+}
+
+struct SnapshotReader<'a>(&'a [u8]);
+
+impl<'a> SnapshotReader<'a> {
+    fn take(&mut self, length: usize) -> Result<&'a [u8], DatexJsError> {
+        if self.0.len() < length {
+            return Err(invalid_snapshot("Snapshot is truncated"));
+        }
+        let (taken, rest) = self.0.split_at(length);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn take_array<const N: usize>(&mut self) -> Result<[u8; N], DatexJsError> {
+        Ok(self.take(N)?.try_into().expect("length was checked"))
+    }
+}
+
+fn invalid_snapshot(message: impl Into<String>) -> DatexJsError {
+    DatexJsError::new(JsErrorKind::Storage, "InvalidSnapshot", message)
+}
+
+/// Collects all pointer addresses referenced in a DIF value container.
+/// A DIF value container is either a pointer address (string) or a DIF value
+/// object `{ type?, value }`, where the value can contain nested containers:
+/// arrays contain containers, maps contain [key, value] container pairs
+/// and objects map string keys to containers
+fn collect_references(container: &JsValue, references: &mut Vec<String>) {
+    if let Some(address) = container.as_string() {
+        references.push(address);
+        return;
+    }
+    if !container.is_object() {
+        return;
+    }
+    let Ok(value) = Reflect::get(container, &"value".into()) else {
+        return;
+    };
+    if Array::is_array(&value) {
+        for item in value.unchecked_ref::<Array>().iter() {
+            if Array::is_array(&item) {
+                for entry in item.unchecked_ref::<Array>().iter() {
+                    collect_references(&entry, references);
+                }
+            } else {
+                collect_references(&item, references);
+            }
+        }
+    } else if value.is_object() {
+        for key in Object::keys(value.unchecked_ref::<Object>()).iter() {
+            if let Ok(item) = Reflect::get(&value, &key) {
+                collect_references(&item, references);
+            }
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use std::{
+    cell::RefCell,
+    rc::{Rc, Weak},
+};
+
+use datex_core::{
+    dif::interface::DIFInterface,
+    runtime::RuntimeInternal,
+    shared_values::{
+        observers::{ObserveOptions, TransceiverId},
+        pointer_address::PointerAddress,
+    },
+};
+use log::error;
+use wasm_bindgen_futures::spawn_local;
+
+use crate::{
+    storage::{PointerStorageSlot, encoding::encode_pointer},
+    utils::{
+        errors::{DatexJsError, JsErrorKind, ToDatexJsError},
+        logger::RuntimeLogContext,
+    },
+};
+
+/// Transceiver id of the write-through observers, distinct from the
+/// transceiver ids of DIF clients so that updates of all clients are stored
+const STORAGE_TRANSCEIVER_ID: TransceiverId = TransceiverId::MAX;
+
+/// Writes pointers through to the pointer storage of a runtime.
+/// An observer is registered for every attached pointer, so that the
+/// stored pointer is updated on every change, independent of the source of
+/// the update (DIF, script execution or remote endpoints).
+/// Writes are executed in a spawned task and coalesced per pointer, write
+/// errors are logged.
+#[derive(Clone)]
+pub struct WriteThrough {
+    runtime: Weak<RuntimeInternal>,
+    storage: PointerStorageSlot,
+    log_context: RuntimeLogContext,
+    state: Rc<WriteThroughState>,
+}
+
+#[derive(Default)]
+struct WriteThroughState {
+    // attached pointers with the id of their write-through observer
+    observers: RefCell<Vec<(PointerAddress, u32)>>,
+    // attached pointers with a scheduled write
+    pending: RefCell<Vec<PointerAddress>>,
+}
+
+impl WriteThroughState {
+    fn is_attached(&self, address: &PointerAddress) -> bool {
+        self.observers.borrow().iter().any(|(a, _)| a == address)
+    }
+}
+
+impl WriteThrough {
+    pub fn new(
+        runtime: &Rc<RuntimeInternal>,
+        storage: PointerStorageSlot,
+        log_context: RuntimeLogContext,
+    ) -> Self {
+        WriteThrough {
+            runtime: Rc::downgrade(runtime),
+            storage,
+            log_context,
+            state: Rc::default(),
+        }
+    }
+
+    /// Stores the pointer now and on every following update,
+    /// until it is detached. Has no effect if no storage is set.
+    pub fn attach(&self, address: &PointerAddress) -> Result<(), DatexJsError> {
+        if self.storage.borrow().is_none() || self.state.is_attached(address) {
+            return Ok(());
+        }
+        let Some(runtime) = self.runtime.upgrade() else {
+            return Ok(());
+        };
+        let write_through = self.clone();
+        let observed = address.clone();
+        let observer_id = runtime
+            .observe_pointer(
+                STORAGE_TRANSCEIVER_ID,
+                address.clone(),
+                ObserveOptions::default(),
+                move |_, _| write_through.schedule_write(&observed),
+            )
+            .js_err(JsErrorKind::Storage, "WriteThroughFailed")?;
+        self.state
+            .observers
+            .borrow_mut()
+            .push((address.clone(), observer_id));
+        self.schedule_write(address);
+        Ok(())
+    }
+
+    /// Stops storing the pointer, a scheduled write is dropped.
+    /// Returns true if the pointer was attached
+    pub fn detach(&self, address: &PointerAddress) -> bool {
+        let observer_id = {
+            let mut observers = self.state.observers.borrow_mut();
+            let Some(index) = observers.iter().position(|(a, _)| a == address)
+            else {
+                return false;
+            };
+            observers.remove(index).1
+        };
+        self.state.pending.borrow_mut().retain(|a| a != address);
+        if let Some(runtime) = self.runtime.upgrade()
+            && let Err(e) =
+                runtime.unobserve_pointer(address.clone(), observer_id)
+        {
+            error!("Failed to remove write-through observer: {e:?}");
+        }
+        true
+    }
+
+    /// Detaches all pointers
+    pub fn detach_all(&self) {
+        let addresses = self
+            .state
+            .observers
+            .borrow()
+            .iter()
+            .map(|(address, _)| address.clone())
+            .collect::<Vec<_>>();
+        for address in &addresses {
+            self.detach(address);
+        }
+    }
+
+    /// Removes the pointer from the storage, if set
+    pub fn delete(&self, address: &PointerAddress) {
+        let Some(storage) = self.storage.borrow().clone() else {
+            return;
+        };
+        let address = address.clone();
+        spawn_local(self.log_context.scope(async move {
+            if let Err(err) = storage.delete(&address).await {
+                error!(
+                    "Failed to delete pointer {} from the storage: {err}",
+                    address.to_address_string()
+                );
+            }
+        }));
+    }
+
+    fn schedule_write(&self, address: &PointerAddress) {
+        if self.state.pending.borrow().contains(address) {
+            return;
+        }
+        self.state.pending.borrow_mut().push(address.clone());
+        let write_through = self.clone();
+        let address = address.clone();
+        spawn_local(self.log_context.scope(async move {
+            write_through.write(address).await;
+        }));
+    }
+
+    /// Serializes the pointer when the write is executed, so that all
+    /// updates since the write was scheduled are included
+    async fn write(&self, address: PointerAddress) {
+        {
+            let mut pending = self.state.pending.borrow_mut();
+            let Some(index) = pending.iter().position(|a| a == &address) else {
+                // the pointer was detached in the meantime
+                return;
+            };
+            pending.remove(index);
+        }
+        let (Some(runtime), Some(storage)) =
+            (self.runtime.upgrade(), self.storage.borrow().clone())
+        else {
+            return;
+        };
+        let data = match encode_pointer(&runtime, &address) {
+            Ok(data) => data,
+            Err(err) => {
+                error!(
+                    "Failed to serialize pointer {}: {err}",
+                    address.to_address_string()
+                );
+                return;
+            }
+        };
+        if let Err(err) = storage.put(&address, data).await {
+            error!(
+                "Failed to store pointer {}: {err}",
+                address.to_address_string()
+            );
+        }
+    }
+}
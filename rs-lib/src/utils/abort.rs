@@ -0,0 +1,45 @@
+use futures::{FutureExt, select};
+use futures_channel::oneshot;
+use wasm_bindgen::{JsCast, JsValue, prelude::Closure};
+use web_sys::AbortSignal;
+
+/// Runs the given future until it completes or the abort signal is triggered.
+/// If the signal is aborted, the future is dropped (cancelling all pending work
+/// including its execution context) and the abort reason of the signal is
+/// returned as error (an `AbortError` DOMException if no reason was provided).
+pub async fn run_abortable<T, F>(
+    future: F,
+    abort_signal: Option<AbortSignal>,
+) -> Result<T, JsValue>
+where
+    F: Future<Output = Result<T, JsValue>>,
+{
+    let Some(abort_signal) = abort_signal else {
+        return future.await;
+    };
+    if abort_signal.aborted() {
+        return Err(abort_signal.reason());
+    }
+
+    let (abort_tx, abort_rx) = oneshot::channel::<()>();
+    let on_abort = Closure::once(move || {
+        let _ = abort_tx.send(());
+    });
+    abort_signal.add_event_listener_with_callback(
+        "abort",
+        on_abort.as_ref().unchecked_ref(),
+    )?;
+
+    futures::pin_mut!(future);
+    let result = select! {
+        result = future.fuse() => result,
+        _ = abort_rx.fuse() => Err(abort_signal.reason()),
+    };
+
+    // the listener must be removed before the closure is dropped
+    let _ = abort_signal.remove_event_listener_with_callback(
+        "abort",
+        on_abort.as_ref().unchecked_ref(),
+    );
+    result
+}
@@ -0,0 +1,85 @@
+use std::{
+    cell::RefCell,
+    rc::{Rc, Weak},
+};
+
+use wasm_bindgen::JsValue;
+use web_sys::js_sys::Function;
+
+/// A JS callback that is called from Rust and can be released at any time,
+/// e.g. when the runtime is closed. Calling a released callback is a no-op.
+#[derive(Clone)]
+pub struct JsCallback(Rc<RefCell<Option<Function>>>);
+
+impl JsCallback {
+    fn function(&self) -> Option<Function> {
+        // clone the function so that the callback can be released during the call
+        self.0.borrow().clone()
+    }
+
+    pub fn is_released(&self) -> bool {
+        self.0.borrow().is_none()
+    }
+
+    pub fn release(&self) {
+        self.0.borrow_mut().take();
+    }
+
+    pub fn call1(&self, arg1: &JsValue) -> Result<JsValue, JsValue> {
+        match self.function() {
+            Some(function) => function.call1(&JsValue::NULL, arg1),
+            None => Ok(JsValue::UNDEFINED),
+        }
+    }
+
+    pub fn call2(
+        &self,
+        arg1: &JsValue,
+        arg2: &JsValue,
+    ) -> Result<JsValue, JsValue> {
+        match self.function() {
+            Some(function) => function.call2(&JsValue::NULL, arg1, arg2),
+            None => Ok(JsValue::UNDEFINED),
+        }
+    }
+
+    pub fn call3(
+        &self,
+        arg1: &JsValue,
+        arg2: &JsValue,
+        arg3: &JsValue,
+    ) -> Result<JsValue, JsValue> {
+        match self.function() {
+            Some(function) => function.call3(&JsValue::NULL, arg1, arg2, arg3),
+            None => Ok(JsValue::UNDEFINED),
+        }
+    }
+}
+
+/// Keeps track of all JS callbacks that are registered on a runtime,
+/// so that they can be released together when the runtime is closed.
+/// Callbacks that are dropped on the Rust side are removed automatically.
+#[derive(Clone, Default)]
+pub struct JsCallbackRegistry {
+    callbacks: Rc<RefCell<Vec<Weak<RefCell<Option<Function>>>>>>,
+}
+
+impl JsCallbackRegistry {
+    pub fn register(&self, function: Function) -> JsCallback {
+        let callback = Rc::new(RefCell::new(Some(function)));
+        let mut callbacks = self.callbacks.borrow_mut();
+        callbacks.retain(|callback| callback.strong_count() > 0);
+        callbacks.push(Rc::downgrade(&callback));
+        JsCallback(callback)
+    }
+
+    /// Releases all registered JS callbacks
+    pub fn release_all(&self) {
+        let callbacks = self.callbacks.take();
+        for callback in callbacks {
+            if let Some(callback) = callback.upgrade() {
+                callback.borrow_mut().take();
+            }
+        }
+    }
+}
@@ -1,6 +1,188 @@
+use std::{
+    error::Error,
+    fmt::{Debug, Display},
+    ops::Range,
+};
+
+use wasm_bindgen::{JsError, JsValue};
+use web_sys::js_sys::Reflect;
+
+use crate::js_utils::js_object;
+
+/// The category of an error that is passed to JavaScript,
+/// exposed as `kind` property on the JS error object
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsErrorKind {
+    Compiler,
+    Decompiler,
+    Execution,
+    DIF,
+    ComHub,
+    Network,
+    Conversion,
+    Storage,
+    Crypto,
+    Runtime,
+}
+
+impl JsErrorKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JsErrorKind::Compiler => "compiler",
+            JsErrorKind::Decompiler => "decompiler",
+            JsErrorKind::Execution => "execution",
+            JsErrorKind::DIF => "dif",
+            JsErrorKind::ComHub => "com_hub",
+            JsErrorKind::Network => "network",
+            JsErrorKind::Conversion => "conversion",
+            JsErrorKind::Storage => "storage",
+            JsErrorKind::Crypto => "crypto",
+            JsErrorKind::Runtime => "runtime",
+        }
+    }
+}
+
+/// A structured error that is converted to a JS `Error` object with the
+/// additional properties `kind`, `code`, `span` and `cause`.
+/// The `code` is a stable identifier of the error that is set explicitly where
+/// the error is created, `span` is the byte range in the script source for
+/// compiler errors and `cause` is the error that caused this error, if any.
+#[derive(Debug, Clone)]
+pub struct DatexJsError {
+    pub kind: JsErrorKind,
+    pub code: String,
+    pub message: String,
+    pub span: Option<Range<usize>>,
+    pub cause: Option<Box<DatexJsError>>,
+}
+
+impl DatexJsError {
+    pub fn new(
+        kind: JsErrorKind,
+        code: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        DatexJsError {
+            kind,
+            code: code.into(),
+            message: message.into(),
+            span: None,
+            cause: None,
+        }
+    }
+
+    /// Creates a structured error with the given code from a Rust error.
+    /// The chain of source errors is exposed as nested `cause` errors
+    pub fn from_error<E: Error + ?Sized>(
+        kind: JsErrorKind,
+        code: impl Into<String>,
+        err: &E,
+    ) -> Self {
+        let error = DatexJsError::new(kind, code, err.to_string());
+        match err.source() {
+            Some(source) => error
+                .with_cause(DatexJsError::from_error(kind, "Source", source)),
+            None => error,
+        }
+    }
+
+    /// Creates a structured error with the given code from a Rust error
+    /// that only implements `Debug`
+    pub fn from_debug<E: Debug>(
+        kind: JsErrorKind,
+        code: impl Into<String>,
+        err: &E,
+    ) -> Self {
+        DatexJsError::new(kind, code, format!("{err:?}"))
+    }
+
+    pub fn with_span(mut self, span: Range<usize>) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    pub fn with_cause(mut self, cause: DatexJsError) -> Self {
+        self.cause = Some(Box::new(cause));
+        self
+    }
+
+    pub fn to_js_value(&self) -> JsValue {
+        let error: JsValue = JsError::new(&self.message).into();
+        let _ = Reflect::set(&error, &"name".into(), &"DatexError".into());
+        let _ =
+            Reflect::set(&error, &"kind".into(), &self.kind.as_str().into());
+        let _ =
+            Reflect::set(&error, &"code".into(), &self.code.as_str().into());
+        if let Some(span) = &self.span {
+            let span = js_object(vec![
+                ("start", span.start as u32),
+                ("end", span.end as u32),
+            ]);
+            let _ = Reflect::set(&error, &"span".into(), &span);
+        }
+        if let Some(cause) = &self.cause {
+            let _ = Reflect::set(&error, &"cause".into(), &cause.to_js_value());
+        }
+        error
+    }
+}
+
+impl Display for DatexJsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl From<DatexJsError> for JsValue {
+    fn from(err: DatexJsError) -> JsValue {
+        err.to_js_value()
+    }
+}
+
+/// Converts a (de)serialization error of a JS value to a structured `DatexJsError`
+pub fn conversion_error(err: serde_wasm_bindgen::Error) -> DatexJsError {
+    DatexJsError::new(
+        JsErrorKind::Conversion,
+        "InvalidJsValue",
+        err.to_string(),
+    )
+}
+
+/// Converts an error of the decompiler to a structured `DatexJsError`
+pub fn decompiler_error(err: impl Display) -> DatexJsError {
+    DatexJsError::new(JsErrorKind::Decompiler, "InvalidDXB", err.to_string())
+}
+
+pub trait ToDatexJsError<T> {
+    /// Converts the error of a result to a structured `DatexJsError`
+    /// of the given kind and with the given code
+    fn js_err(
+        self,
+        kind: JsErrorKind,
+        code: &'static str,
+    ) -> Result<T, DatexJsError>;
+}
+
+impl<T, E: Error> ToDatexJsError<T> for Result<T, E> {
+    fn js_err(
+        self,
+        kind: JsErrorKind,
+        code: &'static str,
+    ) -> Result<T, DatexJsError> {
+        self.map_err(|err| DatexJsError::from_error(kind, code, &err))
+    }
+}
+
 #[macro_export]
 macro_rules! wrap_error_for_js {
     ($wrapper:ident, $source:path) => {
+        $crate::wrap_error_for_js!(
+            $wrapper,
+            $source,
+            $crate::utils::errors::JsErrorKind::Runtime
+        );
+    };
+    ($wrapper:ident, $source:path, $kind:expr) => {
         pub struct $wrapper(pub $source);
 
         impl From<$source> for $wrapper {
@@ -9,9 +191,19 @@ macro_rules! wrap_error_for_js {
             }
         }
 
+        impl From<$wrapper> for $crate::utils::errors::DatexJsError {
+            fn from(err: $wrapper) -> $crate::utils::errors::DatexJsError {
+                $crate::utils::errors::DatexJsError::new(
+                    $kind,
+                    stringify!($wrapper),
+                    err.0.to_string(),
+                )
+            }
+        }
+
         impl From<$wrapper> for wasm_bindgen::JsValue {
             fn from(err: $wrapper) -> wasm_bindgen::JsValue {
-                wasm_bindgen::JsValue::from_str(&err.0.to_string())
+                $crate::utils::errors::DatexJsError::from(err).into()
             }
         }
         impl From<$wrapper> for wasm_bindgen::JsError {
@@ -0,0 +1,119 @@
+use std::{
+    cell::{Cell, RefCell},
+    panic::PanicHookInfo,
+    rc::{Rc, Weak},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use wasm_bindgen::JsValue;
+use web_sys::js_sys::Function;
+
+use crate::js_utils::js_object;
+
+// set when a panic occurred, the wasm instance can not be used afterwards.
+// Runtimes that are created after a panic are poisoned from the start
+static POISONED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    // fatal error handlers of all runtimes
+    static HANDLERS: RefCell<Vec<Weak<FatalErrorHandler>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Notifies a runtime about a panic in the wasm instance and keeps the
+/// poisoned state of the runtime.
+/// Since the wasm module is compiled with `panic = "abort"`, a panic
+/// poisons all runtimes of the instance, not only the one that panicked.
+pub struct FatalErrorHandler {
+    endpoint: String,
+    callback: RefCell<Option<Function>>,
+    poisoned: Cell<bool>,
+}
+
+impl FatalErrorHandler {
+    pub fn new(endpoint: String, callback: Option<Function>) -> Rc<Self> {
+        let handler = Rc::new(FatalErrorHandler {
+            endpoint,
+            callback: RefCell::new(callback),
+            poisoned: Cell::new(POISONED.load(Ordering::SeqCst)),
+        });
+        HANDLERS.with_borrow_mut(|handlers| {
+            handlers.retain(|handler| handler.strong_count() > 0);
+            handlers.push(Rc::downgrade(&handler));
+        });
+        handler
+    }
+
+    pub fn set_callback(&self, callback: Option<Function>) {
+        self.callback.replace(callback);
+    }
+
+    /// Returns true if a panic occurred while this runtime was alive
+    /// or before it was created
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned.get()
+    }
+
+    fn notify(&self, message: &str, location: &JsValue) {
+        self.poisoned.set(true);
+        // the callback might be borrowed if the panic occurred while setting it
+        let Ok(callback) = self.callback.try_borrow() else {
+            return;
+        };
+        if let Some(callback) = callback.as_ref() {
+            let error = js_object(vec![
+                ("message", JsValue::from(message)),
+                ("location", location.clone()),
+                ("endpoint", JsValue::from(self.endpoint.as_str())),
+            ]);
+            // errors in the callback are ignored, the runtime is dead anyway
+            let _ = callback.call1(&JsValue::NULL, &error);
+        }
+    }
+}
+
+/// Installs a panic hook that logs the panic to the console
+/// and notifies the fatal error handlers of all runtimes
+pub fn init_panic_hook() {
+    std::panic::set_hook(Box::new(|info| {
+        console_error_panic_hook::hook(info);
+        POISONED.store(true, Ordering::SeqCst);
+        notify_handlers(info);
+    }));
+}
+
+fn notify_handlers(info: &PanicHookInfo) {
+    let payload = info.payload();
+    let message = payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("Box<dyn Any>");
+    let location = info
+        .location()
+        .map(|location| {
+            js_object(vec![
+                ("file", JsValue::from(location.file())),
+                ("line", JsValue::from(location.line())),
+                ("column", JsValue::from(location.column())),
+            ])
+            .into()
+        })
+        .unwrap_or(JsValue::NULL);
+
+    // the handler list might be borrowed if the panic occurred while registering a runtime
+    let handlers = HANDLERS
+        .try_with(|handlers| {
+            handlers.try_borrow().map(|handlers| {
+                handlers
+                    .iter()
+                    .filter_map(Weak::upgrade)
+                    .collect::<Vec<_>>()
+            })
+        })
+        .ok()
+        .and_then(Result::ok)
+        .unwrap_or_default();
+    for handler in handlers {
+        handler.notify(message, &location);
+    }
+}
@@ -0,0 +1,61 @@
+use futures::{FutureExt, select};
+use gloo_timers::future::TimeoutFuture;
+use serde::Deserialize;
+use wasm_bindgen::JsValue;
+use web_sys::js_sys::Date;
+
+use crate::utils::errors::{DatexJsError, JsErrorKind};
+
+/// Per-call limits for an asynchronous script execution.
+///
+/// Only a wall time limit is supported: the execution loop lives in
+/// datex-core and exposes no instruction or allocation budget.
+/// Synchronous executions can not be interrupted from JS, so the sync
+/// variants of `execute` accept no limits at all.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ExecutionLimits {
+    // maximum wall time of the execution in milliseconds
+    pub max_time_ms: Option<u32>,
+}
+
+fn time_limit_exceeded(max_time_ms: u32) -> JsValue {
+    DatexJsError::new(
+        JsErrorKind::Execution,
+        "TimeLimitExceeded",
+        format!("Execution exceeded the time limit of {max_time_ms}ms"),
+    )
+    .into()
+}
+
+/// Runs the given future until it completes or the wall time limit is reached.
+/// If the timer fires first, the future is dropped (cancelling all pending
+/// work) and an error with code `TimeLimitExceeded` is returned.
+/// The timer can only fire while the execution is suspended (e.g. waiting
+/// for a remote response), so an execution that finishes after the deadline
+/// without yielding is rejected with the same error once it returns.
+pub async fn run_with_limits<T, F>(
+    future: F,
+    limits: &ExecutionLimits,
+) -> Result<T, JsValue>
+where
+    F: Future<Output = Result<T, JsValue>>,
+{
+    let Some(max_time_ms) = limits.max_time_ms else {
+        return future.await;
+    };
+
+    let deadline = Date::now() + max_time_ms as f64;
+    let timeout = TimeoutFuture::new(max_time_ms).fuse();
+    let future = future.fuse();
+    futures::pin_mut!(future, timeout);
+    select! {
+        result = future => {
+            if Date::now() > deadline {
+                Err(time_limit_exceeded(max_time_ms))
+            } else {
+                result
+            }
+        },
+        _ = timeout => Err(time_limit_exceeded(max_time_ms)),
+    }
+}
@@ -0,0 +1,360 @@
+use std::{
+    async_iter::AsyncIterator,
+    cell::RefCell,
+    pin::Pin,
+    rc::{Rc, Weak},
+    str::FromStr,
+    task::{Context, Poll},
+};
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use wasm_bindgen::JsValue;
+use web_sys::{
+    console,
+    js_sys::{Date, Function},
+};
+
+use crate::{
+    js_utils::js_object,
+    utils::errors::{DatexJsError, JsErrorKind},
+};
+
+thread_local! {
+    // configuration for records that are logged outside of a runtime context
+    static GLOBAL_STATE: RefCell<LoggerState> = RefCell::new(LoggerState::default());
+    // the runtime context that is currently active (see `RuntimeLogContext::enter`)
+    static CURRENT_CONTEXT: RefCell<Option<RuntimeLogContext>> = const { RefCell::new(None) };
+    // all runtime contexts, used to calculate the global max level
+    static RUNTIME_STATES: RefCell<Vec<Weak<RefCell<LoggerState>>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Log configuration, either global or of a single runtime
+#[derive(Default)]
+struct LoggerState {
+    // level for all targets without a matching filter
+    default_level: Option<LevelFilter>,
+    // target filters, e.g. ("datex_core::network", Trace)
+    filters: Vec<(String, LevelFilter)>,
+    // JS callback that receives all log records instead of the console
+    sink: Option<Function>,
+}
+
+impl LoggerState {
+    /// Returns the level for the given target, using the filter
+    /// with the longest matching module path, or None if not configured
+    fn level_for(&self, target: &str) -> Option<LevelFilter> {
+        self.filters
+            .iter()
+            .filter(|(module, _)| {
+                target == module
+                    || target
+                        .strip_prefix(module.as_str())
+                        .is_some_and(|rest| rest.starts_with("::"))
+            })
+            .max_by_key(|(module, _)| module.len())
+            .map(|(_, level)| *level)
+            .or(self.default_level)
+    }
+
+    fn max_level(&self) -> LevelFilter {
+        self.filters
+            .iter()
+            .map(|(_, level)| *level)
+            .chain(self.default_level)
+            .max()
+            .unwrap_or(LevelFilter::Off)
+    }
+
+    fn set_level(&mut self, target: Option<&str>, level: LevelFilter) {
+        match target {
+            None => self.default_level = Some(level),
+            Some(target) => {
+                self.filters.retain(|(module, _)| module != target);
+                self.filters.push((target.to_string(), level));
+            }
+        }
+    }
+}
+
+/// Log configuration of a single runtime.
+/// Records that are logged while the context is entered are filtered with
+/// the configuration of the runtime (falling back to the global configuration)
+/// and prefixed with the endpoint of the runtime.
+#[derive(Clone)]
+pub struct RuntimeLogContext {
+    endpoint: Rc<str>,
+    state: Rc<RefCell<LoggerState>>,
+}
+
+impl RuntimeLogContext {
+    pub fn new(endpoint: impl Into<Rc<str>>) -> Self {
+        let state = Rc::new(RefCell::new(LoggerState::default()));
+        RUNTIME_STATES.with_borrow_mut(|states| {
+            states.retain(|state| state.strong_count() > 0);
+            states.push(Rc::downgrade(&state));
+        });
+        RuntimeLogContext {
+            endpoint: endpoint.into(),
+            state,
+        }
+    }
+
+    /// Sets the log level of this runtime for the given target module,
+    /// or the default level if no target is given
+    pub fn set_log_level(&self, target: Option<&str>, level: LevelFilter) {
+        self.state.borrow_mut().set_level(target, level);
+        update_max_level();
+    }
+
+    /// Sets the JS callback that receives all log records of this runtime,
+    /// if None is given, the global sink or the console is used
+    pub fn set_log_sink(&self, sink: Option<Function>) {
+        self.state.borrow_mut().sink = sink;
+    }
+
+    /// Activates this context until the returned guard is dropped
+    pub fn enter(&self) -> LogContextGuard {
+        let previous = CURRENT_CONTEXT.replace(Some(self.clone()));
+        LogContextGuard { previous }
+    }
+
+    /// Wraps a future (or an async iterator) so that this context is active
+    /// whenever it is polled
+    pub fn scope<F>(&self, inner: F) -> LogScope<F> {
+        LogScope {
+            context: Some(self.clone()),
+            inner: Box::pin(inner),
+        }
+    }
+
+    /// Returns the context that is currently active, if any
+    pub fn current() -> Option<RuntimeLogContext> {
+        CURRENT_CONTEXT.with_borrow(|context| context.clone())
+    }
+}
+
+/// Wraps a future (or an async iterator) so that the currently active
+/// context (if any) is also active whenever it is polled later, e.g. when
+/// it is spawned or passed to the com hub of the runtime.
+/// Tasks that are spawned inside datex-core itself (e.g. by the runtime
+/// loop) can not be wrapped, their records use the global configuration.
+pub fn in_current_scope<F>(inner: F) -> LogScope<F> {
+    in_scope(RuntimeLogContext::current().as_ref(), inner)
+}
+
+/// Wraps a future (or an async iterator) so that the given context (if any)
+/// is active whenever it is polled
+pub fn in_scope<F>(
+    context: Option<&RuntimeLogContext>,
+    inner: F,
+) -> LogScope<F> {
+    LogScope {
+        context: context.cloned(),
+        inner: Box::pin(inner),
+    }
+}
+
+/// Spawns a future that inherits the currently active context
+pub fn spawn_local_in_current_scope<F: Future<Output = ()> + 'static>(
+    future: F,
+) {
+    wasm_bindgen_futures::spawn_local(in_current_scope(future));
+}
+
+/// Restores the previously active log context when dropped
+pub struct LogContextGuard {
+    previous: Option<RuntimeLogContext>,
+}
+
+impl Drop for LogContextGuard {
+    fn drop(&mut self) {
+        CURRENT_CONTEXT.set(self.previous.take());
+    }
+}
+
+/// A future or async iterator that is polled with an active runtime
+/// log context
+pub struct LogScope<F> {
+    context: Option<RuntimeLogContext>,
+    inner: Pin<Box<F>>,
+}
+
+impl<F> LogScope<F> {
+    fn enter(&self) -> Option<LogContextGuard> {
+        self.context.as_ref().map(RuntimeLogContext::enter)
+    }
+}
+
+impl<F: Future> Future for LogScope<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        let _guard = self.enter();
+        self.inner.as_mut().poll(cx)
+    }
+}
+
+impl<F: AsyncIterator> AsyncIterator for LogScope<F> {
+    type Item = F::Item;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<F::Item>> {
+        let _guard = self.enter();
+        self.inner.as_mut().poll_next(cx)
+    }
+}
+
+/// A logger that forwards records to the browser console
+/// or to a JS log sink, filtered by runtime and target module
+struct JsLogger;
+
+impl JsLogger {
+    /// Returns the level, sink and endpoint for a target in the current context
+    fn resolve(
+        &self,
+        target: &str,
+    ) -> (LevelFilter, Option<Function>, Option<Rc<str>>) {
+        let context = RuntimeLogContext::current();
+        let (global_level, global_sink) = GLOBAL_STATE.with_borrow(|state| {
+            (
+                state.level_for(target).unwrap_or(LevelFilter::Off),
+                state.sink.clone(),
+            )
+        });
+        match context {
+            Some(context) => {
+                let state = context.state.borrow();
+                (
+                    state.level_for(target).unwrap_or(global_level),
+                    state.sink.clone().or(global_sink),
+                    Some(context.endpoint.clone()),
+                )
+            }
+            None => (global_level, global_sink, None),
+        }
+    }
+}
+
+impl Log for JsLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.resolve(metadata.target()).0
+    }
+
+    fn log(&self, record: &Record) {
+        // the sink is cloned so that no state is borrowed during the JS call
+        let (level, sink, endpoint) = self.resolve(record.target());
+        if record.level() > level {
+            return;
+        }
+        let message = record.args().to_string();
+        match sink {
+            Some(sink) => {
+                let entry = js_object(vec![
+                    ("level", JsValue::from(record.level().as_str())),
+                    ("target", JsValue::from(record.target())),
+                    ("message", JsValue::from(message)),
+                    ("timestamp", JsValue::from(Date::now())),
+                    (
+                        "endpoint",
+                        endpoint
+                            .as_deref()
+                            .map(JsValue::from)
+                            .unwrap_or(JsValue::NULL),
+                    ),
+                ]);
+                // errors in the sink are ignored, they can not be logged
+                let _ = sink.call1(&JsValue::NULL, &entry);
+            }
+            None => {
+                let prefix = endpoint
+                    .map(|endpoint| format!("{endpoint} "))
+                    .unwrap_or_default();
+                let line = JsValue::from(format!(
+                    "{prefix}{} [{}] {}",
+                    record.level(),
+                    record.target(),
+                    message
+                ));
+                match record.level() {
+                    Level::Error => console::error_1(&line),
+                    Level::Warn => console::warn_1(&line),
+                    Level::Info => console::info_1(&line),
+                    Level::Debug => console::debug_1(&line),
+                    Level::Trace => console::log_1(&line),
+                }
+            }
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Installs the global logger, must only be called once
+pub fn init_logger() {
+    if log::set_logger(&JsLogger).is_err() {
+        console::warn_1(&"A logger is already installed".into());
+    }
+    update_max_level();
+}
+
+/// Updates the max level of the `log` crate to the highest level
+/// of the global configuration and all runtime configurations
+fn update_max_level() {
+    let global_level = GLOBAL_STATE.with_borrow(|state| state.max_level());
+    let runtime_level = RUNTIME_STATES.with_borrow(|states| {
+        states
+            .iter()
+            .filter_map(|state| state.upgrade())
+            .map(|state| state.borrow().max_level())
+            .max()
+            .unwrap_or(LevelFilter::Off)
+    });
+    log::set_max_level(global_level.max(runtime_level));
+}
+
+/// Parses a log level, e.g. "info" or "off"
+pub fn parse_level_filter(level: &str) -> Result<LevelFilter, DatexJsError> {
+    LevelFilter::from_str(level).map_err(|_| {
+        DatexJsError::new(
+            JsErrorKind::Runtime,
+            "InvalidLogLevel",
+            format!(
+                "Invalid log level '{level}', expected one of: off, error, warn, info, debug, trace"
+            ),
+        )
+    })
+}
+
+/// Parses comma separated log filters, e.g. "warn,datex_core::network=trace".
+/// A directive without a target sets the default level.
+pub fn parse_log_filters(
+    filters: &str,
+) -> Result<Vec<(Option<String>, LevelFilter)>, DatexJsError> {
+    filters
+        .split(',')
+        .map(str::trim)
+        .filter(|directive| !directive.is_empty())
+        .map(|directive| match directive.split_once('=') {
+            Some((target, level)) => Ok((
+                Some(target.trim().to_string()),
+                parse_level_filter(level.trim())?,
+            )),
+            None => Ok((None, parse_level_filter(directive)?)),
+        })
+        .collect()
+}
+
+/// Sets the global log level for the given target module,
+/// or the default level if no target is given
+pub fn set_log_level(target: Option<&str>, level: LevelFilter) {
+    GLOBAL_STATE.with_borrow_mut(|state| state.set_level(target, level));
+    update_max_level();
+}
+
+/// Sets the global JS callback that receives all log records,
+/// if None is given, logs are written to the console again
+pub fn set_log_sink(sink: Option<Function>) {
+    GLOBAL_STATE.with_borrow_mut(|state| state.sink = sink);
+}
@@ -42,11 +42,22 @@ macro_rules! define_registry {
                         com_hub
                             .remove_interface(interface_uuid.clone())
                             .await
-                            .map_err(|e| JsError::new(&format!("{:?}", e)))?;
+                            .map_err(|e| {
+                                $crate::utils::errors::DatexJsError::from_debug(
+                                    $crate::utils::errors::JsErrorKind::ComHub,
+                                    "RemoveInterfaceFailed",
+                                    &e,
+                                )
+                            })?;
                         Ok(JsValue::TRUE)
                     } else {
                         error!("Failed to find interface");
-                        Err(JsError::new("Failed to find interface").into())
+                        Err($crate::utils::errors::DatexJsError::new(
+                            $crate::utils::errors::JsErrorKind::ComHub,
+                            "InterfaceNotFound",
+                            "Failed to find interface",
+                        )
+                        .into())
                     }
                 })
             }
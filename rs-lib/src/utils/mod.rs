@@ -1,2 +1,7 @@
+pub mod abort;
+pub mod callbacks;
 pub mod errors;
+pub mod fatal_error;
+pub mod limits;
+pub mod logger;
 pub mod macros;
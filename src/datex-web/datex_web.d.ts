@@ -258,6 +258,119 @@ export type ReconnectionConfig = "NoReconnect" | "InstantReconnect" | { Reconnec
 export type TLSMode = { type: "HandledExternally" } | { type: "WithCertificate"; data: { private_key: number[]; certificate: number[] } };
 
 
+export class BlockBuilder {
+    free(): void;
+    [Symbol.dispose](): void;
+    /**
+     * Serializes the block, the block size and receiver
+     * fields of the routing header are calculated automatically
+     */
+    build(): Uint8Array;
+    constructor();
+    set_allow_execution(allow_execution: boolean): void;
+    set_block_number(block_number: number): void;
+    /**
+     * Sets the block type, one of "request", "response", "hello",
+     * "trace" or "trace_back"
+     */
+    set_block_type(block_type: string): void;
+    /**
+     * Sets the DXB body of the block
+     */
+    set_body(body: Uint8Array): void;
+    set_bounce_back(bounce_back: boolean): void;
+    set_context_id(context_id: number): void;
+    /**
+     * Sets the encryption type flag of the routing header, one of "none"
+     * or "encrypted". The body is not encrypted by the builder
+     */
+    set_encryption_type(encryption_type: string): void;
+    set_end_of_context(end_of_context: boolean): void;
+    set_end_of_section(end_of_section: boolean): void;
+    /**
+     * Sets the receiver endpoints of the block
+     */
+    set_receivers(receivers: string[]): void;
+    set_scope_id(scope_id: number): void;
+    set_section_index(section_index: number): void;
+    /**
+     * Sets the sender endpoint, e.g. "@example"
+     */
+    set_sender(sender: string): void;
+    /**
+     * Attaches an unencrypted Ed25519 signature to the block,
+     * or removes the signature if null is given
+     */
+    set_signature(signature?: Uint8Array | null): void;
+    /**
+     * Sets the signature type flag of the routing header, one of "none",
+     * "unencrypted" or "encrypted". The flag is overwritten by
+     * `set_signature`, this allows constructing blocks with a
+     * mismatching signature flag
+     */
+    set_signature_type(signature_type: string): void;
+    /**
+     * Sets the creation timestamp in milliseconds since the unix epoch.
+     * Fails if the timestamp is not a non-negative integer that fits into
+     * the timestamp field of the block header
+     */
+    set_timestamp(timestamp: number): void;
+    /**
+     * Sets the time to live (maximum number of hops) of the block
+     */
+    set_ttl(ttl: number): void;
+}
+
+/**
+ * The crypto primitives of the DATEX runtime.
+ * Keys are passed as raw bytes, public keys are SPKI
+ * and private keys PKCS#8 encoded.
+ */
+export class JSCrypto {
+    free(): void;
+    [Symbol.dispose](): void;
+    aes_ctr_decrypt(key: Uint8Array, iv: Uint8Array, data: Uint8Array): Promise<Uint8Array>;
+    /**
+     * Encrypt data with AES-CTR using a 32 byte key and a 16 byte IV
+     */
+    aes_ctr_encrypt(key: Uint8Array, iv: Uint8Array, data: Uint8Array): Promise<Uint8Array>;
+    constructor();
+    /**
+     * Decode a base58 string that encodes exactly 32 bytes (e.g. a key)
+     */
+    decode_base58_32(encoded: string): Uint8Array;
+    /**
+     * Derive a 32 byte shared secret from an own private key
+     * and the public key of the peer
+     */
+    derive_x25519(private_key: Uint8Array, peer_public_key: Uint8Array): Promise<Uint8Array>;
+    encode_base58(data: Uint8Array): string;
+    /**
+     * Generate an Ed25519 keypair `{ public_key, private_key }`
+     */
+    generate_ed25519(): Promise<any>;
+    /**
+     * Generate an X25519 keypair `{ public_key, private_key }`
+     */
+    generate_x25519(): Promise<any>;
+    hash_sha256(data: Uint8Array): Promise<Uint8Array>;
+    /**
+     * Derive a 32 byte key from the input key material and salt
+     */
+    hkdf_sha256(ikm: Uint8Array, salt: Uint8Array): Promise<Uint8Array>;
+    key_unwrap_rfc3394(kek: Uint8Array, wrapped_key: Uint8Array): Promise<Uint8Array>;
+    /**
+     * Wrap a 32 byte key with a 32 byte key encryption key (RFC 3394)
+     */
+    key_wrap_rfc3394(kek: Uint8Array, key: Uint8Array): Promise<Uint8Array>;
+    /**
+     * Generate cryptographically secure random bytes
+     */
+    random_bytes(length: number): Uint8Array;
+    sign_ed25519(private_key: Uint8Array, data: Uint8Array): Promise<Uint8Array>;
+    verify_ed25519(public_key: Uint8Array, signature: Uint8Array, data: Uint8Array): Promise<boolean>;
+}
+
 export class JSComHub {
     private constructor();
     free(): void;
@@ -271,6 +384,12 @@ export class JSComHub {
     register_incoming_block_interceptor(callback: Function): void;
     register_interface_factory(interface_type: string, factory: Function): void;
     register_outgoing_block_interceptor(callback: Function): void;
+    /**
+     * Removes all interfaces (and their sockets) from the com hub.
+     * All interfaces are removed even if removing one of them fails,
+     * the first error is returned afterwards
+     */
+    remove_all_interfaces(): Promise<void>;
     remove_interface(interface_uuid: string): Promise<void>;
     remove_socket(socket_uuid: string): Promise<void>;
 }
@@ -279,22 +398,306 @@ export class JSRuntime {
     private constructor();
     free(): void;
     [Symbol.dispose](): void;
-    _create_block(body: Uint8Array | null | undefined, receivers: string[]): Uint8Array;
-    crypto_test_tmp(): Promise<Promise<any>>;
+    /**
+     * Add the X25519 public key (SPKI) of an endpoint, used to derive the
+     * key for end-to-end encrypted blocks exchanged with the endpoint.
+     * Keys are also exchanged automatically on first contact with endpoints
+     * that have a trusted key (see `add_trusted_key`), a manually added key
+     * replaces the announced key
+     */
+    add_peer_encryption_key(endpoint: string, public_key: Uint8Array): void;
+    /**
+     * Add the Ed25519 public key (SPKI) of an endpoint,
+     * used to verify blocks and encryption key announcements that were
+     * sent by the endpoint
+     */
+    add_trusted_key(endpoint: string, public_key: Uint8Array): void;
+    /**
+     * Close the runtime: removes all pointer observers, interfaces and
+     * sockets, releases all registered JS callbacks (interceptors, observers,
+     * interface factories) and stops the runtime loop.
+     * The teardown is always completed, if an interface could not be
+     * removed, the error is returned afterwards.
+     * The runtime (and its DIF handles) can not be used anymore after it
+     * was closed, calls fail with a `RuntimeClosed` error. Closing an
+     * already closed runtime has no effect.
+     */
+    close(): Promise<void>;
+    /**
+     * Compile a script to a DXB body without executing it.
+     * If `{ inserted_values }` are given, the script is compiled as a
+     * template and the DIF values are inserted for the `?` placeholders.
+     * Returns an object containing the compiled body and compiler diagnostics.
+     */
+    compile(script: string, options: any): any;
+    /**
+     * Create a named execution context that keeps its state (e.g. declared
+     * variables) between executions. Returns the name of the context, which
+     * can be passed to `execute`, `execute_sync`, `execute_dxb` and
+     * `execute_dxb_sync`.
+     */
+    create_context(options: any): string;
+    /**
+     * Decrypt the body of a block that was encrypted by its sender
+     * with `encrypt_block`. Fails with an `InvalidMac` error if the
+     * block was modified
+     */
+    decrypt_block(block: Uint8Array): Promise<Uint8Array>;
     /**
      * Get a handle to the DIF interface of the runtime
      */
     dif(): RuntimeDIFHandle;
-    execute(script: string, dif_values?: any[] | null): Promise<any>;
-    execute_sync(script: string, dif_values?: any[] | null): any;
-    execute_sync_with_string_result(script: string, dif_values: any[] | null | undefined, decompile_options: any): string;
-    execute_with_string_result(script: string, dif_values: any[] | null | undefined, decompile_options: any): Promise<string>;
+    /**
+     * Remove an execution context
+     */
+    dispose_context(name: string): void;
+    /**
+     * Encrypt the body of a serialized block for its receiver, using a key
+     * derived from the encryption key of this runtime and the public
+     * encryption key of the receiver. The encrypted body is authenticated
+     * with an HMAC over the header, IV and ciphertext
+     */
+    encrypt_block(block: Uint8Array): Promise<Uint8Array>;
+    /**
+     * Execute a script and return the result as a DIF value.
+     * The execution can be cancelled with an optional AbortSignal, in which
+     * case the promise is rejected with the abort reason of the signal.
+     * Optional execution limits (e.g. `{ max_time_ms: 1000 }`) reject the
+     * promise with an error with code `TimeLimitExceeded` if they are exceeded.
+     * If the name of an execution context created with `create_context` is
+     * given, the script is executed in this context and its state is kept.
+     */
+    execute(script: string, dif_values: any[] | null | undefined, abort_signal: AbortSignal | null | undefined, limits: any, context?: string | null): Promise<any>;
+    /**
+     * Execute a precompiled DXB body (e.g. returned by `compile`) and
+     * return the result as a DIF value.
+     * The optional DIF values are bound to the execution as inserted values
+     * for the placeholders of the body, so a body can be compiled once and
+     * executed with different values.
+     * The execution can be cancelled with an optional AbortSignal and
+     * limited with optional execution limits (see `execute`).
+     * If the name of an execution context created with `create_context` is
+     * given, the body is executed in this context and its state is kept.
+     */
+    execute_dxb(dxb_body: Uint8Array, dif_values: any[] | null | undefined, abort_signal: AbortSignal | null | undefined, limits: any, context?: string | null): Promise<any>;
+    /**
+     * Synchronous variant of `execute_dxb`.
+     * Fails if the DXB body contains instructions that require async
+     * execution (e.g. remote calls). No execution limits are supported.
+     */
+    execute_dxb_sync(dxb_body: Uint8Array, dif_values?: any[] | null, context?: string | null): any;
+    /**
+     * Execute a script on a remote endpoint and return the result as a DIF value.
+     * The script is compiled locally, sent to the endpoint via the ComHub
+     * and the promise resolves when the response block is received.
+     * Rejects with an error of kind `network` and code `RemoteTimeout` if no
+     * response is received within `options.timeout` milliseconds
+     * (default: 30 seconds), with code `EndpointUnreachable` if the block
+     * could not be routed to the endpoint, or with code
+     * `RemoteExecutionFailed` if the remote execution failed.
+     */
+    execute_remote(endpoint: string, script: string, dif_values: any[] | null | undefined, options: any): Promise<any>;
+    /**
+     * Synchronous variant of `execute`.
+     * Synchronous executions can not be interrupted, so no execution
+     * limits are supported.
+     */
+    execute_sync(script: string, dif_values?: any[] | null, context?: string | null): any;
+    /**
+     * Synchronous variant of `execute_with_string_result`.
+     * Synchronous executions can not be interrupted, so no execution
+     * limits are supported.
+     */
+    execute_sync_with_string_result(script: string, dif_values: any[] | null | undefined, decompile_options: any, context?: string | null): string;
+    /**
+     * Execute a script and return the decompiled result as a string.
+     * The execution can be cancelled with an optional AbortSignal, in which
+     * case the promise is rejected with the abort reason of the signal.
+     * Optional execution limits (e.g. `{ max_time_ms: 1000 }`) reject the
+     * promise with an error with code `TimeLimitExceeded` if they are exceeded.
+     */
+    execute_with_string_result(script: string, dif_values: any[] | null | undefined, decompile_options: any, abort_signal: AbortSignal | null | undefined, limits: any, context?: string | null): Promise<string>;
+    /**
+     * Export the identity of this runtime, encrypted and authenticated with
+     * a key derived from the given password.
+     * The key is derived with PBKDF2-HMAC-SHA256 (600000 iterations), the
+     * salt and the iteration count are stored in the export.
+     * Fails with `EmptyPassword` if the password is empty.
+     */
+    export_identity(password: string): Promise<Uint8Array>;
+    /**
+     * Generate a new identity (Ed25519 signing and X25519 encryption
+     * keypair) for the endpoint of this runtime, replacing the current keys.
+     * Returns the public keys, see `public_keys`.
+     */
+    generate_identity(): Promise<any>;
+    /**
+     * Import an identity that was exported with `export_identity`,
+     * the identity must belong to the endpoint of this runtime
+     */
+    import_identity(identity: Uint8Array, password: string): Promise<void>;
+    /**
+     * Get information about an execution context
+     */
+    inspect_context(name: string): any;
+    /**
+     * Returns true if this runtime is poisoned by a panic. A poisoned
+     * runtime can not be used anymore.
+     * Note: a panic aborts the whole wasm instance, so it poisons all
+     * runtimes of the instance (including runtimes created afterwards),
+     * not only the runtime that caused it. The wasm module must be
+     * reloaded to create a working runtime.
+     */
+    is_poisoned(): boolean;
+    /**
+     * Get the names of all execution contexts
+     */
+    list_contexts(): string[];
+    /**
+     * Get the addresses of all pointers in the pointer storage
+     */
+    list_stored_pointers(): Promise<string[]>;
+    /**
+     * Get the public keys of all endpoints that blocks can be encrypted
+     * for, as an object mapping endpoint names to keys
+     */
+    peer_encryption_keys(): any;
+    /**
+     * Get the public keys of the endpoint of this runtime as
+     * `{ signing_key, encryption_key }` (SPKI, null if not set)
+     */
+    public_keys(): any;
+    /**
+     * Remove the public key of an endpoint, a new key is exchanged
+     * on the next contact
+     */
+    remove_peer_encryption_key(endpoint: string): void;
+    remove_trusted_key(endpoint: string): void;
+    /**
+     * Require end-to-end encryption for all requests and responses
+     * exchanged with an endpoint. Blocks sent to the endpoint are encrypted,
+     * if its key is not known yet, the keys are exchanged before the block
+     * is sent. The key of an endpoint without a trusted key must be added
+     * with `add_peer_encryption_key`. Unencrypted blocks sent to or received from the endpoint
+     * are dropped, logged and passed to the `on_unencrypted_block` callback.
+     */
+    require_encryption(endpoint: string, required: boolean): void;
+    /**
+     * Reset an execution context, dropping its state
+     */
+    reset_context(name: string): void;
+    /**
+     * Restore the pointers of a snapshot created with `snapshot` under
+     * their original addresses and return the addresses.
+     * Fails without restoring any pointer with an `AddressInUse` error if
+     * one of the addresses is already in memory.
+     * The restored pointers are owned by the runtime with one reference
+     * (released with `release_pointer`) and are written through to the
+     * pointer storage.
+     */
+    restore(snapshot: Uint8Array): string[];
+    /**
+     * Replace the signing keypair ("signing"), the encryption keypair
+     * ("encryption") or both (null) with newly generated keys.
+     * Returns the new public keys, see `public_keys`.
+     */
+    rotate_keys(kind?: string | null): Promise<any>;
+    /**
+     * Encrypt all requests and responses of this runtime that are sent to
+     * a single endpoint. The keys are exchanged on first contact with
+     * endpoints that have a trusted key, until the key of an endpoint is
+     * known, blocks are sent to it unencrypted.
+     * Requires a signing and an encryption key, see `generate_identity`
+     */
+    set_encryption_enabled(enabled: boolean): void;
+    /**
+     * Set a JS object with the methods `load(endpoint)` and
+     * `save(endpoint, data)` (which may return promises) that persists the
+     * identity of this runtime encrypted with the given password, or null to
+     * remove the storage. The password is used like in `export_identity`
+     * and must have high entropy.
+     * If the storage contains an identity for the endpoint, it is loaded and
+     * true is returned. Otherwise, the current identity (if any) is saved.
+     */
+    set_identity_storage(storage: any, password?: string | null): Promise<boolean>;
+    /**
+     * Change the log level of this runtime, either the default level
+     * or the level of a single target module (e.g. "datex_core::network")
+     */
+    set_log_level(level: string, target?: string | null): void;
+    /**
+     * Set a callback that receives all log records of this runtime,
+     * or null to use the global log sink or the console
+     */
+    set_log_sink(sink?: Function | null): void;
+    /**
+     * Set a callback that is called with { message, location, endpoint }
+     * if a panic occurs, or null to remove the callback
+     */
+    set_on_fatal_error(callback?: Function | null): void;
+    /**
+     * Set a callback that is called with `{ endpoint, direction, socket }`
+     * for dropped unencrypted blocks exchanged with an endpoint that
+     * requires encryption, or null to only log these blocks
+     */
+    set_on_unencrypted_block(callback?: Function | null): void;
+    /**
+     * Set a JS pointer storage object with the methods `get(address)`,
+     * `put(address, data)`, `delete(address)` and `list()` (which may return
+     * promises), or null to remove the storage.
+     * Pointers that are not in memory are loaded from the storage into
+     * memory before they are requested from the network. Pointers created
+     * via DIF and pointers loaded from the storage are written through to
+     * the storage on every update (including updates by scripts and remote
+     * endpoints) and deleted from the storage when they are removed from
+     * memory, see `release_pointer`.
+     * When the storage is set, all pointers owned by the runtime are stored.
+     */
+    set_pointer_storage(storage: any): void;
+    /**
+     * Set the signature verification policy for blocks that are received
+     * by the interfaces of this runtime: "off" (default), "verify" (report
+     * invalid signatures) or "require" (drop all blocks without a valid
+     * signature of a trusted key, including data of stream based interfaces
+     * that is not a complete block).
+     * With both "verify" and "require", signed blocks whose timestamp is
+     * outside of the accepted window ("expired") and blocks that were
+     * already received ("replayed") are dropped.
+     * Blocks that violate the policy are logged and passed to the optional
+     * callback as `{ sender, status, socket, dropped }`.
+     * Blocks that are sent by this runtime are signed automatically if a
+     * signing key is set.
+     */
+    set_verification_policy(policy: string, on_verification_failure?: Function | null): void;
+    /**
+     * Sign a serialized block (e.g. created with a `BlockBuilder`)
+     * with the signing key of this runtime
+     */
+    sign_block(block: Uint8Array): Promise<Uint8Array>;
+    /**
+     * Serialize all local pointers in memory (with their values, allowed
+     * types and mutability) into a versioned binary snapshot
+     */
+    snapshot(): Uint8Array;
     /**
      * Start the LSP server, returning a JS function to send messages to Rust
      */
     start_lsp(send_to_js: Function): Function;
+    /**
+     * Use a pointer storage that keeps all stored pointers in memory
+     */
+    use_in_memory_pointer_storage(): void;
     value_to_string(dif_value: any, decompile_options: any): string;
+    /**
+     * Verify the signature of a serialized block against the trusted key
+     * of its sender. Returns "unsigned", "valid", "invalid" or "unknown_key".
+     */
+    verify_block(block: Uint8Array): Promise<string>;
     com_hub: JSComHub;
+    /**
+     * The crypto primitives used by the runtime
+     */
+    readonly crypto: JSCrypto;
     readonly endpoint: string;
     readonly version: string;
 }
@@ -304,6 +707,11 @@ export class Repl {
     [Symbol.dispose](): void;
     execute(script: string): Promise<any>;
     constructor(runtime: JSRuntime, verbose: boolean);
+    /**
+     * Set the execution limits that are applied to every following execution
+     * (e.g. `{ max_time_ms: 1000 }`, null or undefined removes all limits)
+     */
+    set_execution_limits(limits: any): void;
 }
 
 export class RuntimeDIFHandle {
@@ -312,7 +720,45 @@ export class RuntimeDIFHandle {
     [Symbol.dispose](): void;
     apply(callee: any, value: any): any;
     create_pointer(value: any, allowed_type: any, mutability: number): string;
+    /**
+     * List all pointers in memory, optionally filtered with
+     * `{ owned, observed, address_prefix, min_size }`.
+     * Each pointer is described with its address, owner endpoint, allowed
+     * type, mutability, reference count (for pointers owned by the runtime),
+     * JS observer count and remote subscribers. The approximate size is
+     * only computed with `with_size: true` or a `min_size` filter, since
+     * the pointer has to be serialized.
+     * Pointers that can not be described are included with an `error`.
+     */
+    list_pointers(filter: any): any;
+    /**
+     * Observe the updates of a pointer. In addition to the DATEX observe
+     * options, the delivery of updates to the callback can be configured:
+     * `coalesce` ("microtask" or "animation_frame"), `throttle_ms` or
+     * `debounce_ms` collect updates and deliver them together as an array,
+     * with `latest_only`, a single `Replace` update with the current value
+     * of the pointer is delivered instead of the collected updates.
+     * If `setTimeout` is not available, throttled and debounced updates
+     * are delivered immediately.
+     */
     observe_pointer(transceiver_id: number, address: string, observe_options: any, callback: Function): number;
+    /**
+     * Whether the pointer was created or restored by this runtime
+     * and was not released yet
+     */
+    owns_pointer(address: string): boolean;
+    /**
+     * Remove a reference to a pointer owned by the runtime and return the
+     * remaining reference count. The observer that was registered for the
+     * released reference (if any) is removed, other observers are kept.
+     * When the last reference is released, the pointer is no longer owned
+     * by the runtime and its remaining JS observers are removed. It is only
+     * removed from memory and deleted from the pointer storage if
+     * datex-core reports no other owners (DATEX values, e.g. variables of
+     * an execution context, or remote subscribers), otherwise it stays in
+     * memory (and in the storage) as long as it is used by them.
+     */
+    release_pointer(address: string, observer_id?: number | null): number;
     /**
      * Resolve a pointer address, returning a Promise
      * If the pointer is in memory, the promise resolves immediately
@@ -323,9 +769,84 @@ export class RuntimeDIFHandle {
      * Resolve a pointer address synchronously if it's in memory, otherwise return an error
      */
     resolve_pointer_address_sync(address: string): any;
+    /**
+     * Add a reference to a pointer owned by the runtime.
+     * A created pointer starts with one reference.
+     * Returns the new reference count.
+     */
+    retain_pointer(address: string): number;
     unobserve_pointer(address: string, observer_id: number): void;
     update(transceiver_id: number, address: string, update: any): void;
+    /**
+     * Apply multiple updates `[{ address, update }, ...]` atomically.
+     * The updates are first applied to temporary copies of the pointers,
+     * if an update fails, no pointer is changed and the error is returned.
+     * The copies are removed before the updates are applied and are not
+     * listed, stored or owned by the runtime.
+     * Each JS observer is notified once after all updates were applied,
+     * with an array of the updates of its pointer.
+     * If an update fails although it was applied to the copy, the
+     * previous updates of the batch are reverted and the error is
+     * returned, or a `BatchRevertFailed` error caused by it if not all
+     * pointers could be restored.
+     */
+    update_batch(transceiver_id: number, updates: any): void;
+    /**
+     * Change the DATEX observe options and the delivery options of an
+     * observer registered with `observe_pointer`. Pending updates are
+     * delivered with the previous delivery options.
+     */
     update_observer_options(address: string, observer_id: number, observe_options: any): void;
+    /**
+     * Whether the runtime of this handle was closed
+     */
+    readonly closed: boolean;
 }
 
+/**
+ * Compile a script to a DXB body without executing it.
+ * If `{ inserted_values }` are given, the script is compiled as a template
+ * and the DIF values are inserted for the `?` placeholders.
+ * Returns an object containing the compiled body and compiler diagnostics.
+ * Inserted values must not contain references, since no runtime memory
+ * is available.
+ */
+export function compile(script: string, options: any): any;
+
 export function create_runtime(config: any, debug_config: any): Promise<JSRuntime>;
+
+/**
+ * Decompile a raw DXB body (e.g. the body of a block captured by a block
+ * interceptor) to a DATEX source string, using the given decompile options.
+ */
+export function decompile_dxb(dxb_body: Uint8Array, decompile_options: any): string;
+
+/**
+ * Parse raw block bytes (e.g. a block captured by a block interceptor)
+ * into an object describing the routing header, receivers, block header
+ * flags, signature, encryption status and body.
+ * If `{ decompile: true }` is passed, unencrypted bodies are decompiled
+ * with the optional `decompile_options`.
+ */
+export function parse_block(bytes: Uint8Array, options: any): any;
+
+/**
+ * Change the global log level after startup, either the default level
+ * or the level of a single target module (e.g. "datex_core::network").
+ * The global level applies to all runtimes without their own log level.
+ */
+export function set_log_level(level: string, target?: string | null): void;
+
+/**
+ * Set a global callback that receives all log records as objects
+ * { level, target, message, timestamp, endpoint }, or null to log to the console.
+ * Runtimes with their own log sink do not use the global sink.
+ */
+export function set_log_sink(sink?: Function | null): void;
+
+/**
+ * Validate a runtime config without creating a runtime.
+ * Returns an object `{ valid, issues }`, where each issue contains
+ * the affected `field` (if any), an error `code` and a `message`.
+ */
+export function validate_runtime_config(config: any): any;
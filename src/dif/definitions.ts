@@ -177,4 +177,44 @@ export type DIFUpdate = {
 /** Options for observing DIF pointers. */
 export type ObserveOptions = {
     relay_own_updates: boolean;
+    /** Collect updates until the next microtask or animation frame and deliver them together. */
+    coalesce?: "microtask" | "animation_frame";
+    /** Deliver the collected updates at most once per interval. */
+    throttle_ms?: number;
+    /** Deliver the collected updates after no update was received for the interval. */
+    debounce_ms?: number;
+    /** Deliver a single Replace update with the current value instead of the collected updates. */
+    latest_only?: boolean;
+};
+
+/** Filter for listing pointers, all conditions must match. */
+export type PointerFilter = {
+    /** Only pointers that are (or are not) owned by the runtime. */
+    owned?: boolean;
+    /** Only pointers that have (or have no) JS observers. */
+    observed?: boolean;
+    address_prefix?: string;
+    /** Only pointers with an approximate size of at least this many bytes. */
+    min_size?: number;
+    /** Compute the approximate size of every pointer. */
+    with_size?: boolean;
+};
+
+/** Description of a pointer in memory. */
+export type PointerDescription = {
+    address: string;
+    /** The endpoint of the pointer address, null for internal pointers. */
+    owner: string | null;
+    allowed_type: DIFTypeDefinition | null;
+    /** Name of the mutability, e.g. "Mutable". */
+    mutability: string | null;
+    /** Number of JS references if the pointer is owned by the runtime. */
+    ref_count: number | null;
+    observer_count: number;
+    /** Endpoints that subscribed to the updates of the pointer. */
+    subscribers: string[];
+    /** Length of the serialized pointer in bytes, only set if requested. */
+    size: number | null;
+    /** The error if the pointer could not be described. */
+    error: string | null;
 };
@@ -18,6 +18,8 @@ import {
     type DIFValue,
     type DIFValueContainer,
     type ObserveOptions,
+    type PointerDescription,
+    type PointerFilter,
 } from "./definitions.ts";
 import { CoreTypeAddress, CoreTypeAddressRanges } from "./core.ts";
 import { type TypeBinding, TypeRegistry } from "./type-registry.ts";
@@ -30,6 +32,16 @@ export type CustomReferenceMetadata = Record<string | symbol, unknown> & {
     [IS_PROXY_ACCESS]?: boolean;
 };
 
+/** options for an asynchronous script execution */
+export type ExecuteOptions = {
+    /** optional signal to cancel the execution, the execution is rejected with the abort reason */
+    signal?: AbortSignal;
+    /** optional name of an execution context created with `Runtime.createContext` */
+    context?: string;
+    /** optional wall time limit in milliseconds, the execution is rejected with an error with code `TimeLimitExceeded` if it is exceeded */
+    maxTimeMs?: number;
+};
+
 export type ReferenceMetadata<M extends CustomReferenceMetadata> = {
     address: string;
     customMetadata: M;
@@ -126,16 +138,21 @@ export class DIFHandler {
      * Executes a Datex script asynchronously and returns a Promise that resolves to a DIFContainer.
      * @param datexScript - The Datex script source code to execute.
      * @param values - An optional array of values to inject into the script.
+     * @param options - Optional execution options, e.g. an AbortSignal to cancel the execution.
      * @returns A Promise that resolves to the execution result as a DIFContainer.
-     * @throws If an error occurs during execution.
+     * @throws If an error occurs during execution or the execution was aborted.
      */
     public executeDIF(
         datexScript: string,
         values: unknown[] | null = [],
+        options: ExecuteOptions = {},
     ): Promise<DIFValueContainer> {
         return this.#runtime.execute(
             datexScript,
             this.convertToDIFValues(values),
+            options.signal,
+            { max_time_ms: options.maxTimeMs },
+            options.context,
         );
     }
 
@@ -143,21 +160,47 @@ export class DIFHandler {
      * Executes a Datex script synchronously and returns the result as a DIFContainer.
      * @param datexScript - The Datex script source code to execute.
      * @param values - An optional array of values to inject into the script.
+     * @param context - Optional name of an execution context to execute the script in.
      * @returns The execution result as a DIFContainer.
      * @throws If an error occurs during execution.
      */
     public executeSyncDIF(
         datexScript: string,
         values: unknown[] | null = [],
+        context?: string,
     ): DIFValueContainer {
         return this.#runtime.execute_sync(
             datexScript,
             this.convertToDIFValues(values),
+            context,
+        );
+    }
+
+    /**
+     * Executes a precompiled DXB body asynchronously and returns a Promise that resolves to a DIFContainer.
+     * @param dxbBody - The DXB body, e.g. compiled with `Runtime.compile`.
+     * @param values - An optional array of values that are bound to the placeholders of the body.
+     * @param options - Optional execution options, e.g. an AbortSignal or an execution context.
+     * @returns A Promise that resolves to the execution result as a DIFContainer.
+     * @throws If an error occurs during execution or the execution was aborted.
+     */
+    public executeDXBDIF(
+        dxbBody: Uint8Array,
+        values: unknown[] | null = [],
+        options: ExecuteOptions = {},
+    ): Promise<DIFValueContainer> {
+        return this.#runtime.execute_dxb(
+            dxbBody,
+            this.convertToDIFValues(values),
+            options.signal,
+            { max_time_ms: options.maxTimeMs },
+            options.context,
         );
     }
 
     /**
      * Creates a new shared value for the specified DIF value.
+     * The caller holds the first reference to the pointer and releases it with releaseReference.
      * @param difValueContainer - The DIFValueContainer value to create a pointer for.
      * @param allowedType - The allowed type for the pointer.
      * @param mutability - The mutability of the pointer.
@@ -175,6 +218,40 @@ export class DIFHandler {
         );
     }
 
+    /**
+     * Adds a reference to a pointer created by this runtime.
+     * Every reference must be released with releaseReference.
+     * @param address - The address of the pointer.
+     * @returns The new reference count.
+     * @throws If the pointer is not owned by this runtime.
+     */
+    public retainReference(address: string): number {
+        return this.#handle.retain_pointer(address);
+    }
+
+    /**
+     * Releases a reference to a pointer created by this runtime.
+     * When the last reference is released, the observers of the pointer are removed. The pointer is
+     * only removed from memory and from the pointer storage if it is not used by DATEX values
+     * (e.g. variables of an execution context) or remote subscribers.
+     * @param address - The address of the pointer.
+     * @param observerId - The observer registered for the reference with observePointerBindDirect, removed with it.
+     * @returns The remaining reference count.
+     * @throws If the pointer is not owned by this runtime.
+     */
+    public releaseReference(address: string, observerId: number | null = null): number {
+        return this.#handle.release_pointer(address, observerId);
+    }
+
+    /**
+     * Lists all pointers in memory.
+     * @param filter - Optional conditions that the listed pointers must match.
+     * @returns The descriptions of the matching pointers.
+     */
+    public listPointers(filter: PointerFilter = {}): PointerDescription[] {
+        return this.#handle.list_pointers(filter);
+    }
+
     /**
      * Updates the DIF value at the specified address.
      * @param address - The address of the DIF value to update.
@@ -184,12 +261,24 @@ export class DIFHandler {
         this.#handle.update(this.#transceiver_id, address, dif);
     }
 
+    /**
+     * Applies multiple updates atomically. If an update fails, no DIF value is changed
+     * and the error is thrown.
+     * Each observer is notified once, with an array of the updates of its pointer.
+     * @param updates - The addresses of the DIF values to update and their DIFUpdate objects.
+     * @throws If an update of the batch fails.
+     */
+    public updateReferences(updates: { address: string; update: DIFUpdateData }[]) {
+        this.#handle.update_batch(this.#transceiver_id, updates);
+    }
+
     /**
      * Registers an observer callback for changes to the DIF value at the specified address
      * directly on the DATEX core runtime.
      * This method should only be used internally, since it comes with additional overhead.
      * For normal use cases, use the observePointer method instead.
-     * The callback will be invoked whenever the value at the address is updated.
+     * The callback will be invoked whenever the value at the address is updated,
+     * with an array of updates for the updates of a batch.
      * @param address - The address of the DIF value to observe.
      * @param callback - The callback function to invoke on updates.
      * @returns An observer ID that can be used to unregister the observer.
@@ -197,7 +286,7 @@ export class DIFHandler {
      */
     public observePointerBindDirect(
         address: string,
-        callback: (value: DIFUpdate) => void,
+        callback: (value: DIFUpdate | DIFUpdate[]) => void,
         options: ObserveOptions = { relay_own_updates: false },
     ): number {
         return this.#runtime.dif().observe_pointer(
@@ -210,11 +299,13 @@ export class DIFHandler {
 
     /**
      * Updates the observe options for a registered observer.
+     * Updates that are pending because of the previous delivery options are delivered first.
      * @param address - The address of the DIF value being observed.
-     * @param observerId - The observer ID returned by the observePointer method.
+     * @param observerId - The observer ID returned by the observePointerBindDirect method.
      * @param options - The new observe options to apply.
+     * @throws If the observer is not registered.
      */
-    private updateObserverOptions(
+    public updateObserverOptions(
         address: string,
         observerId: number,
         options: ObserveOptions,
@@ -710,13 +801,20 @@ export class DIFHandler {
     /**
      * Initializes a reference with the given value and mutability, by
      * adding a proxy wrapper if necessary, and setting up observation and caching on the JS side.
+     * The wrapped value holds a reference to the pointer if it was created by this runtime,
+     * a new reference is added unless retain is false (the reference of the creator is taken over).
      */
     protected initReference<T>(
         pointerAddress: string,
         value: T,
         mutability: DIFSharedValueMutability,
         allowedType: DIFTypeDefinition | null = null,
+        retain = true,
     ): T | Ref<T> {
+        if (retain && this.#handle.owns_pointer(pointerAddress)) {
+            this.retainReference(pointerAddress);
+        }
+
         let wrappedValue = this.wrapJSValue(
             value,
             pointerAddress,
@@ -747,39 +845,42 @@ export class DIFHandler {
         if (mutability !== DIFSharedValueMutability.Immutable) {
             observerId = this.observePointerBindDirect(
                 pointerAddress,
-                (update) => {
-                    // if source_id is not own transceiver id, handle pointer update
-                    if (update.source_id !== this.#transceiver_id) {
-                        try {
-                            this.handlePointerUpdate(
-                                pointerAddress,
-                                wrappedValue,
-                                update.data,
-                                typeBinding,
-                            );
-                        } catch (e) {
-                            console.error(
-                                "Error handling pointer update",
-                                e,
-                            );
-                            throw e;
-                        }
-                    }
-                    // call all local observers
-                    const observers = this.#observers.get(pointerAddress);
-                    if (observers) {
-                        for (const cb of observers.values()) {
+                (updates) => {
+                    // the updates of a batch are delivered together
+                    for (const update of Array.isArray(updates) ? updates : [updates]) {
+                        // if source_id is not own transceiver id, handle pointer update
+                        if (update.source_id !== this.#transceiver_id) {
                             try {
-                                cb(update.data);
+                                this.handlePointerUpdate(
+                                    pointerAddress,
+                                    wrappedValue,
+                                    update.data,
+                                    typeBinding,
+                                );
                             } catch (e) {
                                 console.error(
-                                    "Error in pointer observer callback",
+                                    "Error handling pointer update",
                                     e,
                                 );
+                                throw e;
+                            }
+                        }
+                        // call all local observers
+                        const observers = this.#observers.get(pointerAddress);
+                        if (observers) {
+                            for (const cb of observers.values()) {
+                                try {
+                                    cb(update.data);
+                                } catch (e) {
+                                    console.error(
+                                        "Error in pointer observer callback",
+                                        e,
+                                    );
+                                }
                             }
                         }
+                        console.debug("Pointer update received", update);
                     }
-                    console.debug("Pointer update received", update);
                 },
             );
         }
@@ -886,7 +987,7 @@ export class DIFHandler {
             );
         }
 
-        // register finalizer to clean up the cache and free the reference in the runtime
+        // register finalizer to clean up the cache and release the reference in the runtime
         // when the object is garbage collected
         const finalizationRegistry = new FinalizationRegistry(
             (address: string) => {
@@ -898,8 +999,15 @@ export class DIFHandler {
                 this.#cache.delete(address);
                 // remove local observers
                 this.#observers.delete(address);
-                // if observer is active, unregister it
-                if (observerId !== null) {
+                // the runtime already released all observers when it was closed
+                if (this.#handle.closed) {
+                    return;
+                }
+                // release the reference held by the proxy together with its observer
+                // if the pointer was created by this runtime, otherwise only unregister the observer
+                if (this.#handle.owns_pointer(address)) {
+                    this.releaseReference(address, observerId);
+                } else if (observerId !== null) {
                     this.unobserveReferenceBindDirect(address, observerId);
                 }
             },
@@ -952,11 +1060,13 @@ export class DIFHandler {
                 ptrAddress,
             ) as DIFSharedValue).allowed_type;
         }
+        // the reference of the creator is held by the returned value
         return this.initReference(
             ptrAddress,
             value,
             mutability,
             allowedType,
+            false,
         ) as PointerOut<V, M>;
     }
 
@@ -6,6 +6,14 @@ export class Repl {
         this.#repl = new ReplInternal(runtime._runtime, verbose);
     }
 
+    /**
+     * Sets a wall time limit in milliseconds for every following execution
+     * (null removes the limit).
+     */
+    public setMaxTimeMs(maxTimeMs: number | null) {
+        this.#repl.set_execution_limits({ max_time_ms: maxTimeMs });
+    }
+
     public async execute(script: string): Promise<string> {
         const result = await this.#repl.execute(script);
         const jsValue = this.runtime.dif.resolveDIFValueContainerSync(result);
@@ -1,6 +1,6 @@
-import { create_runtime, type DecompileOptions, type JSRuntime } from "../datex.ts";
+import { create_runtime, type DecompileOptions, type JSCrypto, type JSRuntime } from "../datex.ts";
 import { ComHub } from "../network/com-hub.ts";
-import { DIFHandler, type PointerOut } from "../dif/dif-handler.ts";
+import { DIFHandler, type ExecuteOptions, type PointerOut } from "../dif/dif-handler.ts";
 import type { DIFSharedValueMutability, DIFTypeDefinition } from "../dif/definitions.ts";
 import type { Ref } from "../refs/ref.ts";
 import { unimplemented } from "../utils/exceptions.ts";
@@ -12,9 +12,32 @@ const VERSION: string = "0.0.14";
 /** debug flags for the runtime */
 interface DebugConfig {
     // optional log level for internal runtime logs, if not set, no logs are printed
-    log_level?: "error" | "warn" | "info" | "debug" | "trace" | null;
+    log_level?: "off" | "error" | "warn" | "info" | "debug" | "trace" | null;
+    // optional comma separated log filters for single modules,
+    // e.g. "datex_core::network=trace,datex_core::runtime=off"
+    log_filters?: string | null;
+    // optional callback that receives all log records of the runtime instead of the console
+    log_sink?: ((record: LogRecord) => void) | null;
+    // optional callback that is called if a panic occurs, the runtime can not be used afterwards
+    on_fatal_error?: ((error: FatalError) => void) | null;
 }
 
+/** log record passed to the log sink of a runtime */
+export type LogRecord = {
+    level: string;
+    target: string;
+    message: string;
+    timestamp: number;
+    endpoint: string | null;
+};
+
+/** fatal error passed to the on_fatal_error callback of a runtime */
+export type FatalError = {
+    message: string;
+    location: { file: string; line: number; column: number } | null;
+    endpoint: string;
+};
+
 /** configuration for the runtime  */
 export type RuntimeConfig = {
     endpoint?: string;
@@ -22,6 +45,103 @@ export type RuntimeConfig = {
     env?: Record<string, string>;
 };
 
+/**
+ * pointer storage implemented in JS (e.g. with IndexedDB or OPFS),
+ * pointers are stored as opaque bytes
+ */
+export type PointerStorage = {
+    get(address: string): Uint8Array | null | Promise<Uint8Array | null>;
+    put(address: string, data: Uint8Array): void | Promise<void>;
+    delete(address: string): void | Promise<void>;
+    list(): string[] | Promise<string[]>;
+};
+
+/** public keys (SPKI) of the identity of a runtime, null if not set */
+export type PublicKeys = {
+    /** Ed25519 key for signing blocks */
+    signing_key: Uint8Array | null;
+    /** X25519 key for deriving encryption keys */
+    encryption_key: Uint8Array | null;
+};
+
+/** storage for the encrypted identity of a runtime */
+export type IdentityStorage = {
+    load(endpoint: string): Uint8Array | null | Promise<Uint8Array | null>;
+    save(endpoint: string, data: Uint8Array): void | Promise<void>;
+};
+
+/**
+ * policy for the signature verification of received blocks:
+ * "off" (no verification), "verify" (report invalid signatures, drop
+ * expired and replayed blocks) or "require" (drop all blocks without a
+ * valid signature of a trusted key)
+ */
+export type VerificationPolicy = "off" | "verify" | "require";
+
+/**
+ * result of the signature verification of a block, received blocks with a valid
+ * signature are "expired" if their timestamp is outside of the accepted window
+ * and "replayed" if they were already received
+ */
+export type SignatureStatus = "unsigned" | "valid" | "invalid" | "unknown_key" | "expired" | "replayed";
+
+/** a received block that violates the verification policy */
+export type VerificationFailure = {
+    sender: string;
+    status: SignatureStatus;
+    /** uuid of the socket that received the block, if known */
+    socket: string | null;
+    /** true if the block was dropped */
+    dropped: boolean;
+};
+
+/** an unencrypted block that was dropped because the endpoint requires encryption */
+export type UnencryptedBlock = {
+    endpoint: string;
+    direction: "incoming" | "outgoing";
+    /** uuid of the socket that received the block, if known */
+    socket: string | null;
+};
+
+/** options for creating a named execution context */
+export type ExecutionContextOptions = {
+    /** unique name of the context, a name is generated if not set */
+    name?: string;
+    /** if true, all executed instructions are logged */
+    verbose?: boolean;
+};
+
+/** information about a named execution context */
+export type ExecutionContextInfo = {
+    name: string;
+    verbose: boolean;
+    /** number of successfully completed executions since creation or last reset */
+    execution_count: number;
+    /** number of failed executions since creation or last reset */
+    failure_count: number;
+    /** names of the variables declared in the context */
+    declared_variables: string[];
+    /** true if an execution is currently running in the context */
+    is_busy: boolean;
+};
+
+/** a compiler error or warning */
+export type CompileDiagnostic = {
+    severity: "error" | "warning";
+    code: string;
+    message: string;
+    /** byte range [start, end] in the script source, if known */
+    span: [number, number] | null;
+};
+
+/** the compiled DXB body and all compiler diagnostics */
+export type CompileResult = {
+    /** the compiled body, null if the script could not be compiled */
+    body: Uint8Array | null;
+    warnings: CompileDiagnostic[];
+    errors: CompileDiagnostic[];
+};
+
 /**
  * The main Runtime class for executing Datex scripts and managing communication interfaces.
  */
@@ -82,6 +202,30 @@ export class Runtime {
         return this.#comHub;
     }
 
+    /**
+     * Gets the crypto primitives used by the runtime.
+     */
+    get crypto(): JSCrypto {
+        return this.#runtime.crypto;
+    }
+
+    /**
+     * True if the runtime is poisoned by a panic and can not be used anymore.
+     * A panic aborts the whole wasm instance, so all runtimes of the
+     * instance are poisoned, not only the one that caused it.
+     * The module must be reloaded to create a working runtime.
+     */
+    get isPoisoned(): boolean {
+        return this.#runtime.is_poisoned();
+    }
+
+    /**
+     * Sets a callback that is called if a panic occurs, or null to remove it.
+     */
+    public setOnFatalError(callback: ((error: FatalError) => void) | null) {
+        this.#runtime.set_on_fatal_error(callback);
+    }
+
     /**
      * @internal only used for debugging
      */
@@ -105,6 +249,8 @@ export class Runtime {
             datexScript,
             this.#difHandler.convertToDIFValues(values),
             decompileOptions,
+            null,
+            null,
         );
     }
 
@@ -166,13 +312,328 @@ export class Runtime {
         return this.#executeInternal<T>(datexScript, valuesArray);
     }
 
+    /**
+     * Asynchronously executes a Datex script with additional execution options.
+     * Example usage:
+     * ```ts
+     * const controller = new AbortController();
+     * const result = runtime.executeWithOptions("1 + ?", [41], { signal: controller.signal });
+     * controller.abort(); // rejects the promise with an AbortError
+     * ```
+     */
+    public executeWithOptions<T = unknown>(
+        datexScript: string,
+        values: unknown[] | null,
+        options: ExecuteOptions,
+    ): Promise<T> {
+        return this.#executeInternal<T>(datexScript, values, options);
+    }
+
+    /**
+     * Executes a Datex script on a remote endpoint and returns the result.
+     * Rejects with an error with code `EndpointUnreachable` if the endpoint can not be reached,
+     * `RemoteTimeout` if no response is received within `options.timeout` milliseconds
+     * (default: 30 seconds) and `RemoteExecutionFailed` if the remote execution failed.
+     */
+    public async executeRemote<T = unknown>(
+        endpoint: string,
+        datexScript: string,
+        values: unknown[] | null = [],
+        options: { timeout?: number } = {},
+    ): Promise<T> {
+        const difValueContainer = await this.#runtime.execute_remote(
+            endpoint,
+            datexScript,
+            this.#difHandler.convertToDIFValues(values),
+            options,
+        );
+        if (difValueContainer === null) {
+            return undefined as T;
+        }
+        return this.#difHandler.resolveDIFValueContainer<T>(difValueContainer);
+    }
+
+    /**
+     * Compiles a Datex script to a DXB body without executing it.
+     * If values are given, they are inserted for the `?` placeholders.
+     * The compiled body can be executed with `executeDXB`.
+     */
+    public compile(datexScript: string, values?: unknown[]): CompileResult {
+        return this.#runtime.compile(
+            datexScript,
+            values ? { inserted_values: this.#difHandler.convertToDIFValues(values) } : null,
+        );
+    }
+
+    /**
+     * Asynchronously executes a precompiled DXB body (e.g. returned by `compile`).
+     * The values are bound to the placeholders of the body at execution time.
+     */
+    public async executeDXB<T = unknown>(
+        dxbBody: Uint8Array,
+        values: unknown[] | null = [],
+        options: ExecuteOptions = {},
+    ): Promise<T> {
+        const difValueContainer = await this.#difHandler.executeDXBDIF(
+            dxbBody,
+            values,
+            options,
+        );
+        if (difValueContainer === null) {
+            return undefined as T;
+        }
+        return this.#difHandler.resolveDIFValueContainer<T>(difValueContainer);
+    }
+
+    /**
+     * Creates a named execution context that keeps its state (e.g. declared variables)
+     * between executions. The returned name can be passed as `context` option to
+     * `executeWithOptions` and `executeDXB`.
+     */
+    public createContext(options: ExecutionContextOptions = {}): string {
+        return this.#runtime.create_context(options);
+    }
+
+    /**
+     * Gets the names of all execution contexts.
+     */
+    public listContexts(): string[] {
+        return this.#runtime.list_contexts();
+    }
+
+    /**
+     * Gets information about an execution context.
+     */
+    public inspectContext(name: string): ExecutionContextInfo {
+        return this.#runtime.inspect_context(name);
+    }
+
+    /**
+     * Resets an execution context, dropping its state.
+     */
+    public resetContext(name: string): void {
+        this.#runtime.reset_context(name);
+    }
+
+    /**
+     * Removes an execution context.
+     */
+    public disposeContext(name: string): void {
+        this.#runtime.dispose_context(name);
+    }
+
+    /**
+     * Sets the pointer storage of the runtime, or null to remove it.
+     * Pointers that are not in memory are loaded from the storage before
+     * they are requested from the network. Created and loaded pointers are
+     * written to the storage on every update and deleted from the storage
+     * when they are released.
+     */
+    public setPointerStorage(storage: PointerStorage | null): void {
+        this.#runtime.set_pointer_storage(storage);
+    }
+
+    /**
+     * Uses a pointer storage that keeps all stored pointers in memory.
+     */
+    public useInMemoryPointerStorage(): void {
+        this.#runtime.use_in_memory_pointer_storage();
+    }
+
+    /**
+     * Gets the addresses of all pointers in the pointer storage.
+     */
+    public listStoredPointers(): Promise<string[]> {
+        return this.#runtime.list_stored_pointers();
+    }
+
+    /**
+     * Serializes all local pointers in memory into a versioned binary snapshot,
+     * which can be restored with `restore`, e.g. after a page reload.
+     */
+    public snapshot(): Uint8Array {
+        return this.#runtime.snapshot();
+    }
+
+    /**
+     * Restores the pointers of a snapshot created with `snapshot` under their
+     * original addresses and returns the addresses.
+     * Throws an error with code `AddressInUse` without restoring any pointer
+     * if one of the addresses is already in memory.
+     * The restored pointers are owned by the runtime and released with
+     * `dif.releaseReference`.
+     */
+    public restore(snapshot: Uint8Array): string[] {
+        return this.#runtime.restore(snapshot);
+    }
+
+    /**
+     * Generates a new identity (signing and encryption keys) for the endpoint of the runtime.
+     */
+    public generateIdentity(): Promise<PublicKeys> {
+        return this.#runtime.generate_identity();
+    }
+
+    /**
+     * Replaces the signing keys, the encryption keys or both with newly generated keys.
+     */
+    public rotateKeys(kind: "signing" | "encryption" | null = null): Promise<PublicKeys> {
+        return this.#runtime.rotate_keys(kind);
+    }
+
+    /**
+     * Gets the public keys of the identity of the runtime.
+     */
+    get publicKeys(): PublicKeys {
+        return this.#runtime.public_keys();
+    }
+
+    /**
+     * Exports the identity of the runtime, encrypted with a key derived from the password.
+     * The key is derived with PBKDF2-HMAC-SHA256, the password must not be empty.
+     */
+    public exportIdentity(password: string): Promise<Uint8Array> {
+        return this.#runtime.export_identity(password);
+    }
+
+    /**
+     * Imports an identity exported with `exportIdentity` for the endpoint of the runtime.
+     * Fails if the password is wrong or the exported identity was modified.
+     */
+    public importIdentity(identity: Uint8Array, password: string): Promise<void> {
+        return this.#runtime.import_identity(identity, password);
+    }
+
+    /**
+     * Sets a storage that persists the identity of the runtime encrypted with the password
+     * (see `exportIdentity`), or null to remove it.
+     * Returns true if an identity was loaded from the storage.
+     */
+    public setIdentityStorage(storage: IdentityStorage | null, password?: string): Promise<boolean> {
+        return this.#runtime.set_identity_storage(storage, password);
+    }
+
+    /**
+     * Adds the Ed25519 public key (SPKI) of an endpoint that is trusted to
+     * sign blocks and encryption key announcements sent by the endpoint.
+     */
+    public addTrustedKey(endpoint: string, publicKey: Uint8Array): void {
+        this.#runtime.add_trusted_key(endpoint, publicKey);
+    }
+
+    /**
+     * Removes the trusted public key of an endpoint.
+     */
+    public removeTrustedKey(endpoint: string): void {
+        this.#runtime.remove_trusted_key(endpoint);
+    }
+
+    /**
+     * Sets the signature verification policy for received blocks.
+     * Blocks that violate the policy are passed to the optional callback.
+     * Blocks sent by this runtime are signed automatically if the runtime
+     * has a signing key.
+     */
+    public setVerificationPolicy(
+        policy: VerificationPolicy,
+        onVerificationFailure?: (failure: VerificationFailure) => void,
+    ): void {
+        this.#runtime.set_verification_policy(policy, onVerificationFailure);
+    }
+
+    /**
+     * Signs a serialized block with the signing key of the runtime.
+     */
+    public signBlock(block: Uint8Array): Promise<Uint8Array> {
+        return this.#runtime.sign_block(block);
+    }
+
+    /**
+     * Verifies the signature of a serialized block against the trusted key of its sender.
+     */
+    public async verifyBlock(block: Uint8Array): Promise<SignatureStatus> {
+        return await this.#runtime.verify_block(block) as SignatureStatus;
+    }
+
+    /**
+     * Adds the X25519 public key (SPKI) of an endpoint, used to encrypt blocks exchanged with the endpoint.
+     * Keys are also exchanged automatically on first contact with endpoints that have a trusted key.
+     */
+    public addPeerEncryptionKey(endpoint: string, publicKey: Uint8Array): void {
+        this.#runtime.add_peer_encryption_key(endpoint, publicKey);
+    }
+
+    /**
+     * Removes the public encryption key of an endpoint, a new key is exchanged on the next contact.
+     */
+    public removePeerEncryptionKey(endpoint: string): void {
+        this.#runtime.remove_peer_encryption_key(endpoint);
+    }
+
+    /**
+     * Gets the public encryption keys of all endpoints that blocks can be encrypted for.
+     */
+    get peerEncryptionKeys(): Record<string, Uint8Array> {
+        return this.#runtime.peer_encryption_keys();
+    }
+
+    /**
+     * Enables the end-to-end encryption of all requests and responses sent to a single endpoint.
+     * Until the key of an endpoint was exchanged, blocks are sent to it unencrypted.
+     */
+    public setEncryptionEnabled(enabled: boolean): void {
+        this.#runtime.set_encryption_enabled(enabled);
+    }
+
+    /**
+     * Requires end-to-end encryption for all requests and responses exchanged with an endpoint.
+     * Unencrypted blocks sent to or received from the endpoint are dropped and passed to the
+     * callback set with `setOnUnencryptedBlock`.
+     */
+    public requireEncryption(endpoint: string, required = true): void {
+        this.#runtime.require_encryption(endpoint, required);
+    }
+
+    /**
+     * Sets a callback for unencrypted blocks that were dropped because the endpoint requires encryption.
+     */
+    public setOnUnencryptedBlock(callback: ((block: UnencryptedBlock) => void) | null): void {
+        this.#runtime.set_on_unencrypted_block(callback);
+    }
+
+    /**
+     * Encrypts the body of a serialized block for its receiver.
+     */
+    public encryptBlock(block: Uint8Array): Promise<Uint8Array> {
+        return this.#runtime.encrypt_block(block);
+    }
+
+    /**
+     * Decrypts the body of a serialized block that was encrypted by its sender.
+     */
+    public decryptBlock(block: Uint8Array): Promise<Uint8Array> {
+        return this.#runtime.decrypt_block(block);
+    }
+
+    /**
+     * Closes the runtime: removes all interfaces, observers and registered
+     * callbacks and stops the runtime loop. All interfaces are removed even
+     * if removing one of them fails, the error is thrown afterwards.
+     * The runtime can not be used anymore after it was closed.
+     */
+    public close(): Promise<void> {
+        return this.#runtime.close();
+    }
+
     async #executeInternal<T = unknown>(
         datexScript: string,
         values: unknown[] | null = [],
+        options: ExecuteOptions = {},
     ): Promise<T> {
         const difValueContainer = await this.#difHandler.executeDIF(
             datexScript,
             values,
+            options,
         );
         if (difValueContainer === null) {
             return undefined as T;
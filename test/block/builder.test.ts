@@ -0,0 +1,44 @@
+import { assertEquals, assertNotEquals, assertThrows } from "@std/assert";
+import { BlockBuilder } from "../../src/datex.ts";
+
+function assertDatexError(fn: () => unknown, code: string) {
+    const error = assertThrows(fn) as { name: string; code: string };
+    assertEquals(error.name, "DatexError");
+    assertEquals(error.code, code);
+}
+
+Deno.test("block builder rejects invalid timestamps", () => {
+    const builder = new BlockBuilder();
+    builder.set_timestamp(Date.now());
+    for (const timestamp of [-1, 1.5, NaN, Infinity, 2 ** 53]) {
+        assertDatexError(
+            () => builder.set_timestamp(timestamp),
+            "InvalidTimestamp",
+        );
+    }
+});
+
+Deno.test("block builder sets routing header flags", () => {
+    const builder = new BlockBuilder();
+    builder.set_sender("@jonas");
+    const plain = builder.build();
+
+    builder.set_bounce_back(true);
+    builder.set_encryption_type("encrypted");
+    builder.set_signature_type("unencrypted");
+    assertNotEquals(builder.build(), plain);
+
+    builder.set_bounce_back(false);
+    builder.set_encryption_type("none");
+    builder.set_signature_type("none");
+    assertEquals(builder.build(), plain);
+
+    assertDatexError(
+        () => builder.set_encryption_type("aes"),
+        "InvalidEncryptionType",
+    );
+    assertDatexError(
+        () => builder.set_signature_type("rsa"),
+        "InvalidSignatureType",
+    );
+});
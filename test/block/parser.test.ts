@@ -0,0 +1,33 @@
+import { assertEquals } from "@std/assert";
+import { BlockBuilder, parse_block } from "../../src/datex.ts";
+
+const BLOCK_TYPES = ["request", "response", "hello", "trace", "trace_back"];
+
+Deno.test("parsed block types round-trip through the block builder", () => {
+    for (const blockType of BLOCK_TYPES) {
+        const builder = new BlockBuilder();
+        builder.set_block_type(blockType);
+        builder.set_encryption_type("encrypted");
+        builder.set_signature_type("encrypted");
+        const block = parse_block(builder.build(), null);
+        assertEquals(block.block_header.block_type, blockType);
+        assertEquals(block.routing_header.signature_type, "encrypted");
+        assertEquals(block.routing_header.encryption_type, "encrypted");
+
+        const rebuilt = new BlockBuilder();
+        rebuilt.set_block_type(block.block_header.block_type);
+        rebuilt.set_encryption_type(block.routing_header.encryption_type);
+        rebuilt.set_signature_type(block.routing_header.signature_type);
+        assertEquals(rebuilt.build(), builder.build());
+    }
+});
+
+Deno.test("invalid block bodies are reported as decompiler errors", () => {
+    const builder = new BlockBuilder();
+    builder.set_body(new Uint8Array([0xff, 0xff, 0xff]));
+    const block = parse_block(builder.build(), { decompile: true });
+    assertEquals(block.decompiled_body, null);
+    assertEquals(block.decompiler_error.name, "DatexError");
+    assertEquals(block.decompiler_error.kind, "decompiler");
+    assertEquals(block.decompiler_error.code, "InvalidDXB");
+});
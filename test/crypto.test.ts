@@ -1,3 +1,4 @@
+import { assert, assertEquals, assertNotEquals } from "@std/assert";
 import { Runtime } from "../src/runtime/runtime.ts";
 import { isNodeOrBun } from "./is-node.ts";
 
@@ -10,9 +11,81 @@ Deno.test("crypto", async () => {
         return;
     }
     const runtime = await Runtime.create({ endpoint: "@jonas" });
-    console.log(
-        await runtime._runtime.crypto_test_tmp(),
+    const crypto = runtime.crypto;
+
+    // base58
+    const something = new TextEncoder().encode(
+        "yellow submarineyellow submarine",
+    );
+    const encoded = "9At2nzU19GjL8F4WFRyB7RZSGLemMGUMVBZAMChfndF2";
+    assertEquals(crypto.encode_base58(something), encoded);
+    assertEquals(crypto.decode_base58_32(encoded), something);
+
+    // hashes
+    const ikm = new Uint8Array(32);
+    assertEquals(
+        await crypto.hash_sha256(ikm),
+        new Uint8Array([
+            102, 104, 122, 173, 248, 98, 189, 119, 108, 143, 193, 139, 142,
+            159, 142, 32, 8, 151, 20, 133, 110, 226, 51, 179, 144, 42, 89, 29,
+            13, 95, 41, 37,
+        ]),
+    );
+    const salt = new Uint8Array(16);
+    const hashA = await crypto.hkdf_sha256(ikm, salt);
+    const otherIkm = new Uint8Array(32);
+    otherIkm[0] = 1;
+    const hashB = await crypto.hkdf_sha256(otherIkm, salt);
+    assertNotEquals(hashA, hashB);
+    assertEquals(
+        hashA,
+        new Uint8Array([
+            223, 114, 4, 84, 111, 27, 238, 120, 184, 83, 36, 167, 137, 140,
+            161, 25, 179, 135, 224, 19, 134, 209, 174, 240, 55, 120, 29, 74,
+            138, 3, 106, 238,
+        ]),
+    );
+
+    // ed25519
+    const data = new TextEncoder().encode("Some message to  sign");
+    const otherData = new TextEncoder().encode("Some message to sign");
+    const keys = await crypto.generate_ed25519();
+    assertEquals(keys.public_key.length, 44);
+    assertEquals(keys.private_key.length, 48);
+    const signature = await crypto.sign_ed25519(keys.private_key, data);
+    assertEquals(signature.length, 64);
+    assert(await crypto.verify_ed25519(keys.public_key, signature, data));
+    assert(
+        !(await crypto.verify_ed25519(keys.public_key, signature, otherData)),
     );
-    // .then(console.log)
-    // .catch(console.error);
+    const otherKeys = await crypto.generate_ed25519();
+    assert(
+        !(await crypto.verify_ed25519(otherKeys.public_key, signature, data)),
+    );
+
+    // x25519
+    const server = await crypto.generate_x25519();
+    const client = await crypto.generate_x25519();
+    const clientSecret = await crypto.derive_x25519(
+        client.private_key,
+        server.public_key,
+    );
+    const serverSecret = await crypto.derive_x25519(
+        server.private_key,
+        client.public_key,
+    );
+    assertEquals(clientSecret, serverSecret);
+    assertEquals(clientSecret.length, 32);
+
+    // aes ctr
+    const key = crypto.random_bytes(32);
+    const iv = new Uint8Array(16);
+    const message = new TextEncoder().encode("Some message");
+    const ciphered = await crypto.aes_ctr_encrypt(key, iv, message);
+    assertNotEquals(ciphered, message);
+    assertEquals(await crypto.aes_ctr_decrypt(key, iv, ciphered), message);
+
+    // key wrapping
+    const wrapped = await crypto.key_wrap_rfc3394(key, key);
+    assertEquals(await crypto.key_unwrap_rfc3394(key, wrapped), key);
 });
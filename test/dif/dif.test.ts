@@ -28,7 +28,7 @@ Deno.test("pointer create with observe", () => {
     );
     assertEquals(typeof ref, "string");
 
-    let observed: DIFUpdate | null = null;
+    let observed: DIFUpdate | DIFUpdate[] | null = null;
     const observerId = runtime.dif.observePointerBindDirect(ref, (value) => {
         runtime.executeSync('"xy"');
         runtime.dif.unobserveReferenceBindDirect(ref, observerId);
@@ -62,7 +62,7 @@ Deno.test("pointer create without observe", () => {
     );
     assertEquals(typeof ref, "string");
 
-    let observed: DIFUpdate | null = null;
+    let observed: DIFUpdate | DIFUpdate[] | null = null;
     const observerId = runtime.dif.observePointerBindDirect(ref, (value) => {
         runtime.executeSync("'xy'");
         runtime.dif.unobserveReferenceBindDirect(ref, observerId);
@@ -693,3 +693,59 @@ Deno.test("core integer variants", () => {
         type: CoreTypeAddress.integer_u8,
     });
 });
+
+Deno.test("pointer batch update", () => {
+    const ref = runtime.dif.createSharedValueFromDIFValue(
+        { value: "a" },
+        undefined,
+        DIFSharedValueMutability.Mutable,
+    );
+    const observed: (DIFUpdate | DIFUpdate[])[] = [];
+    const observerId = runtime.dif.observePointerBindDirect(ref, (value) => {
+        observed.push(value);
+    }, { relay_own_updates: true });
+
+    runtime.dif.updateReferences([
+        { address: ref, update: { value: { value: "b" }, kind: DIFUpdateKind.Replace } },
+        { address: ref, update: { value: { value: "c" }, kind: DIFUpdateKind.Replace } },
+    ]);
+    runtime.dif.unobserveReferenceBindDirect(ref, observerId);
+
+    // the observer is called once with all updates of the batch
+    assertEquals(observed.length, 1);
+    assertEquals((observed[0] as DIFUpdate[]).map((update) => update.data), [
+        { value: { value: "b" }, kind: DIFUpdateKind.Replace },
+        { value: { value: "c" }, kind: DIFUpdateKind.Replace },
+    ]);
+    assertEquals(runtime.executeSyncWithStringResult("'$" + ref), 'shared mut "c"');
+});
+
+Deno.test("pointer batch update rollback", () => {
+    const ref = runtime.dif.createSharedValueFromDIFValue(
+        { value: "a" },
+        undefined,
+        DIFSharedValueMutability.Mutable,
+    );
+    const immutableRef = runtime.dif.createSharedValueFromDIFValue(
+        { value: "x" },
+        undefined,
+        DIFSharedValueMutability.Immutable,
+    );
+    let observed: DIFUpdate | DIFUpdate[] | null = null;
+    const observerId = runtime.dif.observePointerBindDirect(ref, (value) => {
+        observed = value;
+    }, { relay_own_updates: true });
+
+    // the second update fails, so the first update is not applied
+    assertThrows(() =>
+        runtime.dif.updateReferences([
+            { address: ref, update: { value: { value: "b" }, kind: DIFUpdateKind.Replace } },
+            { address: immutableRef, update: { value: { value: "y" }, kind: DIFUpdateKind.Replace } },
+        ])
+    );
+    runtime.dif.unobserveReferenceBindDirect(ref, observerId);
+
+    assertEquals(observed, null);
+    assertEquals(runtime.executeSyncWithStringResult("'$" + ref), 'shared mut "a"');
+    assertEquals(runtime.executeSyncWithStringResult("'$" + immutableRef), 'shared "x"');
+});
@@ -0,0 +1,87 @@
+import { Runtime } from "../../src/runtime/runtime.ts";
+import { assertEquals, assertThrows } from "@std/assert";
+import {
+    DIFSharedValueMutability,
+    type DIFUpdate,
+    type DIFUpdateData,
+    DIFUpdateKind,
+    type ObserveOptions,
+} from "../../src/dif/definitions.ts";
+import { sleep } from "../utils.ts";
+
+const runtime = await Runtime.create({ endpoint: "@jonas" });
+
+function replace(value: string): DIFUpdateData {
+    return { value: { value }, kind: DIFUpdateKind.Replace };
+}
+
+/**
+ * Creates a text pointer and observes it with the given options,
+ * the delivered notifications are collected in the returned array
+ */
+function observeText(options: Omit<ObserveOptions, "relay_own_updates">) {
+    const ref = runtime.dif.createSharedValueFromDIFValue(
+        { value: "a" },
+        undefined,
+        DIFSharedValueMutability.Mutable,
+    );
+    const observed: (DIFUpdate | DIFUpdate[])[] = [];
+    const observerId = runtime.dif.observePointerBindDirect(ref, (value) => {
+        observed.push(value);
+    }, { relay_own_updates: true, ...options });
+    return { ref, observerId, observed };
+}
+
+Deno.test("throttled observer", async () => {
+    const { ref, observerId, observed } = observeText({ throttle_ms: 50 });
+
+    // the first update is delivered immediately, the following
+    // updates are delivered together after the interval
+    runtime.dif.updateReference(ref, replace("b"));
+    runtime.dif.updateReference(ref, replace("c"));
+    runtime.dif.updateReference(ref, replace("d"));
+    assertEquals(observed.length, 1);
+    assertEquals((observed[0] as DIFUpdate).data, replace("b"));
+
+    await sleep(100);
+    assertEquals(observed.length, 2);
+    assertEquals((observed[1] as DIFUpdate[]).map((update) => update.data), [replace("c"), replace("d")]);
+    runtime.dif.unobserveReferenceBindDirect(ref, observerId);
+});
+
+Deno.test("debounced observer", async () => {
+    const { ref, observerId, observed } = observeText({ debounce_ms: 50 });
+
+    runtime.dif.updateReference(ref, replace("b"));
+    await sleep(20);
+    runtime.dif.updateReference(ref, replace("c"));
+    assertEquals(observed.length, 0);
+
+    await sleep(100);
+    assertEquals(observed.length, 1);
+    assertEquals((observed[0] as DIFUpdate[]).map((update) => update.data), [replace("b"), replace("c")]);
+    runtime.dif.unobserveReferenceBindDirect(ref, observerId);
+});
+
+Deno.test("latest only observer", async () => {
+    const { ref, observerId, observed } = observeText({ debounce_ms: 20, latest_only: true });
+
+    runtime.dif.updateReference(ref, replace("b"));
+    runtime.dif.updateReference(ref, replace("c"));
+
+    // a single replace update with the current value is delivered
+    await sleep(50);
+    assertEquals(observed.length, 1);
+    assertEquals((observed[0] as DIFUpdate).data, replace("c"));
+    runtime.dif.unobserveReferenceBindDirect(ref, observerId);
+});
+
+Deno.test("update options of unknown observer", () => {
+    const { ref, observerId } = observeText({});
+    runtime.dif.unobserveReferenceBindDirect(ref, observerId);
+
+    const error = assertThrows(() =>
+        runtime.dif.updateObserverOptions(ref, observerId, { relay_own_updates: false })
+    ) as { code: string };
+    assertEquals(error.code, "ObserverNotFound");
+});
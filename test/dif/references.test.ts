@@ -0,0 +1,83 @@
+import { Runtime } from "../../src/runtime/runtime.ts";
+import { assert, assertEquals, assertThrows } from "@std/assert";
+import { DIFSharedValueMutability, type DIFUpdate, DIFUpdateKind } from "../../src/dif/definitions.ts";
+
+const runtime = await Runtime.create({ endpoint: "@jonas" });
+
+function createText(value: string) {
+    return runtime.dif.createSharedValueFromDIFValue(
+        { value },
+        undefined,
+        DIFSharedValueMutability.Mutable,
+    );
+}
+
+Deno.test("releasing a reference only removes its observer", () => {
+    const address = createText("a");
+    assertEquals(runtime.dif.retainReference(address), 2);
+
+    const observedA: (DIFUpdate | DIFUpdate[])[] = [];
+    const observedB: (DIFUpdate | DIFUpdate[])[] = [];
+    const observerA = runtime.dif.observePointerBindDirect(address, (value) => {
+        observedA.push(value);
+    }, { relay_own_updates: true });
+    runtime.dif.observePointerBindDirect(address, (value) => {
+        observedB.push(value);
+    }, { relay_own_updates: true });
+
+    // the observer of the released reference is removed, the other observer is kept
+    assertEquals(runtime.dif.releaseReference(address, observerA), 1);
+    runtime.dif.updateReference(address, { value: { value: "b" }, kind: DIFUpdateKind.Replace });
+    assertEquals(observedA.length, 0);
+    assertEquals(observedB.length, 1);
+});
+
+Deno.test("releasing the last reference removes the pointer from memory", () => {
+    const address = createText("a");
+    assert(runtime.dif._handle.owns_pointer(address));
+
+    assertEquals(runtime.dif.releaseReference(address), 0);
+    assert(!runtime.dif._handle.owns_pointer(address));
+    assertThrows(() => runtime.dif._handle.resolve_pointer_address_sync(address));
+});
+
+Deno.test("pointers used by an execution context survive the release of the last reference", async () => {
+    const address = createText("a");
+    const context = runtime.createContext();
+    await runtime.executeWithOptions(`const x = $${address}`, [], { context });
+
+    assertEquals(runtime.dif.releaseReference(address), 0);
+    assert(!runtime.dif._handle.owns_pointer(address));
+    assertEquals(runtime.executeSyncWithStringResult(`'$${address}`), 'shared mut "a"');
+    runtime.disposeContext(context);
+});
+
+Deno.test("resolved values hold their own reference", () => {
+    const address = createText("a");
+    runtime.dif.resolvePointerAddressSync(address);
+
+    // the reference of the creator and the reference of the resolved value
+    assertEquals(runtime.dif.retainReference(address), 3);
+});
+
+Deno.test("list pointers", () => {
+    const address = createText("a");
+    const observerId = runtime.dif.observePointerBindDirect(address, () => {}, { relay_own_updates: true });
+
+    const [description] = runtime.dif.listPointers({ address_prefix: address, with_size: true });
+    assertEquals(description.address, address);
+    assertEquals(description.owner, "@jonas");
+    assertEquals(description.mutability, "Mutable");
+    assertEquals(description.ref_count, 1);
+    assertEquals(description.observer_count, 1);
+    assertEquals(description.subscribers, []);
+    assert(description.size! > 0);
+    assertEquals(description.error, null);
+
+    // the size is only computed if requested
+    assertEquals(runtime.dif.listPointers({ address_prefix: address })[0].size, null);
+    assertEquals(runtime.dif.listPointers({ address_prefix: address, observed: false }), []);
+
+    runtime.dif.unobserveReferenceBindDirect(address, observerId);
+    runtime.dif.releaseReference(address);
+});
@@ -0,0 +1,117 @@
+import { assert, assertEquals, assertThrows } from "@std/assert";
+import { type PointerStorage, Runtime } from "../../src/runtime/runtime.ts";
+import { DIFSharedValueMutability, DIFUpdateKind } from "../../src/dif/definitions.ts";
+
+/**
+ * Pointer storage backed by a JS map, shared between runtimes
+ */
+function createMapStorage(): PointerStorage & { data: Map<string, Uint8Array> } {
+    const data = new Map<string, Uint8Array>();
+    return {
+        data,
+        get: (address) => data.get(address) ?? null,
+        put: (address, bytes) => {
+            data.set(address, bytes);
+        },
+        delete: (address) => {
+            data.delete(address);
+        },
+        list: () => [...data.keys()],
+    };
+}
+
+/**
+ * Waits until spawned write-through tasks have finished
+ */
+function flushWrites() {
+    return new Promise((resolve) => setTimeout(resolve, 0));
+}
+
+Deno.test("created pointers are written through to the storage", async () => {
+    const runtime = await Runtime.create({ endpoint: "@jonas" });
+    const storage = createMapStorage();
+    runtime.setPointerStorage(storage);
+
+    const address = runtime.dif.createSharedValueFromDIFValue(
+        { value: "Hello" },
+        undefined,
+        DIFSharedValueMutability.Mutable,
+    );
+    await flushWrites();
+    assertEquals(await runtime.listStoredPointers(), [address]);
+});
+
+Deno.test("script mutations are written through to the storage", async () => {
+    const runtimeA = await Runtime.create({ endpoint: "@jonas" });
+    const storage = createMapStorage();
+    runtimeA.setPointerStorage(storage);
+
+    const address = runtimeA.dif.createSharedValueFromDIFValue(
+        { value: "Hello" },
+        undefined,
+        DIFSharedValueMutability.Mutable,
+    );
+    await flushWrites();
+    const stored = storage.data.get(address);
+    assert(stored);
+
+    runtimeA.executeSync(`*$${address} = "World"`);
+    await flushWrites();
+    assert(storage.data.get(address) !== stored);
+
+    // a second runtime loads the stored state into its memory
+    const runtimeB = await Runtime.create({ endpoint: "@ben" });
+    runtimeB.setPointerStorage(storage);
+    await runtimeB.dif.resolvePointerAddress(address);
+    assertEquals(
+        runtimeB.executeSyncWithStringResult(`'$${address}`),
+        runtimeA.executeSyncWithStringResult(`'$${address}`),
+    );
+});
+
+Deno.test("released pointers are deleted from the storage", async () => {
+    const runtime = await Runtime.create({ endpoint: "@jonas" });
+    runtime.useInMemoryPointerStorage();
+
+    const address = runtime.dif.createSharedValueFromDIFValue(
+        { value: 42 },
+        undefined,
+        DIFSharedValueMutability.Mutable,
+    );
+    await flushWrites();
+    assertEquals(await runtime.listStoredPointers(), [address]);
+
+    runtime.dif.releaseReference(address);
+    await flushWrites();
+    assertEquals(await runtime.listStoredPointers(), []);
+});
+
+Deno.test("batch updates do not list or store temporary pointer copies", async () => {
+    const runtime = await Runtime.create({ endpoint: "@jonas" });
+    runtime.useInMemoryPointerStorage();
+    const address = runtime.dif.createSharedValueFromDIFValue(
+        { value: "a" },
+        undefined,
+        DIFSharedValueMutability.Mutable,
+    );
+    const immutableAddress = runtime.dif.createSharedValueFromDIFValue(
+        { value: "x" },
+        undefined,
+        DIFSharedValueMutability.Immutable,
+    );
+    const listed = runtime.dif.listPointers().map((pointer) => pointer.address);
+
+    runtime.dif.updateReferences([
+        { address, update: { value: { value: "b" }, kind: DIFUpdateKind.Replace } },
+    ]);
+    assertThrows(() =>
+        runtime.dif.updateReferences([
+            { address, update: { value: { value: "c" }, kind: DIFUpdateKind.Replace } },
+            { address: immutableAddress, update: { value: { value: "y" }, kind: DIFUpdateKind.Replace } },
+        ])
+    );
+    await flushWrites();
+
+    assertEquals(runtime.dif.listPointers().map((pointer) => pointer.address), listed);
+    assertEquals((await runtime.listStoredPointers()).sort(), [address, immutableAddress].sort());
+});
@@ -0,0 +1,30 @@
+import { Runtime } from "datex/runtime/runtime.ts";
+import { websocketServerDenoComInterfaceFactory } from "datex/network/interfaces/websocket-server-deno.ts";
+import { sleep } from "../utils.ts";
+
+/**
+ * Creates a runtime (@test_a) with a websocket server on the given port and
+ * a runtime (@test_b) connected to it. The runtimes can be configured before
+ * the interfaces are created.
+ */
+export async function connectRuntimes(
+    port: number,
+    configure: (server: Runtime, client: Runtime) => Promise<void>,
+) {
+    const server = await Runtime.create({ endpoint: "@test_a" });
+    const client = await Runtime.create({ endpoint: "@test_b" });
+    await configure(server, client);
+    server.comHub.registerInterfaceFactory(
+        websocketServerDenoComInterfaceFactory,
+    );
+    await server.comHub.createInterface(
+        "websocket-server",
+        { bind_address: `0.0.0.0:${port}` },
+    );
+    await client.comHub.createInterface(
+        "websocket-client",
+        { url: `ws://localhost:${port}` },
+    );
+    await sleep(100);
+    return { server, client };
+}
@@ -0,0 +1,32 @@
+import { assert, assertRejects } from "@std/assert";
+import { Runtime } from "../../src/runtime/runtime.ts";
+import { nextTick } from "../utils.ts";
+
+function isAbortError(error: unknown): boolean {
+    return error instanceof DOMException && error.name === "AbortError";
+}
+
+Deno.test("abort running execution", async () => {
+    const runtime = await Runtime.create({ endpoint: "@jonas" });
+    const controller = new AbortController();
+    // the remote execution waits for a response that never arrives
+    const execution = runtime.executeWithOptions("@unreachable :: 1 + 2", [], {
+        signal: controller.signal,
+    });
+    await nextTick();
+    controller.abort();
+    const error = await assertRejects(() => execution);
+    assert(isAbortError(error), `expected AbortError, got ${error}`);
+});
+
+Deno.test("abort execution with already aborted signal", async () => {
+    const runtime = await Runtime.create({ endpoint: "@jonas" });
+    const controller = new AbortController();
+    controller.abort();
+    const error = await assertRejects(() =>
+        runtime.executeWithOptions("1 + 2", [], {
+            signal: controller.signal,
+        })
+    );
+    assert(isAbortError(error), `expected AbortError, got ${error}`);
+});
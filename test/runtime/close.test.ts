@@ -0,0 +1,64 @@
+import { assertEquals, assertRejects, assertThrows } from "@std/assert";
+import { Runtime } from "../../src/runtime/runtime.ts";
+
+function assertClosedError(error: unknown) {
+    assertEquals((error as { code: string }).code, "RuntimeClosed");
+}
+
+Deno.test("closed runtime can not be used anymore", async () => {
+    const runtime = await Runtime.create({ endpoint: "@jonas" });
+    assertEquals(await runtime.execute<number>("1 + 2"), 3);
+    await runtime.close();
+
+    const error = await assertRejects(() => runtime.execute("1 + 2"));
+    assertClosedError(error);
+    assertThrows(() => runtime.executeSync("1 + 2"));
+    assertThrows(() => runtime.createContext());
+});
+
+Deno.test("all entry points fail after close", async () => {
+    const runtime = await Runtime.create({ endpoint: "@jonas" });
+    const address = runtime.dif._handle.create_pointer(1, null, 0);
+    const context = runtime._runtime.create_context(null);
+    await runtime.close();
+
+    const jsRuntime = runtime._runtime;
+    const handle = runtime.dif._handle;
+    assertEquals(handle.closed, true);
+
+    for (
+        const call of [
+            () => jsRuntime.list_contexts(),
+            () => jsRuntime.dispose_context(context),
+            () => jsRuntime.add_trusted_key("@other", new Uint8Array(44)),
+            () => jsRuntime.set_verification_policy("verify"),
+            () => jsRuntime.set_encryption_enabled(true),
+            () => jsRuntime.require_encryption("@other", true),
+            () => jsRuntime.public_keys(),
+            () => handle.unobserve_pointer(address, 0),
+            () => handle.release_pointer(address),
+            () => handle.owns_pointer(address),
+            () =>
+                jsRuntime.com_hub.register_incoming_block_interceptor(() => {}),
+        ]
+    ) {
+        assertClosedError(assertThrows(call));
+    }
+
+    for (
+        const call of [
+            () => jsRuntime.generate_identity(),
+            () => jsRuntime.rotate_keys(null),
+            () => jsRuntime.import_identity(new Uint8Array(), "password"),
+            () => jsRuntime.set_identity_storage(null, null),
+        ]
+    ) {
+        assertClosedError(await assertRejects(call));
+    }
+});
+
+Deno.test("closing a runtime twice has no effect", async () => {
+    const runtime = await Runtime.create({ endpoint: "@jonas" });
+    await runtime.close();
+    await runtime.close();
+});
@@ -0,0 +1,21 @@
+import { assertEquals } from "@std/assert";
+import { Runtime } from "../../src/runtime/runtime.ts";
+import { compile } from "../../src/datex.ts";
+
+Deno.test("compile with and without a runtime", async () => {
+    const runtime = await Runtime.create({ endpoint: "@jonas" });
+
+    // the free function and the runtime method take the same options
+    const withRuntime = runtime.compile("? + 1", [1]);
+    const withoutRuntime = compile("? + 1", { inserted_values: runtime.dif.convertToDIFValues([1]) });
+    assertEquals(withRuntime.errors, []);
+    assertEquals(withoutRuntime.errors, []);
+    assertEquals(withRuntime.body, withoutRuntime.body);
+});
+
+Deno.test("compile errors have specific codes", () => {
+    const { body, errors } = compile("x + 1", null);
+    assertEquals(body, null);
+    assertEquals(errors.length, 1);
+    assertEquals(errors[0].code, "UndeclaredVariable");
+});
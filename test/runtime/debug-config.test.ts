@@ -0,0 +1,29 @@
+import { assertEquals, assertRejects } from "@std/assert";
+import { Runtime } from "../../src/runtime/runtime.ts";
+
+Deno.test("malformed debug config is rejected", async () => {
+    const invalidConfigs = [
+        { log_level: "verbose" },
+        { log_level: 42 },
+        { log_filters: "datex_core::network=loud" },
+        { log_sink: "console" },
+        { loglevel: "debug" },
+    ];
+    for (const debugConfig of invalidConfigs) {
+        await assertRejects(() =>
+            Runtime.create(
+                { endpoint: "@jonas" },
+                // deno-lint-ignore no-explicit-any
+                debugConfig as any,
+            )
+        );
+    }
+});
+
+Deno.test("valid debug config is applied", async () => {
+    const runtime = await Runtime.create({ endpoint: "@jonas" }, {
+        log_level: "off",
+        log_filters: "datex_core::network=warn",
+    });
+    assertEquals(await runtime.execute<number>("1 + 2"), 3);
+});
@@ -0,0 +1,65 @@
+import { assertEquals, assertRejects } from "@std/assert";
+import { Runtime } from "../../src/runtime/runtime.ts";
+
+Deno.test("execution contexts keep state", async () => {
+    const runtime = await Runtime.create({ endpoint: "@jonas" });
+    const context = runtime.createContext({ name: "a" });
+    assertEquals(context, "a");
+
+    await runtime.executeWithOptions("const x = 41", [], { context });
+    const result = await runtime.executeWithOptions<number>("x + 1", [], {
+        context,
+    });
+    assertEquals(result, 42);
+
+    const info = runtime.inspectContext(context);
+    assertEquals(info.execution_count, 2);
+    assertEquals(info.failure_count, 0);
+    assertEquals(info.declared_variables, ["x"]);
+    assertEquals(info.is_busy, false);
+});
+
+Deno.test("execution contexts are isolated", async () => {
+    const runtime = await Runtime.create({ endpoint: "@jonas" });
+    const a = runtime.createContext();
+    const b = runtime.createContext();
+    assertEquals(runtime.listContexts(), [a, b].sort());
+
+    await runtime.executeWithOptions("const x = 1", [], { context: a });
+    await assertRejects(() =>
+        runtime.executeWithOptions("x", [], { context: b })
+    );
+    // variables of a context are not visible outside of the context
+    await assertRejects(() => runtime.execute("x"));
+
+    assertEquals(runtime.inspectContext(a).declared_variables, ["x"]);
+    assertEquals(runtime.inspectContext(b).declared_variables, []);
+    assertEquals(runtime.inspectContext(b).execution_count, 0);
+    assertEquals(runtime.inspectContext(b).failure_count, 1);
+});
+
+Deno.test("execution context reset and dispose", async () => {
+    const runtime = await Runtime.create({ endpoint: "@jonas" });
+    const context = runtime.createContext();
+    await runtime.executeWithOptions("const x = 1", [], { context });
+
+    runtime.resetContext(context);
+    assertEquals(runtime.inspectContext(context).declared_variables, []);
+    await assertRejects(() => runtime.executeWithOptions("x", [], { context }));
+
+    runtime.disposeContext(context);
+    assertEquals(runtime.listContexts(), []);
+    await assertRejects(() => runtime.executeWithOptions("1", [], { context }));
+});
+
+Deno.test("execute precompiled DXB in execution context", async () => {
+    const runtime = await Runtime.create({ endpoint: "@jonas" });
+    const context = runtime.createContext();
+    await runtime.executeWithOptions("const x = 40", [], { context });
+
+    const { body, errors } = runtime.compile("1 + 1");
+    assertEquals(errors, []);
+    const result = await runtime.executeDXB<number>(body!, [], { context });
+    assertEquals(result, 2);
+    assertEquals(runtime.inspectContext(context).execution_count, 2);
+});
@@ -0,0 +1,11 @@
+import { assertEquals } from "@std/assert";
+import { Runtime } from "../../src/runtime/runtime.ts";
+
+Deno.test("runtime is not poisoned without a panic", async () => {
+    const runtimeA = await Runtime.create({ endpoint: "@jonas" });
+    const runtimeB = await Runtime.create({ endpoint: "@ben" });
+    runtimeA.setOnFatalError(() => {});
+    assertEquals(runtimeA.isPoisoned, false);
+    assertEquals(runtimeB.isPoisoned, false);
+    runtimeA.setOnFatalError(null);
+});
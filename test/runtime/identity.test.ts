@@ -0,0 +1,93 @@
+import { assert, assertEquals, assertRejects } from "@std/assert";
+import { Runtime } from "../../src/runtime/runtime.ts";
+import { isNodeOrBun } from "../is-node.ts";
+
+const PASSWORD = "9At2nzU19GjL8F4WFRyB7RZSGLemMGUMVBZAMChfndF2";
+
+Deno.test("export and import an identity", async () => {
+    // FIXME: temporarily disabled because of crypto problems with node.js
+    if (isNodeOrBun) return;
+    const runtimeA = await Runtime.create({ endpoint: "@jonas" });
+    const keys = await runtimeA.generateIdentity();
+    const exported = await runtimeA.exportIdentity(PASSWORD);
+
+    const runtimeB = await Runtime.create({ endpoint: "@jonas" });
+    await runtimeB.importIdentity(exported, PASSWORD);
+    assertEquals(runtimeB.publicKeys, keys);
+});
+
+Deno.test("importing an identity with a wrong password fails", async () => {
+    // FIXME: temporarily disabled because of crypto problems with node.js
+    if (isNodeOrBun) return;
+    const runtimeA = await Runtime.create({ endpoint: "@jonas" });
+    await runtimeA.generateIdentity();
+    const exported = await runtimeA.exportIdentity(PASSWORD);
+
+    const runtimeB = await Runtime.create({ endpoint: "@jonas" });
+    const error = await assertRejects(() => runtimeB.importIdentity(exported, "wrong password")) as {
+        code: string;
+    };
+    assertEquals(error.code, "InvalidPassword");
+    assertEquals(runtimeB.publicKeys, { signing_key: null, encryption_key: null });
+});
+
+Deno.test("empty passwords are rejected", async () => {
+    // FIXME: temporarily disabled because of crypto problems with node.js
+    if (isNodeOrBun) return;
+    const runtimeA = await Runtime.create({ endpoint: "@jonas" });
+    await runtimeA.generateIdentity();
+    const exportError = await assertRejects(() => runtimeA.exportIdentity("")) as { code: string };
+    assertEquals(exportError.code, "EmptyPassword");
+
+    const exported = await runtimeA.exportIdentity(PASSWORD);
+    const runtimeB = await Runtime.create({ endpoint: "@jonas" });
+    const importError = await assertRejects(() => runtimeB.importIdentity(exported, "")) as { code: string };
+    assertEquals(importError.code, "EmptyPassword");
+});
+
+Deno.test("importing a modified identity fails", async () => {
+    // FIXME: temporarily disabled because of crypto problems with node.js
+    if (isNodeOrBun) return;
+    const runtimeA = await Runtime.create({ endpoint: "@jonas" });
+    await runtimeA.generateIdentity();
+    const exported = await runtimeA.exportIdentity(PASSWORD);
+
+    // flip a bit in the encrypted identity, before the MAC
+    const modified = exported.slice();
+    modified[modified.length - 40] ^= 0x01;
+    const runtimeB = await Runtime.create({ endpoint: "@jonas" });
+    const error = await assertRejects(() => runtimeB.importIdentity(modified, PASSWORD)) as { code: string };
+    assertEquals(error.code, "InvalidIdentity");
+});
+
+Deno.test("identities of other endpoints can not be imported", async () => {
+    // FIXME: temporarily disabled because of crypto problems with node.js
+    if (isNodeOrBun) return;
+    const runtimeA = await Runtime.create({ endpoint: "@jonas" });
+    await runtimeA.generateIdentity();
+    const exported = await runtimeA.exportIdentity(PASSWORD);
+
+    const runtimeB = await Runtime.create({ endpoint: "@ben" });
+    const error = await assertRejects(() => runtimeB.importIdentity(exported, PASSWORD)) as { code: string };
+    assertEquals(error.code, "EndpointMismatch");
+});
+
+Deno.test("identity storage persists the identity", async () => {
+    // FIXME: temporarily disabled because of crypto problems with node.js
+    if (isNodeOrBun) return;
+    const data = new Map<string, Uint8Array>();
+    const storage = {
+        load: (endpoint: string) => data.get(endpoint) ?? null,
+        save: (endpoint: string, bytes: Uint8Array) => {
+            data.set(endpoint, bytes);
+        },
+    };
+    const runtimeA = await Runtime.create({ endpoint: "@jonas" });
+    assertEquals(await runtimeA.setIdentityStorage(storage, PASSWORD), false);
+    const keys = await runtimeA.generateIdentity();
+    assert(data.has("@jonas"));
+
+    const runtimeB = await Runtime.create({ endpoint: "@jonas" });
+    assertEquals(await runtimeB.setIdentityStorage(storage, PASSWORD), true);
+    assertEquals(runtimeB.publicKeys, keys);
+});
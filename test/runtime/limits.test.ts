@@ -0,0 +1,45 @@
+import { assertEquals, assertRejects } from "@std/assert";
+import { Runtime } from "../../src/runtime/runtime.ts";
+import { Repl } from "../../src/repl/mod.ts";
+
+// a script that takes well over a millisecond to compile and execute
+const longScript = "[" + Array(100_000).fill("1").join(",") + "]";
+
+Deno.test("time limit cuts off waiting execution", async () => {
+    const runtime = await Runtime.create({ endpoint: "@jonas" });
+    // the remote execution waits for a response that never arrives
+    const error = await assertRejects(() =>
+        runtime.executeWithOptions("@unreachable :: 1 + 2", [], {
+            maxTimeMs: 50,
+        })
+    );
+    assertEquals((error as { code: string }).code, "TimeLimitExceeded");
+});
+
+Deno.test("time limit rejects long running script", async () => {
+    const runtime = await Runtime.create({ endpoint: "@jonas" });
+    const error = await assertRejects(() =>
+        runtime.executeWithOptions(longScript, [], { maxTimeMs: 1 })
+    );
+    assertEquals((error as { code: string }).code, "TimeLimitExceeded");
+    assertEquals((error as { kind: string }).kind, "execution");
+
+    // without a limit, the same script succeeds
+    const result = await runtime.executeWithOptions<number[]>(
+        longScript,
+        [],
+        {},
+    );
+    assertEquals(result.length, 100_000);
+});
+
+Deno.test("repl time limit", async () => {
+    const runtime = await Runtime.create({ endpoint: "@jonas" });
+    const repl = new Repl(runtime);
+    repl.setMaxTimeMs(1);
+    const error = await assertRejects(() => repl.execute(longScript));
+    assertEquals((error as { code: string }).code, "TimeLimitExceeded");
+
+    repl.setMaxTimeMs(null);
+    assertEquals(await repl.execute("1 + 2"), "3");
+});
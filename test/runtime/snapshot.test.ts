@@ -0,0 +1,40 @@
+import { assertEquals, assertThrows } from "@std/assert";
+import { Runtime } from "../../src/runtime/runtime.ts";
+import { DIFSharedValueMutability } from "../../src/dif/definitions.ts";
+
+Deno.test("snapshot round trip restores pointers at their addresses", async () => {
+    const runtimeA = await Runtime.create({ endpoint: "@jonas" });
+    const text = runtimeA.dif.createSharedValueFromDIFValue(
+        { value: "Hello" },
+        undefined,
+        DIFSharedValueMutability.Mutable,
+    );
+    // the array references the text pointer, so it is restored after it
+    const list = runtimeA.dif.createSharedValueFromDIFValue(
+        { value: [text, { value: 42 }] },
+        undefined,
+        DIFSharedValueMutability.Immutable,
+    );
+    const snapshot = runtimeA.snapshot();
+    const expectedText = runtimeA.executeSyncWithStringResult(`'$${text}`);
+    const expectedList = runtimeA.executeSyncWithStringResult(`'$${list}`);
+    await runtimeA.close();
+
+    const runtimeB = await Runtime.create({ endpoint: "@jonas" });
+    const restored = runtimeB.restore(snapshot);
+    assertEquals(restored.includes(text), true);
+    assertEquals(restored.includes(list), true);
+    assertEquals(runtimeB.executeSyncWithStringResult(`'$${text}`), expectedText);
+    assertEquals(runtimeB.executeSyncWithStringResult(`'$${list}`), expectedList);
+    assertEquals(runtimeB.dif._handle.owns_pointer(list), true);
+
+    // the addresses are in use now
+    const error = assertThrows(() => runtimeB.restore(snapshot));
+    assertEquals((error as { code: string }).code, "AddressInUse");
+});
+
+Deno.test("restore rejects invalid snapshots", async () => {
+    const runtime = await Runtime.create({ endpoint: "@jonas" });
+    const error = assertThrows(() => runtime.restore(new Uint8Array([1, 2, 3])));
+    assertEquals((error as { code: string }).code, "InvalidSnapshot");
+});